@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE match_records DROP COLUMN first_mover;
\ No newline at end of file
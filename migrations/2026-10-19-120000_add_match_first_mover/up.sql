@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- Matches recorded before this column existed were always started by the player
+ALTER TABLE match_records ADD COLUMN first_mover INTEGER NOT NULL DEFAULT 1 CHECK (first_mover == 1 OR first_mover == 2);
\ No newline at end of file
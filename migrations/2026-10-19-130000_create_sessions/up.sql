@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE sessions (
+    session_id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    last_seen DATETIME NOT NULL,
+    expires_at DATETIME NOT NULL,
+    user_agent TEXT,
+    FOREIGN KEY(user_id) REFERENCES users(user_id)
+);
+CREATE INDEX sessions_user_id ON sessions(user_id);
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX users_user_key;
+ALTER TABLE users DROP COLUMN user_key;
\ No newline at end of file
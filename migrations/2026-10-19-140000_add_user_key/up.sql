@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Keys are filled in by queries::users::backfill_keys after migrating since SQLite can't normalise Unicode
+ALTER TABLE users ADD COLUMN user_key TEXT;
+CREATE UNIQUE INDEX users_user_key ON users(user_key);
\ No newline at end of file
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+CREATE TABLE user_lockouts (
+    user_key TEXT PRIMARY KEY NOT NULL,
+    failures INTEGER NOT NULL,
+    last_failure DATETIME NOT NULL,
+    locked_until DATETIME
+);
\ No newline at end of file
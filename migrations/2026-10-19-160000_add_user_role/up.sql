@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN role INTEGER NOT NULL DEFAULT 1 CHECK (role == 1 OR role == 2);
\ No newline at end of file
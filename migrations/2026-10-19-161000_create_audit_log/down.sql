@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX audit_log_performed_at;
+DROP TABLE audit_log;
\ No newline at end of file
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY,
+    admin_id TEXT NOT NULL,
+    action TEXT NOT NULL,
+    target TEXT NOT NULL,
+    performed_at DATETIME NOT NULL
+);
+CREATE INDEX audit_log_performed_at ON audit_log(performed_at);
\ No newline at end of file
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE api_tokens (
+    id INTEGER PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    scopes INTEGER NOT NULL,
+    created_at DATETIME NOT NULL,
+    last_used DATETIME,
+    UNIQUE (user_id, name),
+    FOREIGN KEY(user_id) REFERENCES users(user_id)
+);
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE match_records DROP COLUMN rows;
+ALTER TABLE match_records DROP COLUMN columns;
\ No newline at end of file
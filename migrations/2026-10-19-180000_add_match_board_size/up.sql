@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Board sizes were not recorded before these columns existed, so older matches are left NULL (unknown size)
+ALTER TABLE match_records ADD COLUMN columns INTEGER;
+ALTER TABLE match_records ADD COLUMN rows INTEGER;
\ No newline at end of file
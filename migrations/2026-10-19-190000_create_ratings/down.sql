@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX rating_history_user_game;
+DROP TABLE rating_history;
+DROP TABLE ratings;
\ No newline at end of file
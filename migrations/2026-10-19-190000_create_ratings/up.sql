@@ -0,0 +1,19 @@
+-- Your SQL goes here
+CREATE TABLE ratings (
+    user_id TEXT NOT NULL,
+    game_id INTEGER NOT NULL CHECK (game_id == 1 OR game_id == 2),
+    rating DOUBLE NOT NULL,
+    matches INTEGER NOT NULL,
+    updated_at DATETIME NOT NULL,
+    PRIMARY KEY (user_id, game_id),
+    FOREIGN KEY(user_id) REFERENCES users(user_id)
+);
+CREATE TABLE rating_history (
+    id INTEGER PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    game_id INTEGER NOT NULL CHECK (game_id == 1 OR game_id == 2),
+    rating DOUBLE NOT NULL,
+    recorded_at DATETIME NOT NULL,
+    FOREIGN KEY(user_id) REFERENCES users(user_id)
+);
+CREATE INDEX rating_history_user_game ON rating_history(user_id, game_id);
\ No newline at end of file
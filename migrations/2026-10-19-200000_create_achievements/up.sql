@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE achievements (
+    user_id TEXT NOT NULL,
+    achievement INTEGER NOT NULL CHECK (achievement BETWEEN 1 AND 4),
+    unlocked_at DATETIME NOT NULL,
+    PRIMARY KEY (user_id, achievement),
+    FOREIGN KEY(user_id) REFERENCES users(user_id)
+);
\ No newline at end of file
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE puzzle_results;
\ No newline at end of file
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TABLE puzzle_results (
+    user_id TEXT NOT NULL,
+    day DATE NOT NULL,
+    started_at DATETIME NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    solved_at DATETIME,
+    PRIMARY KEY (user_id, day),
+    FOREIGN KEY(user_id) REFERENCES users(user_id)
+);
\ No newline at end of file
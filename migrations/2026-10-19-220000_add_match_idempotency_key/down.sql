@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX match_records_user_idempotency_key;
+ALTER TABLE match_records DROP COLUMN idempotency_key;
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Keys are chosen by clients, so they only have to be unique among the records of a user
+ALTER TABLE match_records ADD COLUMN idempotency_key TEXT;
+CREATE UNIQUE INDEX match_records_user_idempotency_key ON match_records (user_id, idempotency_key);
\ No newline at end of file
@@ -0,0 +1,340 @@
+//! `arena` command: plays two AI configurations against each other to check their relative strength
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use shared_types::game::*;
+use shared_types::types::GameType;
+
+use super::{Options, VARIANTS};
+use crate::print_info;
+
+/// Usage text for the options of this command
+const USAGE: &str = "Options:
+    --first         AI configuration to test (default: medium)
+    --second        AI configuration to test against (default: easy)
+    --games         Number of games to play on each board size (default: 20)
+    --book          Let the AI use the opening book, true or false (default: true)
+    --format        Output format, text or json (default: text)
+    --min-elo       Fail if the first configuration isn't at least this much stronger (Elo)
+
+AI configurations are either easy, medium, hard or <depth>,<iterations>[,<book variety>]";
+
+/// Result of a single game from the first configuration's view
+#[derive(Clone, Copy)]
+enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// Results of the games played on a board size (or across all of them)
+#[derive(Default, Serialize)]
+struct Results {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    /// Average time taken per move by each configuration, in milliseconds
+    first_move_ms: f64,
+    second_move_ms: f64,
+    /// Estimated Elo difference (positive if the first configuration is stronger),
+    /// None if one side won every game
+    elo_difference: Option<f64>,
+    #[serde(skip)]
+    first_time: Duration,
+    #[serde(skip)]
+    first_moves: u32,
+    #[serde(skip)]
+    second_time: Duration,
+    #[serde(skip)]
+    second_moves: u32,
+}
+
+impl Results {
+    /// Add the outcome of a game
+    fn add(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Loss => self.losses += 1,
+        }
+    }
+
+    /// Add the results of another board size
+    fn merge(&mut self, other: &Results) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+        self.first_time += other.first_time;
+        self.first_moves += other.first_moves;
+        self.second_time += other.second_time;
+        self.second_moves += other.second_moves;
+    }
+
+    /// Calculate the averages and Elo difference once every game has been added
+    fn finish(&mut self) {
+        self.first_move_ms = average_ms(self.first_time, self.first_moves);
+        self.second_move_ms = average_ms(self.second_time, self.second_moves);
+        self.elo_difference = elo_difference(self.wins, self.draws, self.losses);
+    }
+}
+
+/// Results for a single board size
+#[derive(Serialize)]
+struct VariantReport {
+    game_type: GameType,
+    columns: usize,
+    rows: usize,
+    #[serde(flatten)]
+    results: Results,
+}
+
+/// Full report printed as JSON
+#[derive(Serialize)]
+struct Report {
+    first: AIConfiguration,
+    second: AIConfiguration,
+    games_per_variant: u32,
+    variants: Vec<VariantReport>,
+    total: Results,
+}
+
+/// Play every board size and report the results
+pub fn run(mut options: Options) -> Result<(), String> {
+    let first = parse_configuration(&options.get("first", "medium".to_string())?)?;
+    let second = parse_configuration(&options.get("second", "easy".to_string())?)?;
+    let games = options.get("games", 20)?;
+    let use_book = options.get("book", true)?;
+    let format = options.get("format", "text".to_string())?;
+    let min_elo: Option<f64> = options.get_optional("min-elo")?;
+
+    options.finish().map_err(|err| format!("{}\n\n{}", err, USAGE))?;
+
+    if format != "text" && format != "json" {
+        return Err(format!("Unknown format \"{}\"\n\n{}", format, USAGE));
+    }
+
+    let mut variants = Vec::new();
+    let mut total = Results::default();
+
+    for (game_type, columns, rows) in VARIANTS {
+        if format == "text" {
+            print_info(format!("Playing {} games of {:?} {}x{}...", games, game_type, columns, rows));
+        }
+
+        let mut results = Results::default();
+
+        for game in 0..games {
+            let outcome = play_game(game_type, columns, rows, first, second, first_player(game), use_book, &mut results);
+            results.add(outcome);
+        }
+
+        total.merge(&results);
+        results.finish();
+
+        variants.push(VariantReport { game_type, columns, rows, results });
+    }
+
+    total.finish();
+
+    let report = Report {
+        first,
+        second,
+        games_per_variant: games,
+        variants,
+        total,
+    };
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report).map_err(|err| err.to_string())?);
+    } else {
+        print_report(&report);
+    }
+
+    // Let CI fail when the first configuration isn't stronger by enough
+    if let Some(min_elo) = min_elo {
+        let elo = report.total.elo_difference
+            .unwrap_or(if report.total.wins > 0 { f64::INFINITY } else { f64::NEG_INFINITY });
+
+        if elo < min_elo {
+            return Err(format!("Elo difference {:.0} is below the minimum of {:.0}", elo, min_elo));
+        }
+    }
+
+    Ok(())
+}
+
+/// Player that moves first in a game, alternating so neither side gets the advantage
+fn first_player(game: u32) -> u32 {
+    if game % 2 == 0 { PLAYER_ID } else { AI_ID }
+}
+
+/// Estimated Elo difference from the first configuration's results (positive if it's stronger)
+/// 
+/// None if no games were played or one side won every game, since the score can't be converted
+fn elo_difference(wins: u32, draws: u32, losses: u32) -> Option<f64> {
+    let games = (wins + draws + losses) as f64;
+    let score = (wins as f64 + draws as f64 / 2.0) / games;
+
+    if score > 0.0 && score < 1.0 {
+        Some(400.0 * (score / (1.0 - score)).log10())
+    } else {
+        None
+    }
+}
+
+/// Play a single game, the first configuration plays as [PLAYER_ID]
+#[allow(clippy::too_many_arguments)]
+fn play_game(
+    game_type: GameType,
+    columns: usize,
+    rows: usize,
+    first: AIConfiguration,
+    second: AIConfiguration,
+    first_player: u32,
+    use_book: bool,
+    results: &mut Results,
+) -> Outcome {
+    let mut board = Board::new(rows, columns, game_type, first, None, first_player);
+
+    if !use_book {
+        board.set_opening_book(None);
+    }
+
+    loop {
+        let player = if board.is_player_turn() { PLAYER_ID } else { AI_ID };
+
+        board.set_ai_configuration(if player == PLAYER_ID { first } else { second });
+
+        let start = Instant::now();
+        let possible_move = board.get_ai_move();
+        let elapsed = start.elapsed();
+
+        if player == PLAYER_ID {
+            results.first_time += elapsed;
+            results.first_moves += 1;
+        } else {
+            results.second_time += elapsed;
+            results.second_moves += 1;
+        }
+
+        board.place_at_column(possible_move, player);
+
+        match board.get_winner() {
+            Some(PLAYER_ID) => return Outcome::Win,
+            Some(_) => return Outcome::Loss,
+            None if board.check_if_no_more_moves() => return Outcome::Draw,
+            None => {}
+        }
+    }
+}
+
+/// Parse an AI configuration name or `<depth>,<iterations>[,<book variety>]`
+pub(super) fn parse_configuration(value: &str) -> Result<AIConfiguration, String> {
+    match value {
+        "easy" => Ok(AI_EASY),
+        "medium" => Ok(AI_MEDIUM),
+        "hard" => Ok(AI_HARD),
+        _ => {
+            let parts = value.split(',')
+                .map(|part| part.trim().parse::<u32>())
+                .collect::<Result<Vec<u32>, _>>()
+                .map_err(|_| format!("Invalid AI configuration \"{}\"", value))?;
+
+            match parts[..] {
+                [depth, iterations] => Ok(AIConfiguration::new(depth, iterations, 0)),
+                [depth, iterations, variety] => Ok(AIConfiguration::new(depth, iterations, variety)),
+                _ => Err(format!("Invalid AI configuration \"{}\"", value))
+            }
+        }
+    }
+}
+
+/// Average time in milliseconds
+fn average_ms(time: Duration, moves: u32) -> f64 {
+    if moves == 0 {
+        0.0
+    } else {
+        time.as_secs_f64() * 1000.0 / moves as f64
+    }
+}
+
+/// Print the report as a table
+fn print_report(report: &Report) {
+    println!(
+        "\n{:<20}{:>6}{:>6}{:>6}{:>12}{:>12}{:>8}",
+        "Board", "Win", "Draw", "Loss", "1st ms", "2nd ms", "Elo"
+    );
+
+    let rows = report.variants.iter()
+        .map(|variant| (format!("{:?} {}x{}", variant.game_type, variant.columns, variant.rows), &variant.results))
+        .chain(std::iter::once(("Total".to_string(), &report.total)));
+
+    for (name, results) in rows {
+        println!(
+            "{:<20}{:>6}{:>6}{:>6}{:>12.2}{:>12.2}{:>8}",
+            name,
+            results.wins,
+            results.draws,
+            results.losses,
+            results.first_move_ms,
+            results.second_move_ms,
+            results.elo_difference.map_or("-".to_string(), |elo| format!("{:+.0}", elo)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tally(outcomes: &[Outcome]) -> Results {
+        let mut results = Results::default();
+        for outcome in outcomes {
+            results.add(*outcome);
+        }
+        results
+    }
+
+    #[test]
+    fn one_sided_results_have_no_estimate() {
+        assert_eq!(elo_difference(10, 0, 0), None);
+        assert_eq!(elo_difference(0, 0, 10), None);
+        assert_eq!(elo_difference(0, 0, 0), None);
+    }
+
+    #[test]
+    fn even_results_are_equal_strength() {
+        assert_eq!(elo_difference(0, 10, 0), Some(0.0));
+        assert_eq!(elo_difference(5, 0, 5), Some(0.0));
+        assert_eq!(elo_difference(3, 4, 3), Some(0.0));
+    }
+
+    #[test]
+    fn estimate_follows_the_score() {
+        // Scoring 75% is 400 * log10(3) stronger
+        let elo = elo_difference(3, 0, 1).unwrap();
+        assert!((elo - 190.85).abs() < 0.01, "{}", elo);
+        assert_eq!(elo_difference(2, 2, 0), Some(elo));
+        assert_eq!(elo_difference(1, 0, 3), Some(-elo));
+    }
+
+    #[test]
+    fn outcomes_are_tallied() {
+        let mut results = tally(&[Outcome::Win, Outcome::Loss, Outcome::Win, Outcome::Draw]);
+        assert_eq!((results.wins, results.draws, results.losses), (2, 1, 1));
+
+        results.merge(&tally(&[Outcome::Loss, Outcome::Draw]));
+        assert_eq!((results.wins, results.draws, results.losses), (2, 2, 2));
+
+        results.finish();
+        assert_eq!(results.elo_difference, Some(0.0));
+    }
+
+    #[test]
+    fn first_mover_alternates() {
+        let movers: Vec<u32> = (0..4).map(first_player).collect();
+        assert_eq!(movers, vec![PLAYER_ID, AI_ID, PLAYER_ID, AI_ID]);
+    }
+}
@@ -0,0 +1,109 @@
+//! `book` command: generates the opening book bundled with the game
+
+use std::collections::HashSet;
+use std::fs;
+
+use shared_types::game::*;
+
+use super::{Options, VARIANTS};
+use crate::print_info;
+
+/// Usage text for the options of this command
+const USAGE: &str = "Options:
+    --plies         Number of moves deep to store positions for (default: 3)
+    --depth         Search depth used to score moves (default: 4)
+    --iterations    Random search iterations used to score moves (default: 2000)
+    --keep          Number of moves stored per position (default: 3)
+    --output        File to write the book to (default: shared_types/src/game/openings.book)";
+
+/// Highest weight given to a book move
+const MAX_WEIGHT: i64 = 100;
+
+/// Generate the opening book by scoring every position up to the given
+/// number of plies with a deeper search than the AI can afford in game
+pub fn run(mut options: Options) -> Result<(), String> {
+    let plies = options.get("plies", 3)?;
+    let depth = options.get("depth", 4)?;
+    let iterations = options.get("iterations", 2000)?;
+    let keep = options.get("keep", 3)?;
+    let output = options.get("output", "shared_types/src/game/openings.book".to_string())?;
+
+    options.finish().map_err(|err| format!("{}\n\n{}", err, USAGE))?;
+
+    let ai = AIConfiguration::new(depth, iterations, 0);
+    let mut book = OpeningBook::new();
+
+    for (game_type, columns, rows) in VARIANTS {
+        print_info(format!("Solving {:?} {}x{} openings...", game_type, columns, rows));
+
+        let mut seen = HashSet::new();
+
+        // Positions are hashed from the view of the player to move,
+        // so both players need to start to cover every position
+        for first_player in [PLAYER_ID, AI_ID] {
+            let mut board = Board::new(rows, columns, game_type, ai, None, first_player);
+            board.set_opening_book(None);
+
+            add_positions(&mut board, plies, keep, &mut book, &mut seen);
+        }
+    }
+
+    let contents = format!(
+        "# Opening book (see OpeningBook for the format)\n\
+        # Generated by `prj3_cli book --plies {} --depth {} --iterations {} --keep {}`\n{}",
+        plies, depth, iterations, keep, book
+    );
+
+    fs::write(&output, contents)
+        .map_err(|err| format!("Unable to write {}: {}", output, err))?;
+
+    print_info(format!("Wrote {} positions to {}", book.len(), output));
+
+    Ok(())
+}
+
+/// Add the current position and every position reachable within the given plies to the book
+fn add_positions(board: &mut Board, plies: u32, keep: usize, book: &mut OpeningBook, seen: &mut HashSet<u64>) {
+    if plies == 0 || board.get_winner().is_some() || board.check_if_no_more_moves() {
+        return;
+    }
+
+    if !seen.insert(board.position_hash()) {
+        return;
+    }
+
+    let scores = board.score_moves();
+    book.insert(board.position_hash(), weigh_moves(scores, keep));
+
+    let player = if board.is_player_turn() { PLAYER_ID } else { AI_ID };
+
+    for possible_move in board.get_possible_moves() {
+        board.place_at_column(possible_move, player);
+        add_positions(board, plies - 1, keep, book, seen);
+        board.undo_move();
+    }
+}
+
+/// Turn move scores into book weights, keeping only the best moves.
+/// Weights are scaled between the worst and best score so the best move
+/// gets [MAX_WEIGHT] and clearly worse moves get little or no weight.
+fn weigh_moves(mut scores: Vec<(PossibleMove, i32)>, keep: usize) -> Vec<BookMove> {
+    scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+    let best = scores.first().map_or(0, |(_, score)| *score as i64);
+    let worst = scores.last().map_or(0, |(_, score)| *score as i64);
+
+    scores.into_iter()
+        .take(keep)
+        .map(|(possible_move, score)| BookMove {
+            column: possible_move.column,
+            letter: possible_move.letter,
+            weight: if best == worst {
+                MAX_WEIGHT
+            } else {
+                (score as i64 - worst) * MAX_WEIGHT / (best - worst)
+            } as u32,
+        })
+        .filter(|book_move| book_move.weight > 0)
+        .collect()
+}
@@ -0,0 +1,92 @@
+//! Commands that can be run directly from the command line instead of the menus
+//! (ex: `prj3_cli book --plies 3`)
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use shared_types::types::GameType;
+
+mod arena;
+mod book;
+mod puzzles;
+
+/// Board sizes offered by the client for each game (columns, rows)
+const VARIANTS: [(GameType, usize, usize); 4] = [
+    (GameType::Connect4, 7, 6),
+    (GameType::Connect4, 5, 4),
+    (GameType::OttoToot, 6, 4),
+    (GameType::OttoToot, 7, 7),
+];
+
+/// Usage text printed for `prj3_cli help`
+const USAGE: &str = "Usage: prj3_cli [COMMAND] [OPTIONS]
+
+Runs the interactive admin console when no command is given.
+
+Commands:
+    arena   Play two AI configurations against each other
+    book    Generate the AI opening book
+    puzzles Generate the daily puzzles
+    help    Print this message";
+
+/// Options given to a command as `--name value` pairs
+pub struct Options {
+    values: HashMap<String, String>,
+}
+
+impl Options {
+    /// Parse `--name value` pairs
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let name = arg.strip_prefix("--")
+                .ok_or(format!("Unexpected argument \"{}\"", arg))?;
+
+            let value = args.next()
+                .ok_or(format!("Missing value for option \"{}\"", arg))?;
+
+            values.insert(name.to_string(), value.clone());
+        }
+
+        Ok(Self { values })
+    }
+
+    /// Get the value of an option or the default if it wasn't given
+    pub fn get<T: FromStr>(&mut self, name: &str, default: T) -> Result<T, String> {
+        Ok(self.get_optional(name)?.unwrap_or(default))
+    }
+
+    /// Get the value of an option if it was given
+    pub fn get_optional<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, String> {
+        match self.values.remove(name) {
+            Some(value) => value.parse()
+                .map(Some)
+                .map_err(|_| format!("Invalid value \"{}\" for option \"--{}\"", value, name)),
+            None => Ok(None)
+        }
+    }
+
+    /// Make sure every option given was used by the command
+    pub fn finish(self) -> Result<(), String> {
+        match self.values.keys().next() {
+            Some(name) => Err(format!("Unknown option \"--{}\"", name)),
+            None => Ok(())
+        }
+    }
+}
+
+/// Run the command given on the command line
+pub fn run(command: &str, args: &[String]) -> Result<(), String> {
+    match command {
+        "arena" => arena::run(Options::parse(args)?),
+        "book" => book::run(Options::parse(args)?),
+        "puzzles" => puzzles::run(Options::parse(args)?),
+        "help" | "--help" => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        _ => Err(format!("Unknown command \"{}\"\n\n{}", command, USAGE))
+    }
+}
@@ -0,0 +1,137 @@
+//! `puzzles` command: generates the daily puzzles bundled with the game
+
+use std::collections::HashSet;
+use std::fs;
+
+use shared_types::game::*;
+
+use super::arena::parse_configuration;
+use super::{Options, VARIANTS};
+use crate::print_info;
+
+/// Usage text for the options of this command
+const USAGE: &str = "Options:
+    --count         Number of puzzles generated for each board size (default: 8)
+    --min-win-in    Fewest moves a puzzle can be won in (default: 2)
+    --max-win-in    Most moves a puzzle can be won in (default: 3)
+    --setup         Fewest moves played before a puzzle starts (default: 6)
+    --games         Most games played on each board size to find puzzles (default: 200)
+    --ai            AI configuration that plays the games (default: medium)
+    --output        File to write the puzzles to (default: shared_types/src/game/puzzles.txt)
+
+AI configurations are either easy, medium, hard or <depth>,<iterations>[,<book variety>]";
+
+/// Generate puzzles by having the AI play itself and keeping the positions
+/// where the player to move can force a win
+pub fn run(mut options: Options) -> Result<(), String> {
+    let count = options.get("count", 8)?;
+    let min_win_in = options.get("min-win-in", 2)?;
+    let max_win_in = options.get("max-win-in", 3)?;
+    let setup = options.get("setup", 6)?;
+    let games = options.get("games", 200)?;
+    let ai = options.get("ai", "medium".to_string())?;
+    let output = options.get("output", "shared_types/src/game/puzzles.txt".to_string())?;
+
+    options.finish().map_err(|err| format!("{}\n\n{}", err, USAGE))?;
+
+    if min_win_in == 0 || min_win_in > max_win_in {
+        return Err(format!("--min-win-in has to be between 1 and --max-win-in\n\n{}", USAGE));
+    }
+
+    let configuration = parse_configuration(&ai)?;
+    let mut variants = Vec::new();
+
+    for (game_type, columns, rows) in VARIANTS {
+        print_info(format!("Finding {:?} {}x{} puzzles...", game_type, columns, rows));
+
+        let mut puzzles = Vec::new();
+        let mut seen = HashSet::new();
+
+        for game in 0..games {
+            if puzzles.len() >= count {
+                break;
+            }
+
+            // Alternate who starts so puzzles come up with either parity
+            let first_player = if game % 2 == 0 { PLAYER_ID } else { AI_ID };
+            let mut board = Board::new(rows, columns, game_type, configuration, None, first_player);
+
+            if let Some(puzzle) = find_puzzle(&mut board, setup, min_win_in, max_win_in, &mut seen) {
+                print_info(format!("Found {}", puzzle));
+                puzzles.push(puzzle);
+            }
+        }
+
+        if puzzles.len() < count {
+            print_info(format!("Only found {} puzzles after {} games", puzzles.len(), games));
+        }
+
+        variants.push(puzzles);
+    }
+
+    // Interleave the board sizes so consecutive days get different games
+    let mut contents = format!(
+        "# Daily puzzles (see Puzzle for the format)\n\
+        # Generated by `prj3_cli puzzles --count {} --min-win-in {} --max-win-in {} --setup {} --games {} --ai {}`\n",
+        count, min_win_in, max_win_in, setup, games, ai
+    );
+    let mut total = 0;
+
+    for i in 0..count {
+        for puzzles in &variants {
+            if let Some(puzzle) = puzzles.get(i) {
+                contents.push_str(&format!("{}\n", puzzle));
+                total += 1;
+            }
+        }
+    }
+
+    if total == 0 {
+        return Err("No puzzles found, try playing more games".into());
+    }
+
+    fs::write(&output, contents)
+        .map_err(|err| format!("Unable to write {}: {}", output, err))?;
+
+    print_info(format!("Wrote {} puzzles to {}", total, output));
+
+    Ok(())
+}
+
+/// Play a game between two copies of the AI until a position comes up where
+/// the player to move can force a win within the given number of moves
+fn find_puzzle(
+    board: &mut Board,
+    setup: usize,
+    min_win_in: u32,
+    max_win_in: u32,
+    seen: &mut HashSet<u64>
+) -> Option<Puzzle> {
+    let mut played = Vec::new();
+
+    loop {
+        if board.get_winner().is_some() || board.check_if_no_more_moves() {
+            return None;
+        }
+
+        if board.is_player_turn() && played.len() >= setup && seen.insert(board.position_hash()) {
+            let moves = moves_to_force_win(board, max_win_in).filter(|moves| *moves >= min_win_in);
+
+            if let Some(win_in) = moves {
+                return Some(Puzzle {
+                    game_type: board.game_type,
+                    columns: board.columns,
+                    rows: board.rows,
+                    win_in,
+                    setup: played,
+                });
+            }
+        }
+
+        let player = if board.is_player_turn() { PLAYER_ID } else { AI_ID };
+        let possible_move = board.get_ai_move();
+
+        board.place_at_column(possible_move, player);
+        played.push(possible_move);
+    }
+}
\ No newline at end of file
@@ -9,6 +9,7 @@ use shared_types::models::*;
 use types::*;
 use menus::*;
 
+mod commands;
 mod types;
 mod menus;
 
@@ -23,6 +24,18 @@ fn print_err(input: String) {
 }
 
 fn main() {
+    // Run a single command instead of the menus if one was given
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Some((command, args)) = args.split_first() {
+        if let Err(err) = commands::run(command, args) {
+            print_err(err);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     println!("{}\n", Style::new().bold().paint("Project 3 Admin Console"));
 
     // Get database path
@@ -51,9 +64,12 @@ fn main() {
             Actions::AddUser => add_user(&conn),
             Actions::DeleteUser => delete_user(&conn),
             Actions::ListUsers => list_users(&conn),
+            Actions::SetUserRole => set_user_role(&conn),
             Actions::AddRecord => add_record(&conn),
             Actions::DeleteRecord => delete_record(&conn),
             Actions::ListUserRecords => list_user_records(&conn),
+            Actions::ListLockouts => list_lockouts(&conn),
+            Actions::ClearLockout => clear_lockout(&conn),
             Actions::Exit => break
         }
 
@@ -69,7 +85,7 @@ fn add_user(conn: &SqliteConnection) {
     let new_user = NewUserMenu::prompt_default();
     
     if let Some(NewUser { username, password }) = new_user {
-        match UserModel::generate_new(username, password) {
+        match UserModel::generate_new(username, password, &PasswordHashParams::default()) {
             Ok(model) => match users::add(conn, model) {
                 Ok(()) => print_info(format!("User added successfully")),
                 Err(err) => print_err(format!("{:?}", err))
@@ -123,13 +139,32 @@ fn list_users(conn: &SqliteConnection) {
 
 }
 
+/// Change the role of a user (admins can use the server's admin API)
+fn set_user_role(conn: &SqliteConnection) {
+    if let Some((id, role)) = UserIdMenu::prompt_default().and_then(|id| UserRoleMenu::prompt_default().map(|role| (id, role))) {
+        match users::find_by_id(conn, &id) {
+            Ok(Some(user)) => match users::update_role(conn, &user.user_id, role) {
+                Ok(_) => print_info(format!("User {} is now {:?}", user.user_id, role)),
+                Err(err) => print_err(format!("{:?}", err))
+            },
+            Ok(None) => print_err(format!("User {} does not exist", id)),
+            Err(err) => print_err(format!("{:?}", err))
+        }
+    }
+}
+
 /// Add a new record
 fn add_record(conn: &SqliteConnection) {
     use shared_types::types::UserAuthToken;
 
     if let Some(record) = UserIdMenu::prompt_default().and_then(|id| MatchRecordMenu::prompt_default().map(|record| (UserAuthToken::from(id), record))) {
         match match_records::add(conn, MatchRecordModel::from(record)) {
-            Ok(()) => print_info(format!("Record added successfully")),
+            Ok(unlocked) => {
+                print_info(String::from("Record added successfully"));
+                for model in unlocked {
+                    print_info(format!("Unlocked achievement {}", shared_types::achievements::info(model.achievement()).name));
+                }
+            },
             Err(err) => print_err(format!("{:?}", err))
         }
     }
@@ -139,21 +174,34 @@ fn add_record(conn: &SqliteConnection) {
 fn delete_record(conn: &SqliteConnection) {
     if let Some(id) = MatchRecordIdMenu::prompt_default() {
         match match_records::delete(conn, id) {
-            Ok(()) => print_info(format!("Record deleted successfully")),
+            Ok(0) => print_info(format!("Record {} does not exist", id)),
+            Ok(_) => print_info(String::from("Record deleted successfully")),
             Err(err) => print_err(format!("{:?}", err))
         }
     }
 }
 
 /// List user records
+/// 
+/// Pages with cursors, keeping the cursors of the previous pages to go back with
+/// and only counting the records once
 fn list_user_records(conn: &SqliteConnection) {
     use shared_types::types::MatchQuerySortBy;
 
     if let Some(id) = UserIdMenu::prompt_default() {
         let mut offset = 0;
+        let mut total = None;
+        let mut cursors = Vec::new();
         let step = 10;
         loop {
-            let records = match match_records::find_by_user(
+            let page = match_records::PageOptions {
+                cursor: cursors.last().copied(),
+                offset: 0,
+                limit: step,
+                count: total.is_none()
+            };
+
+            let (records, next_cursor) = match match_records::find_by_user(
                 conn,
                 &id,
                 None,
@@ -161,15 +209,20 @@ fn list_user_records(conn: &SqliteConnection) {
                 false,
                 None,
                 None,
-                step,
-                offset
+                page
             ) {
-                Ok((records, total)) => PartialList {
-                    items: records.into_iter()
-                        .map(|model| (model.get_id().unwrap(), model.as_record()))
-                        .collect(),
-                    offset,
-                    total
+                Ok((records, count, next_cursor)) => {
+                    total = total.or(count);
+                    (
+                        PartialList {
+                            items: records.into_iter()
+                                .map(|model| (model.get_id().unwrap(), model.as_record()))
+                                .collect(),
+                            offset,
+                            total: total.unwrap_or(0)
+                        },
+                        next_cursor
+                    )
                 },
                 Err(err) => {
                     eprintln!("{:?}", err);
@@ -178,10 +231,57 @@ fn list_user_records(conn: &SqliteConnection) {
             };
 
             match ListRecordsMenu::prompt(&records) {
-                Some(ListNav::Next) => offset += step,
-                Some(ListNav::Prev) => offset -= step,
+                Some(ListNav::Next) => match next_cursor {
+                    Some(cursor) => {
+                        cursors.push(cursor);
+                        offset += step;
+                    },
+                    None => break
+                },
+                Some(ListNav::Prev) => {
+                    cursors.pop();
+                    offset -= step;
+                },
                 None => break
             }
         }
     }
 }
+
+/// List usernames with failed logins
+fn list_lockouts(conn: &SqliteConnection) {
+    let mut offset = 0;
+    let step = 10;
+    loop {
+        let list = match lockouts::find_all(conn, step, offset) {
+            Ok((items, total)) => PartialList {
+                items,
+                offset,
+                total
+            },
+            Err(err) => {
+                print_err(format!("{:?}", err));
+                break;
+            }
+        };
+
+        match ListLockoutsMenu::prompt(&list) {
+            Some(ListNav::Next) => offset += step,
+            Some(ListNav::Prev) => offset -= step,
+            None => break
+        }
+    }
+}
+
+/// Clear the failed logins of a username (unlocking it)
+fn clear_lockout(conn: &SqliteConnection) {
+    use shared_types::validation::username_key;
+
+    if let Some(id) = UserIdMenu::prompt_default() {
+        match lockouts::delete(conn, &username_key(&id)) {
+            Ok(0) => print_info(format!("User {} has no failed logins", id)),
+            Ok(_) => print_info(String::from("Lockout cleared successfully")),
+            Err(err) => print_err(format!("{:?}", err))
+        }
+    }
+}
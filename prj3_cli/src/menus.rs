@@ -3,6 +3,8 @@ use std::str::FromStr;
 
 use terminal_menu::{TerminalMenuStruct, TerminalMenuItem};
 
+use shared_types::validation::*;
+
 /// Shorthand type for no error
 type NoErr = Option<Infallible>;
 /// Shorthand type for result return type
@@ -111,9 +113,12 @@ impl ToMenu for ActionMenu {
             terminal_menu::button("Add User"),
             terminal_menu::button("Delete User"),
             terminal_menu::button("List Users"),
+            terminal_menu::button("Set User Role"),
             terminal_menu::button("Add Record"),
             terminal_menu::button("Delete Record"),
             terminal_menu::button("List a User's Records"),
+            terminal_menu::button("List Login Lockouts"),
+            terminal_menu::button("Clear Login Lockout"),
             terminal_menu::button("Exit")
         ]
     }
@@ -129,9 +134,12 @@ impl FromMenu for ActionMenu {
             "Add User" => Actions::AddUser,
             "Delete User" => Actions::DeleteUser,
             "List Users" => Actions::ListUsers,
+            "Set User Role" => Actions::SetUserRole,
             "Add Record" => Actions::AddRecord,
             "Delete Record" => Actions::DeleteRecord,
             "List a User's Records" => Actions::ListUserRecords,
+            "List Login Lockouts" => Actions::ListLockouts,
+            "Clear Login Lockout" => Actions::ClearLockout,
             "Exit" => Actions::Exit,
             val => panic!("Invalid Action value: {}", val)
         };
@@ -144,28 +152,32 @@ impl FromMenu for ActionMenu {
 /// Create a new user menu
 pub struct NewUserMenu;
 
-pub struct NewUserMenuError {
-    pub username_empty: bool,
-    pub password_empty: bool
-}
+pub struct NewUserMenuError(ValidationErrors);
 
 impl ToMenu for NewUserMenu {
     type Params = ();
 
     fn to_menu(_: &Self::Params, prev_err: Option<NewUserMenuError>) -> Vec<TerminalMenuItem> {
 
-        let NewUserMenuError { username_empty, password_empty } = prev_err.unwrap_or(NewUserMenuError { username_empty: false, password_empty: false });
+        let NewUserMenuError(errors) = prev_err.unwrap_or(NewUserMenuError(ValidationErrors::default()));
+
+        let username_error = errors.get(USERNAME_FIELD);
+        let password_error = errors.get(PASSWORD_FIELD);
 
         let mut menu = vec![
             terminal_menu::label("Register New User"),
-            required_field(terminal_menu::string("Username", "", false), username_empty),
-            required_field(terminal_menu::string("Password", "", false), password_empty),
+            required_field(terminal_menu::string("Username", "", false), username_error.is_some()),
+            required_field(terminal_menu::string("Password", "", false), password_error.is_some()),
             terminal_menu::button("Confirm"),
             terminal_menu::button("Cancel")
         ];
 
-        if username_empty || password_empty {
-            menu.push(terminal_menu::label(error_text("Username and password must not be empty!")))
+        if let Some(err) = username_error {
+            menu.push(terminal_menu::label(error_text(&format!("Username {}!", err))))
+        }
+
+        if let Some(err) = password_error {
+            menu.push(terminal_menu::label(error_text(&format!("Password {}!", err))))
         }
 
         menu
@@ -184,13 +196,9 @@ impl FromMenu for NewUserMenu {
                 let username = String::from(menu.selection_value("Username"));
                 let password = String::from(menu.selection_value("Password"));
 
-                let username_empty = username.trim().is_empty();
-                let password_empty = password.trim().is_empty();
-
-                if username_empty || password_empty {
-                    Err(NewUserMenuError { username_empty, password_empty })
-                } else {
-                    Ok(Some(NewUser { username, password }))
+                match validate_new_user(&username, &password) {
+                    Ok(()) => Ok(Some(NewUser { username, password })),
+                    Err(errors) => Err(NewUserMenuError(errors))
                 }
             },
             "Cancel" => Ok(None),
@@ -238,6 +246,42 @@ impl FromMenu for UserIdMenu {
     }
 }
 
+/// Menu for choosing the role of a user
+pub struct UserRoleMenu;
+
+impl ToMenu for UserRoleMenu {
+    type Params = ();
+
+    fn to_menu(_: &Self::Params, _: NoErr) -> Vec<TerminalMenuItem> {
+        vec![
+            terminal_menu::list(
+                "Role", vec!["User", "Admin"]
+            ),
+            terminal_menu::button("Confirm"),
+            terminal_menu::button("Cancel")
+        ]
+    }
+}
+
+impl FromMenu for UserRoleMenu {
+    type Output = Option<shared_types::types::UserRole>;
+    type Error = Infallible;
+
+    fn from_menu(menu: &mut TerminalMenuStruct) -> MResult<Self> {
+        use shared_types::types::UserRole;
+
+        match menu.selected_item_name() {
+            "Confirm" => Ok(Some(match menu.selection_value("Role") {
+                "User" => UserRole::User,
+                "Admin" => UserRole::Admin,
+                val => panic!("Invalid Role value: {}", val)
+            })),
+            "Cancel" => Ok(None),
+            val => panic!("Invalid Action value: {}", val)
+        }
+    }
+}
+
 /// Menu for creating a new match record
 pub struct MatchRecordMenu;
 
@@ -283,6 +327,12 @@ impl ToMenu for MatchRecordMenu {
             terminal_menu::list(
                 "Result", vec!["Win", "Loss", "Tie"]
             ),
+            terminal_menu::list(
+                "First Move", vec!["Player", "CPU"]
+            ),
+            terminal_menu::list(
+                "Board Size", vec!["7x6", "5x4", "6x4", "7x7"]
+            ),
             terminal_menu::button("Submit"),
             terminal_menu::button("Cancel")
         ]
@@ -297,7 +347,7 @@ impl FromMenu for MatchRecordMenu {
         use chrono::{Utc, Local, TimeZone};
         use chrono::naive::{NaiveDateTime, NaiveDate, NaiveTime};
 
-        use shared_types::types::{MatchRecord, GameType, CpuLevel, MatchResult};
+        use shared_types::types::{MatchRecord, GameType, CpuLevel, MatchResult, FirstMover, BoardSize};
 
         match menu.selected_item_name() {
             "Submit" => {
@@ -339,6 +389,17 @@ impl FromMenu for MatchRecordMenu {
                     val => panic!("Invalid Result value: {}", val)
                 };
 
+                let first_mover = match menu.selection_value("First Move") {
+                    "Player" => FirstMover::Player,
+                    "CPU" => FirstMover::Cpu,
+                    val => panic!("Invalid First Move value: {}", val)
+                };
+
+                let board_size = match BoardSize::from_str(menu.selection_value("Board Size")) {
+                    Ok(size) => size,
+                    Err(_) => panic!("Invalid Board Size value: {}", menu.selection_value("Board Size"))
+                };
+
                 let moves = menu.numeric_value("Moves") as i32;
 
                 Ok(Some(MatchRecord {
@@ -347,7 +408,10 @@ impl FromMenu for MatchRecordMenu {
                     game_id,
                     cpu_level,
                     moves,
-                    result
+                    result,
+                    first_mover,
+                    columns: Some(board_size.columns),
+                    rows: Some(board_size.rows)
                 }))
             },
             "Cancel" => Ok(None),
@@ -414,25 +478,27 @@ impl ToMenu for ListRecordsMenu {
         menu.push(
             terminal_menu::label(
                 format!(
-                    "{:5} | {:8} | {:8} | {:8} | {:<5} | {:8} | {:8}",
+                    "{:5} | {:8} | {:8} | {:8} | {:<5} | {:8} | {:8} | {:6} | {:5}",
                     "ID",
                     "Date",
                     "Time",
                     "Game",
                     "Moves",
                     "Opponent",
-                    "Result"
+                    "Result",
+                    "First",
+                    "Board"
                 )
             )
         );
 
         menu.extend(list.items.iter().map(
             |(id, record)| {
-                use shared_types::types::{GameType, CpuLevel, MatchResult};
+                use shared_types::types::{GameType, CpuLevel, MatchResult, FirstMover};
 
                 terminal_menu::label(
                     format!(
-                        "{:<5} | {:8} | {:8} | {:8} | {:<5} | {:8} | {:8}",
+                        "{:<5} | {:8} | {:8} | {:8} | {:<5} | {:8} | {:8} | {:6} | {:5}",
                         id,
                         record.finished_at.with_timezone(&chrono::Local).format("%d/%m/%y"),
                         record.finished_at.with_timezone(&chrono::Local).format("%H:%M:%S"),
@@ -450,6 +516,14 @@ impl ToMenu for ListRecordsMenu {
                             MatchResult::Win => "Win",
                             MatchResult::Loss => "Loss",
                             MatchResult::Tie => "Tie"
+                        },
+                        match record.first_mover {
+                            FirstMover::Player => "Player",
+                            FirstMover::Cpu => "CPU"
+                        },
+                        match (record.columns, record.rows) {
+                            (Some(columns), Some(rows)) => format!("{}x{}", columns, rows),
+                            _ => String::from("Unknown")
                         }
                     )
                 )
@@ -548,3 +622,88 @@ impl FromMenu for ListUserMenu {
         }
     }
 }
+
+/// Menu for listing usernames with failed logins
+pub struct ListLockoutsMenu;
+
+impl ToMenu for ListLockoutsMenu {
+    type Params = crate::types::PartialList<shared_types::models::LockoutModel>;
+
+    fn to_menu(list: &crate::types::PartialList<shared_types::models::LockoutModel>, _: NoErr) -> Vec<TerminalMenuItem> {
+        use chrono::{DateTime, Local, NaiveDateTime, Utc};
+
+        let local_time = |time: &NaiveDateTime| {
+            DateTime::<Utc>::from_utc(*time, Utc)
+                .with_timezone(&Local)
+                .format("%d/%m/%y %H:%M:%S")
+                .to_string()
+        };
+        let now = Utc::now().naive_utc();
+
+        let mut menu = Vec::with_capacity(list.items.len() + 4);
+        menu.push(
+            terminal_menu::label(
+                format!(
+                    "{:32} | {:8} | {:17} | {:17}",
+                    "Username Key",
+                    "Failures",
+                    "Last Failure",
+                    "Locked Until"
+                )
+            )
+        );
+
+        menu.extend(list.items.iter().map(
+            |lockout| terminal_menu::label(
+                format!(
+                    "{:32} | {:<8} | {:17} | {:17}",
+                    lockout.user_key,
+                    lockout.failures,
+                    local_time(&lockout.last_failure),
+                    match lockout.locked_until {
+                        Some(until) if until > now => local_time(&until),
+                        _ => String::from("Not locked")
+                    }
+                )
+            )
+        ));
+
+        let last_index = list.offset + list.items.len() as i64;
+
+        menu.push(
+            terminal_menu::label(format!(
+                "Showing {} to {} of {}",
+                list.offset + 1,
+                last_index,
+                list.total
+            ))
+        );
+
+        if last_index < list.total {
+            menu.push(terminal_menu::button("Next"));
+        }
+
+        if list.offset > 0 {
+            menu.push(terminal_menu::button("Prev"));
+        }
+
+        menu.push(terminal_menu::button("Done"));
+
+        menu
+    }
+}
+
+impl FromMenu for ListLockoutsMenu {
+    type Output = Option<crate::types::ListNav>;
+    type Error = Infallible;
+
+    fn from_menu(menu: &mut TerminalMenuStruct) -> MResult<Self> {
+        use crate::types::ListNav;
+        match menu.selected_item_name() {
+            "Next" => Ok(Some(ListNav::Next)),
+            "Prev" => Ok(Some(ListNav::Prev)),
+            "Done" => Ok(None),
+            val => panic!("Invalid Lockouts List Action value: {}", val)
+        }
+    }
+}
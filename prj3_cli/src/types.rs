@@ -3,9 +3,12 @@ pub enum Actions {
     AddUser,
     DeleteUser,
     ListUsers,
+    SetUserRole,
     AddRecord,
     DeleteRecord,
     ListUserRecords,
+    ListLockouts,
+    ClearLockout,
     Exit
 }
 
@@ -10,7 +10,10 @@ pub struct Props {
     pub difficulties: Vec<String>, // types of difficulties for game
     pub board_sizes: Vec<String>, // types of board sizes
     pub disc_colors: Vec<String>, // types of disc colors that can be selected
-    pub start_pressed: Callback<[String; 3]>, // start button callback
+    pub first_movers: Vec<String>, // choices for who moves first
+    #[prop_or_default]
+    pub letter_modes: Vec<String>, // types of letter inventories (TOOT and OTTO only)
+    pub start_pressed: Callback<[String; 5]>, // start button callback
 }
 
 pub struct GameSetup {
@@ -18,13 +21,17 @@ pub struct GameSetup {
     selected_difficulty: String, // selected difficulty from radio group
     selected_board_size: String, // selected board size from radio group
     selected_disc_color: String, // selected disc color from radio group
+    selected_letter_mode: String, // selected letter inventory from radio group
+    selected_first_mover: String, // selected first mover from radio group
 }
 
 pub enum Msg {
     StartPressed,
     UpdateDifficulty(String),
     UpdateBoardSize(String),
-    UpdateDiscColor(String)
+    UpdateDiscColor(String),
+    UpdateLetterMode(String),
+    UpdateFirstMover(String)
 }
 impl Component for GameSetup {
     type Message = Msg;
@@ -35,7 +42,9 @@ impl Component for GameSetup {
             should_start: false,
             selected_difficulty: ctx.props().difficulties.clone()[0].to_string(),
             selected_board_size: ctx.props().board_sizes.clone()[0].to_string(),
-            selected_disc_color: ctx.props().disc_colors.clone()[0].to_string()
+            selected_disc_color: ctx.props().disc_colors.clone()[0].to_string(),
+            selected_letter_mode: ctx.props().letter_modes.first().cloned().unwrap_or_default(),
+            selected_first_mover: ctx.props().first_movers.clone()[0].to_string()
         }
     }
 
@@ -49,7 +58,9 @@ impl Component for GameSetup {
                 start_pressed.emit([
                     self.selected_difficulty.clone(),
                         self.selected_board_size.clone(),
-                        self.selected_disc_color.clone()]
+                        self.selected_disc_color.clone(),
+                        self.selected_letter_mode.clone(),
+                        self.selected_first_mover.clone()]
                 );
                 true
             }
@@ -71,6 +82,18 @@ impl Component for GameSetup {
                 self.selected_disc_color = selection;
                 true
             }
+
+            // letter inventory radio selected
+            Msg::UpdateLetterMode(selection) => {
+                self.selected_letter_mode = selection;
+                true
+            }
+
+            // first mover radio selected
+            Msg::UpdateFirstMover(selection) => {
+                self.selected_first_mover = selection;
+                true
+            }
         }
     }
 
@@ -81,6 +104,8 @@ impl Component for GameSetup {
         let difficulties = ctx.props().difficulties.clone();
         let board_sizes = ctx.props().board_sizes.clone();
         let disc_colors = ctx.props().disc_colors.clone();
+        let letter_modes = ctx.props().letter_modes.clone();
+        let first_movers = ctx.props().first_movers.clone();
 
         let setup_header = "Steps to play ".to_string() + &name + ": ";
 
@@ -118,6 +143,14 @@ impl Component for GameSetup {
                                 <RadioGroup title={"Difficulties"} options={difficulties} name={"difficulty"} is_discs={false} update={ctx.link().callback(Msg::UpdateDifficulty).clone()}/>
                                 <RadioGroup title={"Board sizes"} options={board_sizes} name={"board_size"} is_discs={false} update={ctx.link().callback(Msg::UpdateBoardSize).clone()} />
                                 <RadioGroup title={"Disc colors"} options={disc_colors} name={"disc_color"} is_discs={true} update={ctx.link().callback(Msg::UpdateDiscColor).clone()} />
+                                <RadioGroup title={"First move"} options={first_movers} name={"first_mover"} is_discs={false} update={ctx.link().callback(Msg::UpdateFirstMover).clone()} />
+                                {   // letter inventory is only offered for TOOT and OTTO
+                                    if !letter_modes.is_empty() {
+                                        html! {
+                                            <RadioGroup title={"Letters"} options={letter_modes} name={"letter_mode"} is_discs={false} update={ctx.link().callback(Msg::UpdateLetterMode).clone()} />
+                                        }
+                                    } else {html!{}}
+                                }
                                 <div class="container" style={"width:90%; margin-left:0.75rem; margin-top: 190px;" }>
                                     <button class="button is-primary" onclick={ctx.link().callback(|_| Msg::StartPressed)} style={"width: 100%;"}>{"Start game"}</button>
                                 </div>
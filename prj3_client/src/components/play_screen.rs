@@ -5,12 +5,14 @@ use yew::prelude::*;
 use bounce::prelude::*;
 use bounce::query::{use_mutation_value, MutationResult, UseMutationValueHandle};
 
-use shared_types::types::{MatchResult, CpuLevel, ClientMatchData, GameType};
+use shared_types::achievements;
+use shared_types::types::{MatchResult, CpuLevel, ClientMatchData, GameType, FirstMover};
 
 use crate::game;
 use crate::game::*;
 
 use crate::stores::auth::AuthCredentials;
+use crate::mutations::ServiceError;
 use crate::mutations::match_records::UserMatchRecordMutation;
 
 #[derive(Clone, Debug, PartialEq, Properties)]
@@ -23,6 +25,10 @@ pub struct Props {
     pub selected_disc_color: String,
     /// Board size
     pub selected_board_size: String,
+    /// Letter inventory (TOOT and OTTO only)
+    pub selected_letter_mode: String,
+    /// Who moves first
+    pub selected_first_mover: String,
     /// Columns
     pub columns: String,
     /// Rows
@@ -103,24 +109,39 @@ impl BoardUpdateCallbackFactory {
                         panic!("Unknown Game State")
                     };
 
-                    let moves = game_mut.get_num_moves() as i32;
+                    let moves = game_mut.get_num_player_moves() as i32;
+                    let first_mover = game_mut.get_first_mover();
+                    let board_size = game_mut.get_board_size();
 
                     let record_mutation = record_mutation.clone();
                     wasm_bindgen_futures::spawn_local(async move {
                         let _res = record_mutation.run(ClientMatchData {
                             game_id,
                             cpu_level,
-                            moves: moves / 2 + moves % 2,
-                            result
+                            moves,
+                            result,
+                            first_mover,
+                            columns: board_size.columns,
+                            rows: board_size.rows
                         }).await;
                     });
                 }
 
                 let board_state = game_mut.get_board_state();
 
+                // Switch letters if the player just used up the selected one
+                let is_t = match (
+                    game_mut.get_letters_remaining(PLAYER_ID, Letter::T),
+                    game_mut.get_letters_remaining(PLAYER_ID, Letter::O)
+                ) {
+                    (Some(0), _) if state.is_t => false,
+                    (_, Some(0)) if !state.is_t => true,
+                    _ => state.is_t
+                };
+
                 // It appears that state.set() runs synchronously which means that the mutable reference is still active so we drop it here
                 std::mem::drop(game_mut);
-                state.set(PlayScreenState { board_state, game_state, is_t: state.is_t }.into())
+                state.set(PlayScreenState { board_state, game_state, is_t }.into())
             }
         })
     }
@@ -137,14 +158,12 @@ pub fn play_screen(props: &Props) -> Html {
     // Get AI config
     let (ai_config, cpu_level) = get_ai_config(&mode);
 
+    // Get letter inventory
+    let letter_limit = get_letter_limit(&props.selected_letter_mode);
+
     // Spawn a reference to the game that stays throughout this component's lifetime
     let game = use_mut_ref(|| {
-        Game::new(
-            props.rows.clone().parse::<usize>().unwrap(),
-            props.columns.clone().parse::<usize>().unwrap(),
-            get_game_type(props.name.as_str()),
-            ai_config
-        )
+        new_game(props, ai_config, letter_limit)
     });
 
     // Get the state
@@ -165,6 +184,9 @@ pub fn play_screen(props: &Props) -> Html {
     // Get the handle for the match record save mutation
     let record_mutation = use_mutation_value::<UserMatchRecordMutation>();
 
+    // Whether the achievements unlocked by the last match have been dismissed
+    let toast_dismissed = use_state_eq(|| false);
+
     // Get game type
     let (p1, p2, game_type) = if name == "TOOT and OTTO" {
         ("You - TOOT", "Computer - OTTO", GameType::OttoToot)
@@ -214,6 +236,10 @@ pub fn play_screen(props: &Props) -> Html {
     let game_type = get_game_type(name.as_str());
     let is_toot_and_otto = game_type == GameType::OttoToot;
 
+    // Get the letters the player has left (None if unlimited)
+    let t_remaining = game.borrow().get_letters_remaining(PLAYER_ID, Letter::T);
+    let o_remaining = game.borrow().get_letters_remaining(PLAYER_ID, Letter::O);
+
     html! {
         <div class="container" style="max-width:650px">
             <h1 class="title has-text-centered mt-6">{name}</h1>
@@ -240,8 +266,9 @@ pub fn play_screen(props: &Props) -> Html {
                                             type="radio"
                                             onclick = {on_t_selected}
                                             checked = {state.clone().is_t}
+                                            disabled = {t_remaining == Some(0)}
                                             />
-                                        {"T"}
+                                        {get_letter_label("T", t_remaining)}
                                     </span>
                                     //O radio
                                     <span class="mx-2 is-size-6">
@@ -250,8 +277,9 @@ pub fn play_screen(props: &Props) -> Html {
                                             type="radio"
                                             onclick = {on_o_selected}
                                             checked = {!state.clone().is_t}
+                                            disabled = {o_remaining == Some(0)}
                                             />
-                                        {"O"}
+                                        {get_letter_label("O", o_remaining)}
                                     </span>
                             </div>
                         }
@@ -282,13 +310,10 @@ pub fn play_screen(props: &Props) -> Html {
                         // Restart game state callback
                         let on_restart_clicked = {
                             let props = props.clone();
+                            let toast_dismissed = toast_dismissed.clone();
                             Callback::from(move |_| {
-                                *(game.borrow_mut()) = Game::new(
-                                    props.rows.clone().parse::<usize>().unwrap(),
-                                    props.columns.clone().parse::<usize>().unwrap(),
-                                    get_game_type(props.name.as_str()),
-                                    ai_config
-                                );
+                                toast_dismissed.set(false);
+                                *(game.borrow_mut()) = new_game(&props, ai_config, letter_limit);
                                 state.set(PlayScreenState {
                                     board_state: game.borrow().get_board_state(),
                                     game_state: GameState::Running,
@@ -298,6 +323,7 @@ pub fn play_screen(props: &Props) -> Html {
                         };
 
                         html! {
+                            <>
                             <div class="card results-card">
                                 <div class="card-content">
                                 <div class="content">
@@ -307,6 +333,15 @@ pub fn play_screen(props: &Props) -> Html {
                                 </div>
                                 </div>
                             </div>
+                            {
+                                if *toast_dismissed {
+                                    html! {}
+                                } else {
+                                    let toast_dismissed = toast_dismissed.clone();
+                                    render_achievement_toast(record_mutation.result(), Callback::from(move |_| toast_dismissed.set(true)))
+                                }
+                            }
+                            </>
                         }
                     }
                 }
@@ -316,6 +351,33 @@ pub fn play_screen(props: &Props) -> Html {
     }
 }
 
+/// Creates a new game from the props, letting the CPU open if it moves first
+fn new_game(props: &Props, ai_config: AIConfiguration, letter_limit: Option<u32>) -> Game {
+    let mut game = Game::new(
+        props.rows.clone().parse::<usize>().unwrap(),
+        props.columns.clone().parse::<usize>().unwrap(),
+        get_game_type(props.name.as_str()),
+        ai_config,
+        letter_limit,
+        get_first_mover(&props.selected_first_mover)
+    );
+
+    if !game.is_player_turn() {
+        game.ai_turn();
+    }
+
+    game
+}
+
+/// Parses the first mover, picking one at random if requested
+fn get_first_mover(first_mover: &str) -> FirstMover {
+    match first_mover {
+        "CPU first" => FirstMover::Cpu,
+        "Random" => if rand::random() { FirstMover::Player } else { FirstMover::Cpu },
+        _ => FirstMover::Player
+    }
+}
+
 /// Parses the game type
 fn get_game_type(diff: &str) -> GameType {
     match diff {
@@ -346,6 +408,22 @@ fn get_ai_config(diff: &str) -> (AIConfiguration, CpuLevel) {
     }
 }
 
+/// Parses the letter inventory
+fn get_letter_limit(letter_mode: &str) -> Option<u32> {
+    match letter_mode {
+        "6 T's and 6 O's" => Some(game::OFFICIAL_LETTER_COUNT),
+        _ => None
+    }
+}
+
+/// Returns the letter selector label with the number of letters left
+fn get_letter_label(letter: &str, remaining: Option<u32>) -> String {
+    match remaining {
+        Some(count) => format!("{} ({} left)", letter, count),
+        None => letter.to_string()
+    }
+}
+
 /// Returns appropriate result text
 fn get_result_text(state: GameState) -> &'static str {
     if state == GameState::Tie {
@@ -375,7 +453,7 @@ fn get_opponent_color(selected_disc_color: String, is_same: bool) -> &'static st
 }
 
 /// Renders the board game grid
-fn render_grid(selected_board_size: String, board_state: Vec<(i32, String)>, selected_disc_color: String, name: String) -> Html {
+pub fn render_grid(selected_board_size: String, board_state: Vec<(i32, String)>, selected_disc_color: String, name: String) -> Html {
     //get game type toot and otto or connect 4
     let game_type = get_game_type(name.as_str());
     let is_toot_and_otto = game_type == GameType::OttoToot;
@@ -450,6 +528,25 @@ fn render_col_buttons(
     }
 }
 
+/// Render a toast announcing the achievements the match unlocked (if any)
+fn render_achievement_toast(mutation_result: Option<MutationResult<UserMatchRecordMutation>>, on_dismiss: Callback<MouseEvent>) -> Html {
+    match mutation_result {
+        Some(Ok(ref saved)) if !saved.0.is_empty() => html! {
+            <div class="notification is-warning achievement-toast">
+                <button class="delete" onclick={on_dismiss}></button>
+                <p class="has-text-weight-bold">{"Achievement unlocked!"}</p>
+                {
+                    saved.0.iter().map(|unlocked| {
+                        let info = achievements::info(unlocked.achievement);
+                        html! { <p>{format!("{} - {}", info.name, info.description)}</p> }
+                    }).collect::<Html>()
+                }
+            </div>
+        },
+        _ => html! {}
+    }
+}
+
 /// Renders the record save text
 fn render_record_save(mutation_result: Option<MutationResult<UserMatchRecordMutation>>, is_guest: bool) -> Html {
 
@@ -459,7 +556,9 @@ fn render_record_save(mutation_result: Option<MutationResult<UserMatchRecordMuta
                 _ if is_guest => "Login to save match",
                 None => "Saving...",
                 Some(Ok(_)) => "Saved!",
-                Some(Err(_)) => "Save Failed."
+                // The match stays queued on this device either way (see UserMatchRecordMutation)
+                Some(Err(ServiceError::UnableToContactServer)) => "Offline, match will be saved when back online",
+                Some(Err(_)) => "Save failed, will retry later"
             }
         }</h6>
     }
@@ -5,18 +5,26 @@ use yew_router::prelude::*;
 use bounce::BounceRoot;
 use bounce::prelude::*;
 
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+
 mod pages;
 mod components;
 mod mutations;
 mod stores;
-mod game;
+
+use shared_types::game;
 
 use pages::{
-    connect_4::Connect4, home::Home, login::Login, page_not_found::PageNotFound, toot::Toot, leaderboard::Leaderboard
+    account::Account, connect_4::Connect4, home::Home, login::Login, page_not_found::PageNotFound, toot::Toot, leaderboard::Leaderboard,
+    ratings::Ratings, profile::Profile, achievements::Achievements, puzzle::DailyPuzzlePage
 };
 
 use stores::auth::AuthCredentials;
 
+use mutations::match_records::export_url;
+use shared_types::types::ExportFormat;
+
 #[derive(Properties, PartialEq)]
 struct AppLaunchProps {
     session_user: Option<shared_types::types::UserInfo>
@@ -34,8 +42,18 @@ enum Route {
     Home,
     #[at("/login")]
     Login,
+    #[at("/account")]
+    Account,
     #[at("/leaderboard")]
     Leaderboard,
+    #[at("/ratings")]
+    Ratings,
+    #[at("/profile/:id")]
+    Profile { id: String },
+    #[at("/achievements")]
+    Achievements,
+    #[at("/puzzle")]
+    DailyPuzzle,
     #[at("/games/connect4")]
     Connect4,
         #[at("/games/toototto")]
@@ -50,7 +68,12 @@ fn switch(routes: &Route) -> Html {
     match routes {
         Route::Home => html! { <Home/> },
         Route::Login => html! { <Login/> },
+        Route::Account => html! { <Account/> },
         Route::Leaderboard => html! { <Leaderboard/> },
+        Route::Ratings => html! { <Ratings/> },
+        Route::Profile { id } => html! { <Profile id={id.clone()} /> },
+        Route::Achievements => html! { <Achievements/> },
+        Route::DailyPuzzle => html! { <DailyPuzzlePage/> },
         Route::Connect4 => html! { <Connect4/> },
         Route::TootOtto => html! { <Toot/> },
         Route::NotFound => html! { <PageNotFound/> },
@@ -99,6 +122,36 @@ fn nav_bar(props: &NavBarProps) -> Html {
         _ => None,
     };
 
+    // Send the user's matches that failed to send before, now and whenever the browser is back online
+    use_effect_with_deps(
+        move |user: &Option<String>| {
+            let window = web_sys::window().unwrap();
+
+            let listener = user.clone().map(|user_id| {
+                let send = move || {
+                    let user_id = user_id.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        if let Err(err) = mutations::match_records::send_pending_records(&user_id).await {
+                            log::warn!("Unable to send saved matches: {}", err);
+                        }
+                    });
+                };
+                send();
+
+                let listener = Closure::<dyn Fn()>::wrap(Box::new(send));
+                window.add_event_listener_with_callback("online", listener.as_ref().unchecked_ref()).unwrap();
+                listener
+            });
+
+            move || {
+                if let Some(listener) = listener {
+                    window.remove_event_listener_with_callback("online", listener.as_ref().unchecked_ref()).unwrap();
+                }
+            }
+        },
+        user.clone()
+    );
+
     html! {
         <nav class="navbar is-primary" role="navigation" aria-label="main navigation">
             <div class="navbar-brand ml-3">
@@ -124,17 +177,25 @@ fn nav_bar(props: &NavBarProps) -> Html {
                     <Link<Route> classes={classes!("navbar-item")} to={Route::Leaderboard}>
                         { "Leaderboard" }
                     </Link<Route>>
+                    <Link<Route> classes={classes!("navbar-item")} to={Route::DailyPuzzle}>
+                        { "Daily Puzzle" }
+                    </Link<Route>>
                 </div>
                 <div class="navbar-end">
                     {
                         if let Some(username) = user {
-                            // Show logout button if we are logged in
-                            let on_logout = 
+                            // Show logout buttons if we are logged in
+                            let logout = move |everywhere: bool| {
+                                let credentials = credentials.clone();
                                 Callback::from(move |_| {
                                     let credentials = credentials.clone();
-                                    // Log client out
+                                    // Log client out (ending every session of the user if everywhere is set)
                                     wasm_bindgen_futures::spawn_local(async move {
-                                        let res = mutations::auth::logout().await;
+                                        let res = if everywhere {
+                                            mutations::auth::logout_everywhere().await
+                                        } else {
+                                            mutations::auth::logout().await
+                                        };
 
                                         match res {
                                             Ok(()) => credentials.set(AuthCredentials::Guest.into()),
@@ -148,16 +209,45 @@ fn nav_bar(props: &NavBarProps) -> Html {
                                         }
                                         
                                     });
-                                });
+                                })
+                            };
                             html! {
-                                <div class="navbar-item has-dropdown is-hoverable">
-                                    <div class="navbar-link">
-                                        { format!("Hello {}", username) }
+                                <>
+                                    <div class="navbar-item has-dropdown is-hoverable">
+                                        <div class="navbar-link">
+                                            { "Download my data" }
+                                        </div>
+                                        <div class="navbar-dropdown">
+                                            <a class="navbar-item" href={export_url(ExportFormat::Csv)} download="match_records.csv">
+                                                { "Match records (CSV)" }
+                                            </a>
+                                            <a class="navbar-item" href={export_url(ExportFormat::Jsonl)} download="match_records.jsonl">
+                                                { "Match records (JSON Lines)" }
+                                            </a>
+                                        </div>
                                     </div>
-                                    <div class="navbar-dropdown">
-                                        <a class="navbar-item" onclick={on_logout}>{"Logout"}</a>
+                                    <div class="navbar-item has-dropdown is-hoverable">
+                                        <div class="navbar-link">
+                                            { format!("Hello {}", username) }
+                                        </div>
+                                        <div class="navbar-dropdown">
+                                            <Link<Route> classes={classes!("navbar-item")} to={Route::Account}>
+                                                { "Account settings" }
+                                            </Link<Route>>
+                                            <Link<Route> classes={classes!("navbar-item")} to={Route::Profile { id: username.clone() }}>
+                                                { "My profile" }
+                                            </Link<Route>>
+                                            <Link<Route> classes={classes!("navbar-item")} to={Route::Ratings}>
+                                                { "My ratings" }
+                                            </Link<Route>>
+                                            <Link<Route> classes={classes!("navbar-item")} to={Route::Achievements}>
+                                                { "Achievements" }
+                                            </Link<Route>>
+                                            <a class="navbar-item" onclick={logout(false)}>{"Logout"}</a>
+                                            <a class="navbar-item" onclick={logout(true)}>{"Logout everywhere"}</a>
+                                        </div>
                                     </div>
-                                </div>
+                                </>
                             }
                         } else {
                             // Show login button otherwise
@@ -0,0 +1,153 @@
+use shared_types::types::{AccountDeleteForm, ApiTokenForm, ApiTokenInfo, NewApiToken, PasswordChangeForm};
+use shared_types::validation::ValidationErrors;
+
+use super::util::*;
+
+/// Error type for Account APIs
+#[derive(Debug, PartialEq, Clone)]
+pub enum AccountError {
+    IncorrectPassword,
+    InvalidInput(ValidationErrors),
+    Other(super::ServiceError)
+}
+
+impl std::fmt::Display for AccountError {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            AccountError::IncorrectPassword => write!(f, "Account Error: Incorrect password"),
+            AccountError::InvalidInput(errors) => write!(f, "Account Error: Invalid input ({:?})", errors),
+            AccountError::Other(err) => write!(f, "Account Error: API error ({})", err)
+        }
+    }
+
+}
+
+impl std::error::Error for AccountError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AccountError::Other(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<APIError> for AccountError {
+
+    fn from(err: APIError) -> Self {
+        match err {
+            APIError::AuthenticationError => AccountError::IncorrectPassword,
+            APIError::ValidationError(errors) => AccountError::InvalidInput(errors),
+            err => AccountError::Other(super::ServiceError::from(err))
+        }
+    }
+
+}
+
+/// Change the password of the logged in user
+pub async fn change_password(form: &PasswordChangeForm) -> Result<(), AccountError> {
+    run_change_password(form).await.map_err(AccountError::from)
+}
+
+/// Delete the logged in user
+pub async fn delete_account(form: &AccountDeleteForm) -> Result<(), AccountError> {
+    run_delete_account(form).await.map_err(AccountError::from)
+}
+
+/// Get the personal API tokens of the logged in user
+pub async fn list_tokens() -> Result<Vec<ApiTokenInfo>, AccountError> {
+    run_list_tokens().await.map_err(AccountError::from)
+}
+
+/// Create a personal API token for the logged in user
+pub async fn create_token(form: &ApiTokenForm) -> Result<NewApiToken, AccountError> {
+    run_create_token(form).await.map_err(AccountError::from)
+}
+
+/// Revoke a personal API token of the logged in user
+pub async fn revoke_token(id: i32) -> Result<(), AccountError> {
+    run_revoke_token(id).await.map_err(AccountError::from)
+}
+
+/// Run API call for changing the password of the logged in user
+async fn run_change_password(form: &PasswordChangeForm) -> Result<(), APIError> {
+    let endpoint_url = get_base_url().join("api/v1/user/password").unwrap();
+
+    let client = api_client().await;
+
+    let response = client.post(endpoint_url)
+        .form(form)
+        .send()
+        .await?;
+
+
+    response.check_status().await?;
+
+    Ok(())
+}
+
+/// Run API call for deleting the logged in user
+async fn run_delete_account(form: &AccountDeleteForm) -> Result<(), APIError> {
+    let endpoint_url = get_base_url().join("api/v1/user").unwrap();
+
+    let client = api_client().await;
+
+    client.delete(endpoint_url)
+        .form(form)
+        .send()
+        .await?
+        .check_status().await?;
+
+    Ok(())
+}
+
+/// Run API call for listing the API tokens of the logged in user
+async fn run_list_tokens() -> Result<Vec<ApiTokenInfo>, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/user/tokens").unwrap();
+
+    let client = api_client().await;
+
+    Ok(
+        client.get(endpoint_url)
+            .send()
+            .await?
+            .check_status().await?
+            .json::<Vec<ApiTokenInfo>>()
+            .await?
+    )
+}
+
+/// Run API call for creating an API token for the logged in user
+async fn run_create_token(form: &ApiTokenForm) -> Result<NewApiToken, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/user/tokens").unwrap();
+
+    let client = api_client().await;
+
+    let response = client.post(endpoint_url)
+        .form(form)
+        .send()
+        .await?;
+
+
+    Ok(response.check_status().await?.json::<NewApiToken>().await?)
+}
+
+/// Run API call for revoking an API token of the logged in user
+async fn run_revoke_token(id: i32) -> Result<(), APIError> {
+    let endpoint_url = get_base_url().join(&format!("api/v1/user/tokens/{}", id)).unwrap();
+
+    let client = api_client().await;
+
+    let response = client.delete(endpoint_url)
+        .send()
+        .await?;
+
+    // Token was already revoked (such as from another tab)
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(());
+    }
+
+    response.check_status().await?;
+
+    Ok(())
+}
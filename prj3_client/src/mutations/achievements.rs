@@ -0,0 +1,29 @@
+use shared_types::types::{ApiError, UnlockedAchievement};
+
+use super::util::*;
+use super::ServiceError;
+
+/// Get the achievements the logged in user has unlocked
+pub async fn get_achievements() -> Result<Vec<UnlockedAchievement>, ServiceError> {
+    run_get_achievements().await.map_err(|err| match err {
+        // Session ended since the page was loaded
+        APIError::AuthenticationError => ServiceError::Rejected(ApiError::new(401, "Not logged in")),
+        err => ServiceError::from(err)
+    })
+}
+
+/// GET from /user/achievements
+async fn run_get_achievements() -> Result<Vec<UnlockedAchievement>, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/user/achievements").unwrap();
+
+    let client = api_client().await;
+
+    let response = client.get(endpoint_url)
+        .send()
+        .await?
+        .check_status().await?
+        .json::<Vec<UnlockedAchievement>>()
+        .await?;
+
+    Ok(response)
+}
@@ -6,6 +6,7 @@ use bounce::prelude::*;
 use bounce::query::{Mutation, MutationResult};
 
 use shared_types::types::{UserAuthForm, UserInfo};
+use shared_types::validation::ValidationErrors;
 
 use super::util::*;
 
@@ -13,7 +14,8 @@ use super::util::*;
 #[derive(Debug, PartialEq, Clone)]
 pub enum AuthError {
     InvalidCredentials,
-    RegisterTakenUsername,
+    InvalidInput(ValidationErrors),
+    TooManyAttempts,
     Other(super::ServiceError)
 }
 
@@ -22,7 +24,8 @@ impl std::fmt::Display for AuthError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         match self {
             AuthError::InvalidCredentials => write!(f, "Authorization Error: Invalid credentials"),
-            AuthError::RegisterTakenUsername => write!(f, "Authorization Error: Attempted to register using taken username"),
+            AuthError::InvalidInput(errors) => write!(f, "Authorization Error: Invalid input ({:?})", errors),
+            AuthError::TooManyAttempts => write!(f, "Authorization Error: Too many failed attempts"),
             AuthError::Other(err) => write!(f, "Authorization Error: API error ({})", err)
         }
     }
@@ -72,12 +75,9 @@ impl Mutation for AuthMutation {
         ).map_err(
             |err| {
                 match err {
-                    APIError::AuthenticationError => {
-                        match input.login_as {
-                            NewUser => AuthError::RegisterTakenUsername,
-                            RegisteredUser => AuthError::InvalidCredentials
-                        }
-                    }
+                    APIError::AuthenticationError => AuthError::InvalidCredentials,
+                    APIError::ValidationError(errors) => AuthError::InvalidInput(errors),
+                    APIError::TooManyRequests => AuthError::TooManyAttempts,
                     err => AuthError::Other(super::ServiceError::from(err))
                 }
             }
@@ -90,13 +90,15 @@ impl Mutation for AuthMutation {
 async fn register(credentials: &UserAuthForm) -> Result<(), APIError> {
     let endpoint_url = get_base_url().join("api/v1/user/register").unwrap();
 
-    let client = reqwest::Client::new();
+    let client = api_client().await;
 
-    client.post(endpoint_url)
+    let response = client.post(endpoint_url)
         .form(credentials)
         .send()
-        .await?
-        .error_for_status()?;
+        .await?;
+
+
+    response.check_status().await?;
 
     Ok(())
 }
@@ -105,13 +107,13 @@ async fn register(credentials: &UserAuthForm) -> Result<(), APIError> {
 async fn login(credentials: &UserAuthForm) -> Result<(), APIError> {
     let endpoint_url = get_base_url().join("api/v1/user/login").unwrap();
 
-    let client = reqwest::Client::new();
+    let client = api_client().await;
 
     client.post(endpoint_url)
         .form(credentials)
         .send()
         .await?
-        .error_for_status()?;
+        .check_status().await?;
 
     Ok(())
 }
@@ -133,7 +135,7 @@ pub async fn verify() -> Option<UserInfo> {
 pub async fn logout() -> Result<(), APIError> {
     let endpoint_url = get_base_url().join("api/v1/user/logout").unwrap();
 
-    let client = reqwest::Client::new();
+    let client = api_client().await;
 
     client.post(endpoint_url)
         .send()
@@ -142,3 +144,17 @@ pub async fn logout() -> Result<(), APIError> {
     Ok(())
 }
 
+
+
+/// Run API call for logging out of every session
+pub async fn logout_everywhere() -> Result<(), APIError> {
+    let endpoint_url = get_base_url().join("api/v1/user/sessions/revoke").unwrap();
+
+    let client = api_client().await;
+
+    client.post(endpoint_url)
+        .send()
+        .await?;
+
+    Ok(())
+}
\ No newline at end of file
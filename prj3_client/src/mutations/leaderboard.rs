@@ -0,0 +1,42 @@
+use shared_types::types::{BoardSize, CpuLevel, GameType, Leaderboard, LeaderboardPeriod};
+
+use super::util::*;
+use super::ServiceError;
+
+/// Options for a leaderboard request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeaderboardOptions {
+    pub game: GameType,
+    pub level: Option<CpuLevel>,
+    pub board_size: Option<BoardSize>,
+    pub period: LeaderboardPeriod,
+    pub limit: i64,
+    pub offset: i64
+}
+
+/// Get a page of a leaderboard (along with the logged in user's entry)
+pub async fn get_leaderboard(options: &LeaderboardOptions) -> Result<Leaderboard, ServiceError> {
+    run_get_leaderboard(options).await.map_err(ServiceError::from)
+}
+
+/// GET from /leaderboard using options
+async fn run_get_leaderboard(options: &LeaderboardOptions) -> Result<Leaderboard, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/leaderboard").unwrap();
+
+    let client = api_client().await;
+
+    let response = client.get(endpoint_url)
+        .query_pair("game", Some(options.game))
+        .query_pair("level", options.level)
+        .query_pair("board_size", options.board_size.map(|size| size.to_string()))
+        .query_pair("period", Some(options.period))
+        .query_pair("limit", Some(options.limit))
+        .query_pair("offset", Some(options.offset))
+        .send()
+        .await?
+        .check_status().await?
+        .json::<Leaderboard>()
+        .await?;
+
+    Ok(response)
+}
@@ -3,18 +3,26 @@ use std::rc::Rc;
 use async_trait::async_trait;
 
 use bounce::prelude::*;
-use bounce::query::{Mutation, MutationResult, Query, QueryResult};
+use bounce::query::{Mutation, MutationResult};
 
-use shared_types::types::{MatchQueryFilter, MatchQuerySortBy, MatchRecord, ToQueryPairs, ClientMatchData, Records};
+use shared_types::types::{
+    ApiError, BatchMatchData, BatchRecordResult, ExportFormat, MatchQueryFilter, MatchQuerySortBy, MatchRecord, ToQueryPairs,
+    ClientMatchData, Records, UnlockedAchievement
+};
+use shared_types::validation::BATCH_MAX_RECORDS;
 
 use super::util::*;
 use super::ServiceError;
+use crate::stores::auth::AuthCredentials;
+use crate::stores::pending_records;
 
 /// Options for a match query request
 #[derive(Debug, Eq, PartialEq, Hash)]
 pub struct MatchRecordQueryOptions {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// Cursor of the page to get (from the previous page's next_cursor)
+    pub cursor: Option<String>,
     pub filters: Option<MatchQueryFilter>,
     pub sort_by: Option<MatchQuerySortBy>,
     pub asc: Option<bool>
@@ -34,6 +42,7 @@ impl Mutation for MatchRecordQuery {
         let records = get_records(
             input.limit,
             input.offset,
+            &input.cursor,
             &input.filters,
             input.sort_by,
             input.asc
@@ -43,22 +52,32 @@ impl Mutation for MatchRecordQuery {
     }
 }
 
-/// Match Record Query for Currently Authenticated User
+/// Options for a query of a user's match records
+#[derive(Debug, Eq, PartialEq, Hash)]
+pub struct UserMatchRecordQueryOptions {
+    pub user_id: String,
+    pub options: MatchRecordQueryOptions
+}
+
+/// Match Record Query for a User
 #[derive(Debug, PartialEq)]
 pub struct UserMatchRecordQuery(pub Records<MatchRecord>);
 
+// Use mutation here because Bounce's Query API is broken...
 #[async_trait(?Send)]
-impl Query for UserMatchRecordQuery {
-    type Input = MatchRecordQueryOptions;
+impl Mutation for UserMatchRecordQuery {
+    type Input = UserMatchRecordQueryOptions;
     type Error = ServiceError;
 
-    async fn query(_states: &BounceStates, input: Rc<MatchRecordQueryOptions>) -> QueryResult<Self> {
+    async fn run(_states: &BounceStates, input: Rc<UserMatchRecordQueryOptions>) -> MutationResult<Self> {
         let records = get_user_records(
-            input.limit,
-            input.offset,
-            &input.filters,
-            input.sort_by,
-            input.asc
+            &input.user_id,
+            input.options.limit,
+            input.options.offset,
+            &input.options.cursor,
+            &input.options.filters,
+            input.options.sort_by,
+            input.options.asc
         ).await?;
 
         Ok(UserMatchRecordQuery(records).into())
@@ -66,53 +85,113 @@ impl Query for UserMatchRecordQuery {
 }
 
 /// Mutation for reporting a new match to the server backend
+/// 
+/// The match is queued in local storage first (see [pending_records]) and the queue
+/// is then sent, so matches that fail to send are kept until the next try
+/// 
+/// Holds the achievements unlocked by the matches sent
 #[derive(Debug, PartialEq)]
-pub struct UserMatchRecordMutation();
+pub struct UserMatchRecordMutation(pub Vec<UnlockedAchievement>);
 
 #[async_trait(?Send)]
 impl Mutation for UserMatchRecordMutation {
     type Input = ClientMatchData;
     type Error = ServiceError;
 
-    async fn run(_states: &BounceStates, input: Rc<ClientMatchData>) -> MutationResult<Self> {
-        post_user_record(&input).await?;
+    async fn run(states: &BounceStates, input: Rc<ClientMatchData>) -> MutationResult<Self> {
+        let user_id = match *states.get_atom_value::<AuthCredentials>() {
+            AuthCredentials::Verified(ref info) => info.user_id.clone(),
+            AuthCredentials::Guest => return Err(ServiceError::Rejected(ApiError::new(401, "Not logged in")))
+        };
+
+        pending_records::push(&user_id, *input);
 
-        Ok(UserMatchRecordMutation().into())
+        let unlocked = send_pending_records(&user_id).await?;
+
+        Ok(UserMatchRecordMutation(unlocked).into())
     }
 
 }
 
-/// POST to /user/records/add with data
-async fn post_user_record(
-    record: &ClientMatchData
-) -> Result<(), APIError> {
-    let endpoint_url = get_base_url().join("api/v1/user/records/add").unwrap();
+/// Send the queued match records of a user in batches, removing them from the queue once the server has them
+/// 
+/// Stops at the first batch that fails, leaving it and the rest queued for the next try
+/// 
+/// Returns the achievements unlocked by the matches sent
+pub async fn send_pending_records(user_id: &str) -> Result<Vec<UnlockedAchievement>, ServiceError> {
+    let mut unlocked = Vec::new();
+
+    loop {
+        let batch = pending_records::take(user_id, BATCH_MAX_RECORDS);
+
+        if batch.is_empty() {
+            return Ok(unlocked);
+        }
+
+        let results = post_user_record_batch(&batch).await.map_err(|err| match err {
+            // Session ended, the records are sent once the user logs back in
+            APIError::AuthenticationError => ServiceError::Rejected(ApiError::new(401, "Not logged in")),
+            // Batches are never empty or larger than the limit
+            APIError::ValidationError(_) => ServiceError::Rejected(ApiError::new(422, "Invalid batch")),
+            APIError::TooManyRequests => ServiceError::Rejected(ApiError::new(429, "Too many requests")),
+            // Offline or the server is down
+            APIError::RequestError(_) => ServiceError::UnableToContactServer,
+            err => ServiceError::from(err)
+        })?;
+
+        // Invalid records would be rejected every time, so they're dropped along with the rest
+        let keys: Vec<String> = results.iter().map(|result| result.idempotency_key.clone()).collect();
+        pending_records::remove(user_id, &keys);
+
+        unlocked.extend(results.into_iter().flat_map(|result| result.unlocked));
+    }
+}
 
-    let client = reqwest::Client::new();
+/// POST to /user/records/batch with data
+async fn post_user_record_batch(
+    records: &[BatchMatchData]
+) -> Result<Vec<BatchRecordResult>, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/user/records/batch").unwrap();
 
-    let _response = client.post(endpoint_url)
-        .json(&record)
+    let client = api_client().await;
+
+    let response = client.post(endpoint_url)
+        .json(&records)
         .send()
         .await?
-        .error_for_status()?;
+        .check_status().await?
+        .json::<Vec<BatchRecordResult>>()
+        .await?;
+
+    Ok(response)
+}
+
+/// URL of /user/records/export to download every record of the logged in user in a format
+pub fn export_url(format: ExportFormat) -> String {
+    let mut endpoint_url = get_base_url().join("api/v1/user/records/export").unwrap();
+
+    endpoint_url.query_pairs_mut().append_pair("format", format.extension());
 
-    Ok(())
+    endpoint_url.to_string()
 }
 
-/// GET from /user/records using options
+/// GET from /users/<user_id>/records using options
 async fn get_user_records(
+    user_id: &str,
     limit: Option<i64>,
     offset: Option<i64>,
+    cursor: &Option<String>,
     filters: &Option<MatchQueryFilter>,
     sort_by: Option<MatchQuerySortBy>,
     asc: Option<bool>
 ) -> Result<Records<MatchRecord>, APIError> {
-    let endpoint_url = get_base_url().join("api/v1/user/records").unwrap();
+    let endpoint_url = get_base_url().join(&format!("api/v1/users/{}/records", user_id)).unwrap();
 
-    let client = reqwest::Client::new();
+    let client = api_client().await;
 
     let response = client.get(endpoint_url)
         .query_pair("offset", offset)
+        .query_pair("cursor", cursor.as_ref())
         .query_pair("limit", limit)
         .query_pair("sort_by", sort_by)
         .query(
@@ -123,7 +202,7 @@ async fn get_user_records(
         .query_pair("asc", asc)
         .send()
         .await?
-        .error_for_status()?
+        .check_status().await?
         .json::<Records<MatchRecord>>()
         .await?;
 
@@ -134,16 +213,18 @@ async fn get_user_records(
 async fn get_records(
     limit: Option<i64>,
     offset: Option<i64>,
+    cursor: &Option<String>,
     filters: &Option<MatchQueryFilter>,
     sort_by: Option<MatchQuerySortBy>,
     asc: Option<bool>
 ) -> Result<Records<MatchRecord>, APIError> {
     let endpoint_url = get_base_url().join("api/v1/games/records").unwrap();
 
-    let client = reqwest::Client::new();
+    let client = api_client().await;
 
     let response = client.get(endpoint_url)
         .query_pair("offset", offset)
+        .query_pair("cursor", cursor.as_ref())
         .query_pair("limit", limit)
         .query_pair("sort_by", sort_by)
         .query(
@@ -154,7 +235,7 @@ async fn get_records(
         .query_pair("asc", asc)
         .send()
         .await?
-        .error_for_status()?
+        .check_status().await?
         .json::<Records<MatchRecord>>()
         .await?;
 
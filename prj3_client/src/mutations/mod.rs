@@ -1,5 +1,13 @@
+use shared_types::types::ApiError;
+
+pub mod account;
+pub mod achievements;
 pub mod auth;
+pub mod leaderboard;
 pub mod match_records;
+pub mod puzzles;
+pub mod ratings;
+pub mod stats;
 
 mod util;
 
@@ -7,16 +15,18 @@ mod util;
 #[derive(Debug, PartialEq, Clone)]
 pub enum ServiceError {
     UnableToContactServer,
-    InternalServerError
+    InternalServerError,
+    Rejected(ApiError)
 }
 
 impl std::fmt::Display for ServiceError {
 
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        write!(f, "{}", match self {
-            ServiceError::UnableToContactServer => "unable to contact server",
-            ServiceError::InternalServerError => "internal server error"
-        })
+        match self {
+            ServiceError::UnableToContactServer => write!(f, "unable to contact server"),
+            ServiceError::InternalServerError => write!(f, "internal server error"),
+            ServiceError::Rejected(err) => write!(f, "request rejected ({}: {})", err.code, err.message)
+        }
     }
 
 }
@@ -32,15 +42,19 @@ impl From<util::APIError> for ServiceError {
             AuthenticationError => panic!(
                 "ServiceError: Cannot construct from AuthenticationError as it assumes an unauthenticated API"
             ), // TODO: Add to error message reference for AuthenticatedServiceError when made
+            ValidationError(errors) => panic!(
+                "ServiceError: Cannot construct from ValidationError.\nServices MUST internally handle ValidationErrors themselves.\nError found:{:#?}", errors
+            ),
+            TooManyRequests => panic!(
+                "ServiceError: Cannot construct from TooManyRequests as it assumes an unthrottled API"
+            ),
             InternalServerError => ServiceError::InternalServerError,
             TimeoutError => ServiceError::UnableToContactServer,
             RequestError(rerr) => panic!(
                 "ServiceError: Cannot construct from RequestError.\nServices MUST internally handle RequestErrors themselves.\nError found:{:#?}", rerr
             ),
-            UnknownStatus(status) if status >= 500 && status <= 599 => ServiceError::InternalServerError,
-            UnknownStatus(status) => panic!(
-                "ServiceError: Cannot construct from unidentified status {}", status
-            ),
+            Rejected(err) if err.code >= 500 && err.code <= 599 => ServiceError::InternalServerError,
+            Rejected(err) => ServiceError::Rejected(err),
         }
     }
 }
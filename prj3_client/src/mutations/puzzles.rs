@@ -0,0 +1,101 @@
+use shared_types::types::{ApiError, DailyPuzzle, PuzzleAttempt, PuzzleLeaderboardEntry, PuzzleProgress, Records};
+
+use super::util::*;
+use super::ServiceError;
+
+/// Get the puzzle of the day (along with the logged in user's progress)
+pub async fn get_daily_puzzle() -> Result<DailyPuzzle, ServiceError> {
+    run_get_daily_puzzle().await.map_err(ServiceError::from)
+}
+
+/// GET from /puzzles/daily
+async fn run_get_daily_puzzle() -> Result<DailyPuzzle, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/puzzles/daily").unwrap();
+
+    let client = api_client().await;
+
+    let response = client.get(endpoint_url)
+        .send()
+        .await?
+        .check_status().await?
+        .json::<DailyPuzzle>()
+        .await?;
+
+    Ok(response)
+}
+
+/// Start timing the logged in user on the puzzle of the day
+pub async fn start_daily_puzzle() -> Result<PuzzleProgress, ServiceError> {
+    run_start_daily_puzzle().await.map_err(|err| match err {
+        // Session ended since the page was loaded
+        APIError::AuthenticationError => ServiceError::Rejected(ApiError::new(401, "Not logged in")),
+        err => ServiceError::from(err)
+    })
+}
+
+/// POST to /puzzles/daily/start
+async fn run_start_daily_puzzle() -> Result<PuzzleProgress, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/puzzles/daily/start").unwrap();
+
+    let client = api_client().await;
+
+    let response = client.post(endpoint_url)
+        .send()
+        .await?
+        .check_status().await?
+        .json::<PuzzleProgress>()
+        .await?;
+
+    Ok(response)
+}
+
+/// Submit a solution to the puzzle of the day for the logged in user
+pub async fn submit_puzzle_attempt(attempt: &PuzzleAttempt) -> Result<PuzzleProgress, ServiceError> {
+    run_submit_puzzle_attempt(attempt).await.map_err(|err| match err {
+        // Session ended since the page was loaded
+        APIError::AuthenticationError => ServiceError::Rejected(ApiError::new(401, "Not logged in")),
+        // The board only offers moves that can be played, so the puzzle must have changed
+        APIError::ValidationError(_) => ServiceError::Rejected(ApiError::new(422, "Moves can't be played")),
+        err => ServiceError::from(err)
+    })
+}
+
+/// POST to /puzzles/daily/attempts with the attempt
+async fn run_submit_puzzle_attempt(attempt: &PuzzleAttempt) -> Result<PuzzleProgress, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/puzzles/daily/attempts").unwrap();
+
+    let client = api_client().await;
+
+    let response = client.post(endpoint_url)
+        .json(attempt)
+        .send()
+        .await?
+        .check_status().await?
+        .json::<PuzzleProgress>()
+        .await?;
+
+    Ok(response)
+}
+
+/// Get a page of the leaderboard of today's puzzle
+pub async fn get_puzzle_leaderboard(limit: i64, offset: i64) -> Result<Records<PuzzleLeaderboardEntry>, ServiceError> {
+    run_get_puzzle_leaderboard(limit, offset).await.map_err(ServiceError::from)
+}
+
+/// GET from /puzzles/daily/leaderboard
+async fn run_get_puzzle_leaderboard(limit: i64, offset: i64) -> Result<Records<PuzzleLeaderboardEntry>, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/puzzles/daily/leaderboard").unwrap();
+
+    let client = api_client().await;
+
+    let response = client.get(endpoint_url)
+        .query_pair("limit", Some(limit))
+        .query_pair("offset", Some(offset))
+        .send()
+        .await?
+        .check_status().await?
+        .json::<Records<PuzzleLeaderboardEntry>>()
+        .await?;
+
+    Ok(response)
+}
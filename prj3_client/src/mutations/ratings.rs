@@ -0,0 +1,30 @@
+use shared_types::types::{ApiError, UserRating};
+
+use super::util::*;
+use super::ServiceError;
+
+/// Get the ratings of the logged in user with up to [history] points of history for each game
+pub async fn get_ratings(history: i64) -> Result<Vec<UserRating>, ServiceError> {
+    run_get_ratings(history).await.map_err(|err| match err {
+        // Session ended since the page was loaded
+        APIError::AuthenticationError => ServiceError::Rejected(ApiError::new(401, "Not logged in")),
+        err => ServiceError::from(err)
+    })
+}
+
+/// GET from /user/ratings
+async fn run_get_ratings(history: i64) -> Result<Vec<UserRating>, APIError> {
+    let endpoint_url = get_base_url().join("api/v1/user/ratings").unwrap();
+
+    let client = api_client().await;
+
+    let response = client.get(endpoint_url)
+        .query_pair("history", Some(history))
+        .send()
+        .await?
+        .check_status().await?
+        .json::<Vec<UserRating>>()
+        .await?;
+
+    Ok(response)
+}
@@ -0,0 +1,25 @@
+use shared_types::types::UserStats;
+
+use super::util::*;
+use super::ServiceError;
+
+/// Get the statistics of a user
+pub async fn get_user_stats(user_id: &str) -> Result<UserStats, ServiceError> {
+    run_get_user_stats(user_id).await.map_err(ServiceError::from)
+}
+
+/// GET from /users/<user_id>/stats
+async fn run_get_user_stats(user_id: &str) -> Result<UserStats, APIError> {
+    let endpoint_url = get_base_url().join(&format!("api/v1/users/{}/stats", user_id)).unwrap();
+
+    let client = api_client().await;
+
+    let response = client.get(endpoint_url)
+        .send()
+        .await?
+        .check_status().await?
+        .json::<UserStats>()
+        .await?;
+
+    Ok(response)
+}
@@ -1,19 +1,53 @@
+use async_trait::async_trait;
+
 use reqwest::Url;
 
+use shared_types::types::{ApiError, CSRF_COOKIE, CSRF_HEADER};
+use shared_types::validation::ValidationErrors;
+
 /// Util function for getting the base url of the server backend
 pub fn get_base_url() -> Url {
     let origin = web_sys::window().unwrap().location().origin().unwrap();
     Url::parse(&origin).unwrap()
 }
 
+/// Util function for creating a client to call the server backend with
+/// 
+/// The CSRF token cookie set by the server is read when the client is made (right before
+/// each request) and copied into the header the server checks, if there is no cookie yet
+/// one is fetched first
+pub async fn api_client() -> reqwest::Client {
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    let mut headers = HeaderMap::new();
+
+    if wasm_cookies::get(CSRF_COOKIE).is_none() {
+        // Any response sets the cookie, a failure just leaves the request to be rejected
+        let _ = reqwest::get(get_base_url().join("api/v1/csrf").unwrap()).await;
+    }
+
+    if let Some(Ok(token)) = wasm_cookies::get(CSRF_COOKIE) {
+        if let Ok(value) = HeaderValue::from_str(&token) {
+            headers.insert(CSRF_HEADER, value);
+        }
+    }
+
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap()
+}
+
 /// API Error return types
 #[derive(Debug)]
 pub enum APIError {
     AuthenticationError,
+    ValidationError(ValidationErrors),
+    TooManyRequests,
     InternalServerError,
     TimeoutError,
     RequestError(reqwest::Error),
-    UnknownStatus(u16)
+    Rejected(ApiError)
 }
 
 impl From<reqwest::Error> for APIError {
@@ -37,14 +71,52 @@ impl From<reqwest::StatusCode> for APIError {
         use APIError::*;
         match status.as_u16() {
             401 => AuthenticationError,
+            429 => TooManyRequests,
             500 => InternalServerError,
             200..=299 => panic!("Tried to parse http success status code {} as APIError", status),
-            other => UnknownStatus(other)
+            other => Rejected(ApiError::new(other, status.canonical_reason().unwrap_or("Unknown error")))
+        }
+    }
+
+}
+
+impl From<ApiError> for APIError {
+
+    fn from(err: ApiError) -> Self {
+        use APIError::*;
+        match err.code {
+            401 => AuthenticationError,
+            422 => ValidationError(err.validation_errors()),
+            429 => TooManyRequests,
+            500 => InternalServerError,
+            _ => Rejected(err)
         }
     }
 
 }
 
+/// Response helper for decoding the [ApiError] body of failed requests
+#[async_trait(?Send)]
+pub trait CheckStatus: Sized {
+    async fn check_status(self) -> Result<Self, APIError>;
+}
+
+#[async_trait(?Send)]
+impl CheckStatus for reqwest::Response {
+    async fn check_status(self) -> Result<Self, APIError> {
+        let status = self.status();
+        if status.is_success() {
+            return Ok(self);
+        }
+
+        // Fall back to the status alone if the body isn't an ApiError (such as from a proxy)
+        match self.json::<ApiError>().await {
+            Ok(err) => Err(APIError::from(err)),
+            Err(_) => Err(APIError::from(status))
+        }
+    }
+}
+
 /// Client request builder helper function
 pub trait AppendQuery {
     fn query_pair<T: Into<String>, U: serde::Serialize>(self, label: T, data: Option<U>) -> reqwest::RequestBuilder;
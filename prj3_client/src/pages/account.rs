@@ -0,0 +1,379 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use bounce::prelude::*;
+
+use wasm_bindgen::JsCast;
+
+use wasm_bindgen_futures::spawn_local;
+
+use shared_types::types::{AccountDeleteForm, ApiTokenForm, ApiTokenInfo, PasswordChangeForm, TokenScope};
+use shared_types::validation::*;
+
+use crate::mutations::ServiceError;
+use crate::mutations::account::*;
+use crate::stores::auth::*;
+use crate::Route;
+
+/// State for a form on the account page
+#[derive(PartialEq, Default)]
+struct FormState {
+    loading: bool,
+    error: Option<String>,
+    success: Option<String>,
+}
+
+impl FormState {
+    fn error<T: Into<String>>(error: T) -> Self {
+        FormState {
+            error: Some(error.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Message to show for an account error
+fn error_message(err: AccountError) -> String {
+    match err {
+        AccountError::IncorrectPassword => "Incorrect password".into(),
+        AccountError::InvalidInput(errors) => errors.get(NEW_PASSWORD_FIELD)
+            .map(|err| format!("New password {}", err))
+            .or_else(|| errors.get(TOKEN_NAME_FIELD).map(|err| format!("Name {}", err)))
+            .or_else(|| errors.get(TOKEN_SCOPES_FIELD).map(|_| "Select at least one permission".into()))
+            .unwrap_or_else(|| "Invalid input".into()),
+        AccountError::Other(ServiceError::UnableToContactServer) => "Unable to contact server, please try again later".into(),
+        AccountError::Other(ServiceError::InternalServerError) => "Server error occured, please try again later".into(),
+        AccountError::Other(ServiceError::Rejected(err)) => err.message
+    }
+}
+
+/// Get the form element and data from a submit event
+fn get_form(e: &FocusEvent) -> (web_sys::HtmlFormElement, web_sys::FormData) {
+    let element = e.target().unwrap().dyn_into::<web_sys::HtmlFormElement>().unwrap();
+    let data = web_sys::FormData::new_with_form(&element).unwrap();
+    (element, data)
+}
+
+/// Account settings page component
+#[function_component(Account)]
+pub fn account() -> Html {
+    // State of each form
+    let password_state = use_state_eq(FormState::default);
+    let delete_state = use_state_eq(FormState::default);
+
+    // Credentials we have gotten
+    let credentials = use_atom::<AuthCredentials>();
+
+    // For redirect when logged out
+    let history = use_history().unwrap();
+
+    // Only logged in users have account settings
+    let user_id = match *credentials {
+        AuthCredentials::Verified(ref info) => info.user_id.clone(),
+        AuthCredentials::Guest => {
+            history.push(Route::Login);
+            return html! {};
+        }
+    };
+
+    // Callback when the change password form is submitted
+    let submit_password = {
+        let state = password_state.clone();
+        let user_id = user_id.clone();
+        Callback::from(move |e: FocusEvent| {
+            e.prevent_default();
+
+            let (element, data) = get_form(&e);
+
+            let form = PasswordChangeForm {
+                current_password: data.get("current_password").as_string().unwrap(),
+                new_password: data.get("new_password").as_string().unwrap()
+            };
+
+            // Check the new password before sending it
+            if form.new_password != data.get("confirm_password").as_string().unwrap() {
+                state.set(FormState::error("New passwords do not match"));
+                return;
+            }
+
+            if let Err(err) = validate_password(&user_id, &form.new_password) {
+                state.set(FormState::error(format!("New password {}", err)));
+                return;
+            }
+
+            state.set(FormState { loading: true, ..Default::default() });
+
+            let state = state.clone();
+            spawn_local(async move {
+                match change_password(&form).await {
+                    Ok(()) => {
+                        element.reset();
+                        state.set(FormState {
+                            success: Some("Password changed, you have been logged out everywhere else and your API tokens were revoked".into()),
+                            ..Default::default()
+                        })
+                    },
+                    Err(err) => state.set(FormState::error(error_message(err)))
+                }
+            });
+        })
+    };
+
+    // Callback when the delete account form is submitted
+    let submit_delete = {
+        let state = delete_state.clone();
+        let credentials = credentials.clone();
+        let history = history.clone();
+        Callback::from(move |e: FocusEvent| {
+            e.prevent_default();
+
+            let (_, data) = get_form(&e);
+
+            let form = AccountDeleteForm {
+                password: data.get("password").as_string().unwrap()
+            };
+
+            // Make sure the user really wants to do this
+            let confirmed = web_sys::window()
+                .unwrap()
+                .confirm_with_message("Delete your account? This cannot be undone.")
+                .unwrap_or(false);
+
+            if !confirmed {
+                return;
+            }
+
+            state.set(FormState { loading: true, ..Default::default() });
+
+            let state = state.clone();
+            let credentials = credentials.clone();
+            let history = history.clone();
+            spawn_local(async move {
+                match delete_account(&form).await {
+                    Ok(()) => {
+                        credentials.set(AuthCredentials::Guest);
+                        history.push(Route::Home)
+                    },
+                    Err(err) => state.set(FormState::error(error_message(err)))
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="container is-max-desktop center-form">
+            <h1 class="title has-text-centered mt-6">{"Account Settings"}</h1>
+            <h2 class="subtitle has-text-centered">{user_id}</h2>
+
+            <div class="box mt-6">
+                <h3 class="title is-5">{"Change Password"}</h3>
+                <form onsubmit={submit_password}>
+                    <div class="field">
+                        <label class="label">{"Current Password"}</label>
+                        <div class="control">
+                            <input class="input" type="password" name="current_password" placeholder="********"/>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <label class="label">{"New Password"}</label>
+                        <div class="control">
+                            <input class="input" type="password" name="new_password" placeholder="********"/>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <label class="label">{"Confirm New Password"}</label>
+                        <div class="control">
+                            <input class="input" type="password" name="confirm_password" placeholder="********"/>
+                        </div>
+                    </div>
+                    <p class="help is-danger" hidden={password_state.error.is_none()}>{password_state.error.clone().unwrap_or_default()}</p>
+                    <p class="help is-success" hidden={password_state.success.is_none()}>{password_state.success.clone().unwrap_or_default()}</p>
+
+                    <button class="button is-primary mt-4" disabled={password_state.loading}>{"Change Password"}</button>
+                </form>
+            </div>
+
+            <div class="box mt-6">
+                <h3 class="title is-5">{"API Tokens"}</h3>
+                <ApiTokens/>
+            </div>
+
+            <div class="box mt-6">
+                <h3 class="title is-5 has-text-danger">{"Delete Account"}</h3>
+                <p>{"Your match records will be kept for the game statistics but will no longer show your username."}</p>
+                <form class="mt-4" onsubmit={submit_delete}>
+                    <div class="field">
+                        <label class="label">{"Password"}</label>
+                        <div class="control">
+                            <input class="input" type="password" name="password" placeholder="********"/>
+                        </div>
+                    </div>
+                    <p class="help is-danger" hidden={delete_state.error.is_none()}>{delete_state.error.clone().unwrap_or_default()}</p>
+
+                    <button class="button is-danger mt-4" disabled={delete_state.loading}>{"Delete Account"}</button>
+                </form>
+            </div>
+        </div>
+    }
+}
+
+/// Personal API tokens section of the account page
+/// 
+/// Lists the user's tokens with a button to revoke each and a form to create new ones
+#[function_component(ApiTokens)]
+fn api_tokens() -> Html {
+    // Tokens of the user (None until loaded)
+    let tokens = use_state_eq(|| None::<Vec<ApiTokenInfo>>);
+    // Token that was just created, only shown once
+    let new_token = use_state_eq(|| None::<String>);
+    let form_state = use_state_eq(FormState::default);
+
+    // Load tokens on first render
+    {
+        let tokens = tokens.clone();
+        let form_state = form_state.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match list_tokens().await {
+                        Ok(list) => tokens.set(Some(list)),
+                        Err(err) => form_state.set(FormState::error(error_message(err)))
+                    }
+                });
+                || {}
+            },
+            ()
+        );
+    }
+
+    // Callback when the create token form is submitted
+    let submit_token = {
+        let tokens = tokens.clone();
+        let new_token = new_token.clone();
+        let state = form_state.clone();
+        Callback::from(move |e: FocusEvent| {
+            e.prevent_default();
+
+            let (element, data) = get_form(&e);
+
+            let form = ApiTokenForm {
+                name: data.get("name").as_string().unwrap(),
+                read_records: data.get("read_records").as_string().is_some(),
+                submit_records: data.get("submit_records").as_string().is_some()
+            };
+
+            // Check the form before sending it
+            if let Err(errors) = validate_new_token(&form.name, &form.scopes()) {
+                state.set(FormState::error(error_message(AccountError::InvalidInput(errors))));
+                return;
+            }
+
+            state.set(FormState { loading: true, ..Default::default() });
+
+            let tokens = tokens.clone();
+            let new_token = new_token.clone();
+            let state = state.clone();
+            spawn_local(async move {
+                match create_token(&form).await {
+                    Ok(created) => {
+                        element.reset();
+                        let mut list = (*tokens).clone().unwrap_or_default();
+                        list.insert(0, created.info);
+                        tokens.set(Some(list));
+                        new_token.set(Some(created.token));
+                        state.set(FormState::default());
+                    },
+                    Err(err) => state.set(FormState::error(error_message(err)))
+                }
+            });
+        })
+    };
+
+    // Callback for revoking a token
+    let revoke = |id: i32| {
+        let tokens = tokens.clone();
+        let state = form_state.clone();
+        Callback::from(move |_| {
+            let tokens = tokens.clone();
+            let state = state.clone();
+            spawn_local(async move {
+                match revoke_token(id).await {
+                    Ok(()) => tokens.set(
+                        (*tokens).clone().map(|list| list.into_iter().filter(|token| token.id != id).collect())
+                    ),
+                    Err(err) => state.set(FormState::error(error_message(err)))
+                }
+            });
+        })
+    };
+
+    let token_rows = match *tokens {
+        None => html! { <tr><td colspan="5">{"Loading..."}</td></tr> },
+        Some(ref list) if list.is_empty() => html! { <tr><td colspan="5">{"No tokens"}</td></tr> },
+        Some(ref list) => list.iter().map(|token| html! {
+            <tr>
+                <td>{&token.name}</td>
+                <td>{
+                    token.scopes.iter().map(|scope| match scope {
+                        TokenScope::ReadRecords => "Read records",
+                        TokenScope::SubmitRecords => "Submit records"
+                    }).collect::<Vec<_>>().join(", ")
+                }</td>
+                <td>{token.created_at.format("%Y-%m-%d").to_string()}</td>
+                <td>{token.last_used.map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string()).unwrap_or_else(|| "Never".into())}</td>
+                <td><button class="button is-small is-danger is-outlined" onclick={revoke(token.id)}>{"Revoke"}</button></td>
+            </tr>
+        }).collect::<Html>()
+    };
+
+    html! {
+        <>
+            <p>{"Tokens let scripts use your account through the API by sending the header "}<code>{"Authorization: Bearer <token>"}</code></p>
+            <table class="table is-fullwidth mt-4">
+                <thead>
+                    <tr>
+                        <th>{"Name"}</th>
+                        <th>{"Permissions"}</th>
+                        <th>{"Created"}</th>
+                        <th>{"Last Used"}</th>
+                        <th></th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {token_rows}
+                </tbody>
+            </table>
+            {
+                if let Some(ref token) = *new_token {
+                    html! {
+                        <div class="notification is-success is-light">
+                            <p>{"Copy your new token now, it won't be shown again:"}</p>
+                            <code>{token}</code>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            <form onsubmit={submit_token}>
+                <div class="field">
+                    <label class="label">{"Name"}</label>
+                    <div class="control">
+                        <input class="input" type="text" name="name" placeholder="My script"/>
+                    </div>
+                </div>
+                <div class="field">
+                    <label class="checkbox mr-4">
+                        <input type="checkbox" name="read_records" checked=true/>{" Read records"}
+                    </label>
+                    <label class="checkbox">
+                        <input type="checkbox" name="submit_records"/>{" Submit records"}
+                    </label>
+                </div>
+                <p class="help is-danger" hidden={form_state.error.is_none()}>{form_state.error.clone().unwrap_or_default()}</p>
+
+                <button class="button is-primary mt-4" disabled={form_state.loading}>{"Create Token"}</button>
+            </form>
+        </>
+    }
+}
@@ -0,0 +1,88 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use bounce::prelude::*;
+
+use wasm_bindgen_futures::spawn_local;
+
+use shared_types::achievements::{AchievementInfo, ACHIEVEMENTS};
+use shared_types::types::UnlockedAchievement;
+
+use crate::mutations::achievements::get_achievements;
+use crate::stores::auth::AuthCredentials;
+use crate::Route;
+
+/// Render an achievement, greyed out if it is still locked
+fn achievement_card(info: &AchievementInfo, unlocked: Option<&UnlockedAchievement>) -> Html {
+    html! {
+        <div class={classes!("box", unlocked.is_none().then(|| "has-text-grey-light"))}>
+            <p class="is-size-5 has-text-weight-bold">
+                { info.name }
+                {
+                    match unlocked {
+                        Some(unlocked) => html! {
+                            <span class="tag is-warning ml-2">{ format!("Unlocked {}", unlocked.unlocked_at.format("%Y-%m-%d")) }</span>
+                        },
+                        None => html! { <span class="tag ml-2">{"Locked"}</span> }
+                    }
+                }
+            </p>
+            <p>{ info.description }</p>
+        </div>
+    }
+}
+
+/// Achievements page component
+///
+/// Lists every achievement along with when the logged in user unlocked it
+#[function_component(Achievements)]
+pub fn achievements() -> Html {
+    let credentials = use_atom::<AuthCredentials>();
+    let history = use_history().unwrap();
+
+    // Achievements the user has unlocked (None while loading)
+    let unlocked = use_state_eq(|| None::<Result<Vec<UnlockedAchievement>, String>>);
+
+    {
+        let unlocked = unlocked.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    unlocked.set(Some(get_achievements().await.map_err(|err| err.to_string())));
+                });
+                || {}
+            },
+            ()
+        );
+    }
+
+    // Only logged in users have achievements
+    if let AuthCredentials::Guest = *credentials {
+        history.push(Route::Login);
+        return html! {};
+    }
+
+    html! {
+        <div class="container mt-6" style={"max-width:700px;"}>
+            <h1 class="title has-text-centered mt-6">{"Achievements"}</h1>
+            {
+                match *unlocked {
+                    None => html! { <p>{"Loading achievements..."}</p> },
+                    Some(Err(_)) => html! { <p>{"Error loading achievements"}</p> },
+                    Some(Ok(ref unlocked)) => html! {
+                        <>
+                            <p class="has-text-centered mb-5">
+                                { format!("{} of {} unlocked", unlocked.len(), ACHIEVEMENTS.len()) }
+                            </p>
+                            {
+                                ACHIEVEMENTS.iter().map(|info| {
+                                    achievement_card(info, unlocked.iter().find(|unlocked| unlocked.achievement == info.achievement))
+                                }).collect::<Html>()
+                            }
+                        </>
+                    }
+                }
+            }
+        </div>
+    }
+}
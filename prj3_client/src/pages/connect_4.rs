@@ -24,6 +24,9 @@ pub struct Connect4 {
     //possible disc color options
     disc_colors: Vec<String>,
 
+    //choices for who moves first
+    first_movers: Vec<String>,
+
     //chosen difficulty
     selected_difficulty: String,
 
@@ -33,12 +36,18 @@ pub struct Connect4 {
     //chosen board size
     selected_board_size: String,
 
+    //chosen letter inventory (TOOT and OTTO only)
+    selected_letter_mode: String,
+
+    //chosen first mover
+    selected_first_mover: String,
+
     //should game start
     should_start: bool,
 }
 
 pub enum Msg {
-    StartPressed([String; 3]),
+    StartPressed([String; 5]),
 }
 
 impl Component for Connect4 {
@@ -56,9 +65,12 @@ impl Component for Connect4 {
             difficulties: vec!["Easy".to_string(), "Medium".to_string(), "Hard".to_string()],
             board_sizes: vec!["7x6".to_string(), "5x4".to_string()],
             disc_colors: vec!["#FF8E8E".to_string(), "#FFE68E".to_string(), "black".to_string()],
+            first_movers: vec!["You first".to_string(), "CPU first".to_string(), "Random".to_string()],
             selected_difficulty: "Easy".to_string(),
             selected_disc_color: "#FF8E8E".to_string(),
             selected_board_size: "5x4".to_string(),
+            selected_letter_mode: "".to_string(),
+            selected_first_mover: "You first".to_string(),
             should_start: false,
         }
     }
@@ -70,6 +82,8 @@ impl Component for Connect4 {
                 self.selected_difficulty = selections[0].clone();
                 self.selected_board_size = selections[1].clone();
                 self.selected_disc_color = selections[2].clone();
+                self.selected_letter_mode = selections[3].clone();
+                self.selected_first_mover = selections[4].clone();
                 self.should_start = true;
                 true
             }
@@ -89,6 +103,8 @@ impl Component for Connect4 {
                     selected_difficulty = {self.selected_difficulty.clone()}
                     selected_board_size = {self.selected_board_size.clone()}
                     selected_disc_color = {self.selected_disc_color.clone()}
+                    selected_letter_mode = {self.selected_letter_mode.clone()}
+                    selected_first_mover = {self.selected_first_mover.clone()}
                     columns = {columns}
                     rows = {rows}
                 />
@@ -103,6 +119,7 @@ impl Component for Connect4 {
                     difficulties = {self.difficulties.clone()}
                     board_sizes = {self.board_sizes.clone()}
                     disc_colors = {self.disc_colors.clone()}
+                    first_movers = {self.first_movers.clone()}
                     start_pressed = {ctx.link().callback(Msg::StartPressed).clone()}
                 />
             }
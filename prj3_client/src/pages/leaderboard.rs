@@ -1,166 +1,248 @@
 use yew::prelude::*;
+use yew_router::prelude::*;
 
-use bounce::query::{use_mutation_value};
+use wasm_bindgen::JsCast;
 
-use shared_types::types::{MatchQuerySortBy, MatchQueryFilter, GameType, CpuLevel, MatchResult, Records, MatchRecord};
+use wasm_bindgen_futures::spawn_local;
 
-use crate::mutations::match_records::{MatchRecordQuery, MatchRecordQueryOptions};
+use shared_types::types::{BoardSize, CpuLevel, GameType, Leaderboard as LeaderboardData, LeaderboardEntry, LeaderboardPeriod};
 
-#[derive(PartialEq)]
-struct LeaderboardState {
-    is_on_connect_4: bool,
-    error: Option<String>,
+use crate::mutations::leaderboard::{get_leaderboard, LeaderboardOptions};
+use crate::Route;
+
+/// Number of entries on each page
+const PAGE_SIZE: i64 = 10;
+
+/// Board sizes that can be picked for a game (see the game pages)
+fn board_sizes(game: GameType) -> Vec<BoardSize> {
+    match game {
+        GameType::Connect4 => vec![BoardSize { columns: 7, rows: 6 }, BoardSize { columns: 5, rows: 4 }],
+        GameType::OttoToot => vec![BoardSize { columns: 6, rows: 4 }, BoardSize { columns: 7, rows: 7 }]
+    }
+}
+
+/// Get the value of the select element that changed
+fn select_value(e: &Event) -> String {
+    e.target().unwrap().dyn_into::<web_sys::HtmlSelectElement>().unwrap().value()
+}
+
+/// Render a row of the leaderboard
+fn entry_row(entry: &LeaderboardEntry, is_own: bool) -> Html {
+    html! {
+        <tr class={classes!(is_own.then(|| "is-selected"))}>
+            <td>{format!("#{}", entry.rank)}</td>
+            <td>
+                <Link<Route> to={Route::Profile { id: entry.user_id.clone() }}>{&entry.user_id}</Link<Route>>
+            </td>
+            <td>{entry.wins}</td>
+            <td>{entry.losses}</td>
+            <td>{entry.ties}</td>
+            <td>{format!("{:.0}%", entry.win_rate * 100.0)}</td>
+            <td>{entry.best_win.map(|moves| moves.to_string()).unwrap_or_else(|| "-".into())}</td>
+        </tr>
+    }
 }
 
-/// Login page component
+/// Leaderboard page component
+///
+/// Ranks the players of a game by their wins, with filters for the CPU level, board size and period
 #[function_component(Leaderboard)]
 pub fn leaderboard() -> Html {
-    // Get state
-    let state = use_state_eq(|| LeaderboardState {
-        is_on_connect_4: true,
-        error: None
+    let options = use_state_eq(|| LeaderboardOptions {
+        game: GameType::Connect4,
+        level: None,
+        board_size: None,
+        period: LeaderboardPeriod::All,
+        limit: PAGE_SIZE,
+        offset: 0
     });
 
-    let should_refresh_query = use_mut_ref(|| true);
-
-    // Get the option to filter the game
-    let game_filter = if state.is_on_connect_4 {
-        GameType::Connect4
-    } else {
-        GameType::OttoToot
-    };
+    // Leaderboard for the current options (None while loading)
+    let leaderboard = use_state_eq(|| None::<Result<LeaderboardData, String>>);
 
-    // todo: determine which CPU types to filter
-
-    // Get query handle
-    let records_query = use_mutation_value::<MatchRecordQuery>();
-
-    // Handle the query state (doesn't need to be handed here)
-    let srq = *should_refresh_query.borrow();
-    let body = match records_query.result() {
-        Some(Ok(query)) if !srq => {
-            let records: &Records<MatchRecord> = &query.0; // Left the type in to make it easy to identify what it is
-            let x = &records.records;
-            //render each leaderboard record
-            x.iter().enumerate().map(|(i, record)| {
-                log::info!("{:#?}", record);
-                let level;
-                if record.cpu_level == CpuLevel::Easy {
-                    level = "Easy";
-                } else if record.cpu_level == CpuLevel::Medium {
-                    level = "Medium";
-                } else {
-                    level = "Hard";
-                }
-                html! {
-                    <div class="leaderboard-card mt-2 mb-2">
-                        <div class="rank">{format!("#{}", i+1)}</div>
-                        <div class="name">{record.user_id.as_ref().unwrap()}</div>
-                        <div class="difficulty">{level}</div>
-                        <div class="moves">{record.moves}</div>
-                    </div>
-                }
-            }).collect::<Html>()
-        },
-        Some(Err(_err)) if !srq => {html!{ <div class="leaderboard-card mt-2 mb-2">{"Error loading leaderboard"}</div> }},
-        _ => {html!{ <div class="leaderboard-card mt-2 mb-2">{"Loading leaderboard..."}</div> }}
-    };
-
-    // Update query if necessary
+    // Reload whenever the options change
     {
-        let mut srq_mut = should_refresh_query.borrow_mut();
-        if *srq_mut {
-            *srq_mut = false;
-
-            // Set up filter options
-            let filters = MatchQueryFilter {
-                result: vec![MatchResult::Win],
-                game: vec![game_filter],
-                level: vec![]
-            };
-
-            let records_query = records_query.clone();
-            wasm_bindgen_futures::spawn_local(async move {
-                let _res = records_query.run(
-                    MatchRecordQueryOptions {
-                        limit: None,                               // Option<i64>
-                        offset: None,                              // Option<i64>
-                        filters: Some(filters),                    // &Option<MatchQueryFilter>
-                        sort_by: Some(MatchQuerySortBy::Duration), // Option<MatchQuerySortBy>
-                        asc: Some(true)                            // Option<bool>
-                    }
-                ).await;
-            })
-        }
+        let leaderboard = leaderboard.clone();
+        use_effect_with_deps(
+            move |options: &LeaderboardOptions| {
+                let options = *options;
+                leaderboard.set(None);
+                spawn_local(async move {
+                    leaderboard.set(Some(get_leaderboard(&options).await.map_err(|err| err.to_string())));
+                });
+                || {}
+            },
+            *options
+        );
     }
 
-    let mut connect_class = "is-active";
-    let mut toot_class = "";
+    // Update the options and go back to the first page
+    let set_options = |update: fn(&mut LeaderboardOptions, String)| {
+        let options = options.clone();
+        Callback::from(move |e: Event| {
+            let mut new_options = *options;
+            update(&mut new_options, select_value(&e));
+            new_options.offset = 0;
+            options.set(new_options);
+        })
+    };
 
-    if !state.is_on_connect_4 {
-        connect_class = "";
-        toot_class = "is-active";
-    }
-    // Callback for switching to toot leaderboard
-    let switch_to_toot = {
-        let state = state.clone();
-        let should_refresh_query = should_refresh_query.clone();
+    let switch_game = |game: GameType| {
+        let options = options.clone();
         Callback::from(move |_| {
-            *should_refresh_query.borrow_mut() = true;
-            state.set(LeaderboardState {
-                is_on_connect_4: false,
-                error: None
+            options.set(LeaderboardOptions {
+                game,
+                board_size: None,
+                offset: 0,
+                ..*options
             })
         })
     };
 
-    // Callback for switching to connect 4 leaderboard
-    let switch_to_connect = {
-        let state = state.clone();
-        let should_refresh_query = should_refresh_query.clone();
+    let change_page = |offset: i64| {
+        let options = options.clone();
         Callback::from(move |_| {
-            *should_refresh_query.borrow_mut() = true;
-            state.set(LeaderboardState {
-                is_on_connect_4: true,
-                error: None
-            })
+            options.set(LeaderboardOptions { offset, ..*options })
         })
     };
 
+    let on_level_change = set_options(|options, value| {
+        options.level = match value.as_str() {
+            "easy" => Some(CpuLevel::Easy),
+            "medium" => Some(CpuLevel::Medium),
+            "hard" => Some(CpuLevel::Hard),
+            _ => None
+        }
+    });
+
+    let on_size_change = set_options(|options, value| {
+        options.board_size = value.parse().ok();
+    });
+
+    let on_period_change = set_options(|options, value| {
+        options.period = match value.as_str() {
+            "day" => LeaderboardPeriod::Day,
+            "week" => LeaderboardPeriod::Week,
+            "month" => LeaderboardPeriod::Month,
+            _ => LeaderboardPeriod::All
+        }
+    });
+
+    let (body, own_entry, total_count) = match *leaderboard {
+        None => (html! { <tr><td colspan="7">{"Loading leaderboard..."}</td></tr> }, None, 0),
+        Some(Err(_)) => (html! { <tr><td colspan="7">{"Error loading leaderboard"}</td></tr> }, None, 0),
+        Some(Ok(ref data)) if data.ranking.records.is_empty() => (
+            html! { <tr><td colspan="7">{"No matches played yet"}</td></tr> },
+            data.own_entry.clone(),
+            data.ranking.total_count.unwrap_or(0)
+        ),
+        Some(Ok(ref data)) => {
+            let own_user = data.own_entry.as_ref().map(|entry| entry.user_id.clone());
+            (
+                data.ranking.records.iter()
+                    .map(|entry| entry_row(entry, Some(&entry.user_id) == own_user.as_ref()))
+                    .collect::<Html>(),
+                data.own_entry.clone(),
+                data.ranking.total_count.unwrap_or(0)
+            )
+        }
+    };
+
+    // Only show the user's own entry separately when it isn't on the page
+    let own_entry = own_entry.filter(|entry| {
+        entry.rank <= options.offset || entry.rank > options.offset + options.limit
+    });
+
+    let (connect_class, toot_class) = match options.game {
+        GameType::Connect4 => ("is-active", ""),
+        GameType::OttoToot => ("", "is-active")
+    };
+
     html! {
-            <div class="container mt-6" style={"max-width:500px;"}>
+        <div class="container mt-6" style={"max-width:700px;"}>
+            <h1 class="title has-text-centered mt-6">{
+                match options.game {
+                    GameType::Connect4 => "Connect 4 Leaderboard",
+                    GameType::OttoToot => "TOOT OTTO Leaderboard"
+                }
+            }</h1>
+            <div class="tabs is-centered is-boxed pt-5">
+                <ul>
+                    <li class={connect_class} onclick={switch_game(GameType::Connect4)}>
+                        <a><span>{"Connect 4"}</span></a>
+                    </li>
+                    <li class={toot_class} onclick={switch_game(GameType::OttoToot)}>
+                        <a><span>{"TOOT OTTO"}</span></a>
+                    </li>
+                </ul>
+            </div>
+            <div class="field is-grouped is-grouped-centered">
+                <div class="control">
+                    <div class="select">
+                        <select onchange={on_level_change}>
+                            <option value="" selected={options.level.is_none()}>{"Any difficulty"}</option>
+                            <option value="easy" selected={options.level == Some(CpuLevel::Easy)}>{"Easy"}</option>
+                            <option value="medium" selected={options.level == Some(CpuLevel::Medium)}>{"Medium"}</option>
+                            <option value="hard" selected={options.level == Some(CpuLevel::Hard)}>{"Hard"}</option>
+                        </select>
+                    </div>
+                </div>
+                <div class="control">
+                    <div class="select">
+                        <select onchange={on_size_change}>
+                            <option value="" selected={options.board_size.is_none()}>{"Any board size"}</option>
+                            {
+                                board_sizes(options.game).into_iter().map(|size| html! {
+                                    <option value={size.to_string()} selected={options.board_size == Some(size)}>{size.to_string()}</option>
+                                }).collect::<Html>()
+                            }
+                        </select>
+                    </div>
+                </div>
+                <div class="control">
+                    <div class="select">
+                        <select onchange={on_period_change}>
+                            <option value="all" selected={options.period == LeaderboardPeriod::All}>{"All time"}</option>
+                            <option value="month" selected={options.period == LeaderboardPeriod::Month}>{"Past month"}</option>
+                            <option value="week" selected={options.period == LeaderboardPeriod::Week}>{"Past week"}</option>
+                            <option value="day" selected={options.period == LeaderboardPeriod::Day}>{"Past day"}</option>
+                        </select>
+                    </div>
+                </div>
+            </div>
+            <table class="table is-fullwidth is-hoverable">
+                <thead>
+                    <tr>
+                        <th>{"Rank"}</th>
+                        <th>{"Name"}</th>
+                        <th>{"Wins"}</th>
+                        <th>{"Losses"}</th>
+                        <th>{"Ties"}</th>
+                        <th>{"Win Rate"}</th>
+                        <th>{"Best Win (moves)"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { body }
+                </tbody>
                 {
-                    if state.is_on_connect_4 {
+                    if let Some(ref entry) = own_entry {
                         html! {
-                            <h1 class="title has-text-centered mt-6">{"Connect 4 Leaderboard"}</h1>
+                            <tfoot>
+                                { entry_row(entry, true) }
+                            </tfoot>
                         }
                     } else {
-                        html! {
-                            <h1 class="title has-text-centered mt-6">{"TOOT OTTO Leaderboard"}</h1>
-                        }
+                        html! {}
                     }
                 }
-                <div class="tabs is-centered is-boxed pt-5">
-                    <ul>
-                        <li class={connect_class} onclick={switch_to_connect}>
-                          <a>
-                            <span>{"Connect 4"}</span>
-                          </a>
-                        </li>
-                        <li class={toot_class}  onclick={switch_to_toot}>
-                          <a>
-                            <span>{"TOOT OTTO"}</span>
-                          </a>
-                        </li>
-                    </ul>
-                </div>
-                <div class="leaderboard-card">
-                    <div class="rank bold">{"Rank"}</div>
-                    <div class="name bold">{"Name"}</div>
-                    <div class="difficulty bold">{"Difficulty"}</div>
-                    <div class="moves bold">{"Moves"}</div>
-                </div>
-                { body }
-            </div>
-        }
+            </table>
+            <nav class="pagination is-centered">
+                <button class="button pagination-previous" disabled={options.offset == 0}
+                    onclick={change_page((options.offset - options.limit).max(0))}>{"Previous"}</button>
+                <button class="button pagination-next" disabled={options.offset + options.limit >= total_count}
+                    onclick={change_page(options.offset + options.limit)}>{"Next"}</button>
+            </nav>
+        </div>
+    }
 }
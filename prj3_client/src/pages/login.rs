@@ -9,6 +9,7 @@ use wasm_bindgen::{JsCast};
 use wasm_bindgen_futures::spawn_local;
 
 use shared_types::types::{UserAuthForm, UserInfo};
+use shared_types::validation::*;
 
 use crate::mutations::ServiceError;
 use crate::mutations::auth::*;
@@ -20,6 +21,7 @@ use crate::Route;
 struct LoginState {
     is_on_login: bool,
     error: Option<String>,
+    field_errors: ValidationErrors,
 }
 
 /// Login page component
@@ -28,7 +30,8 @@ pub fn login() -> Html {
     // Get state
     let state = use_state_eq(|| LoginState {
         is_on_login: true,
-        error: None
+        error: None,
+        field_errors: ValidationErrors::default()
     });
 
     // Mutation for authentication
@@ -66,7 +69,8 @@ pub fn login() -> Html {
         let state = state.clone();
         Callback::from(move |_| state.set(LoginState {
             is_on_login: true,
-            error: None
+            error: None,
+            field_errors: ValidationErrors::default()
         }))
     };
 
@@ -75,7 +79,8 @@ pub fn login() -> Html {
         let state = state.clone();
         Callback::from(move |_| state.set(LoginState {
             is_on_login: false,
-            error: None
+            error: None,
+            field_errors: ValidationErrors::default()
         }))
     };
 
@@ -103,6 +108,18 @@ pub fn login() -> Html {
                 LoginAs::NewUser
             };
 
+            // Check new users against the same rules as the server before sending
+            if login_as == LoginAs::NewUser {
+                if let Err(field_errors) = validate_new_user(&form.user_id, &form.password) {
+                    state.set(LoginState {
+                        is_on_login: state.is_on_login,
+                        error: None,
+                        field_errors
+                    });
+                    return;
+                }
+            }
+
             // Register the mutation to be ran when ready
             let auth_mutation = auth_mutation.clone();
             let state = state.clone();
@@ -127,16 +144,27 @@ pub fn login() -> Html {
                         );
                         history.push(Route::Home)
                     },
+                    // Show errors for each field
+                    Err(AuthError::InvalidInput(field_errors)) => state.set(
+                        LoginState {
+                            is_on_login: state.is_on_login,
+                            error: None,
+                            field_errors
+                        }
+                    ),
                     // Handle error
                     Err(err) => state.set(
                         LoginState {
                             is_on_login: state.is_on_login,
-                            error: Some(match err {
+                            error: Some(match &err {
                                 AuthError::InvalidCredentials => "Invalid username or password",
-                                AuthError::RegisterTakenUsername => "Username already in use",
+                                AuthError::InvalidInput(_) => unreachable!(),
+                                AuthError::TooManyAttempts => "Too many failed logins, please wait before trying again",
                                 AuthError::Other(ServiceError::UnableToContactServer) => "Unable to contact server, please try again later",
-                                AuthError::Other(ServiceError::InternalServerError) => "Server error occured, please try again later"
-                            }.into())
+                                AuthError::Other(ServiceError::InternalServerError) => "Server error occured, please try again later",
+                                AuthError::Other(ServiceError::Rejected(err)) => &err.message
+                            }.into()),
+                            field_errors: ValidationErrors::default()
                         }
                     )
                 }
@@ -150,11 +178,16 @@ pub fn login() -> Html {
         Callback::from(move |_| {
             state.set(LoginState {
                 is_on_login: state.is_on_login,
-                error: None
+                error: None,
+                field_errors: ValidationErrors::default()
             })
         })
     };
 
+    // Error messages for each field
+    let username_error = state.field_errors.get(USERNAME_FIELD).map(|err| format!("Username {}", err));
+    let password_error = state.field_errors.get(PASSWORD_FIELD).map(|err| format!("Password {}", err));
+
     html! {
         <div class="container is-max-desktop center-form">
             <h1 class="title has-text-centered mt-6">{"Boardgames"}</h1>
@@ -174,14 +207,16 @@ pub fn login() -> Html {
                 <div class="field mt-4">
                     <label class="label">{"Username"}</label>
                     <div class="control">
-                        <input class="input" type="username" name="username" placeholder="e.g. lora"/>
+                        <input class={classes!("input", username_error.as_ref().map(|_| "is-danger"))} type="username" name="username" placeholder="e.g. lora"/>
                     </div>
+                    <p class="help is-danger" hidden={username_error.is_none()}>{username_error.clone().unwrap_or_default()}</p>
                 </div>
                 <div class="field mt-4">
                     <label class="label">{"Password"}</label>
                     <div class="control">
-                        <input class="input" type="password" name="password" placeholder="********"/>
+                        <input class={classes!("input", password_error.as_ref().map(|_| "is-danger"))} type="password" name="password" placeholder="********"/>
                     </div>
+                    <p class="help is-danger" hidden={password_error.is_none()}>{password_error.clone().unwrap_or_default()}</p>
                 </div>
                 <p class="help is-danger" hidden={state.error.is_none()}>{state.error.as_ref().unwrap_or(&"".into())}</p>
 
@@ -1,6 +1,11 @@
+pub mod account;
 pub mod home;
 pub mod login;
 pub mod connect_4;
 pub mod page_not_found;
 pub mod toot;
-pub mod leaderboard;
\ No newline at end of file
+pub mod leaderboard;
+pub mod ratings;
+pub mod profile;
+pub mod achievements;
+pub mod puzzle;
\ No newline at end of file
@@ -0,0 +1,349 @@
+use yew::prelude::*;
+
+use bounce::query::use_mutation_value;
+
+use wasm_bindgen::JsCast;
+
+use wasm_bindgen_futures::spawn_local;
+
+use shared_types::types::{CpuLevel, DailyActivity, GameStats, GameType, MatchQueryFilter, MatchRecord, MatchResult, UserStats};
+
+use crate::mutations::ServiceError;
+use crate::mutations::match_records::{MatchRecordQueryOptions, UserMatchRecordQuery, UserMatchRecordQueryOptions};
+use crate::mutations::stats::get_user_stats;
+
+/// Number of matches on each page of the match history
+const PAGE_SIZE: i64 = 10;
+
+/// Size of the activity chart (in SVG units)
+const CHART_WIDTH: f64 = 600.0;
+const CHART_HEIGHT: f64 = 80.0;
+
+/// Filters and page of the match history
+#[derive(Debug, Clone, PartialEq)]
+struct HistoryOptions {
+    game: Option<GameType>,
+    result: Option<MatchResult>,
+    /// Cursors of the pages visited so far (the last one is the current page, none for the first page)
+    cursors: Vec<String>
+}
+
+fn game_name(game: GameType) -> &'static str {
+    match game {
+        GameType::Connect4 => "Connect 4",
+        GameType::OttoToot => "TOOT OTTO"
+    }
+}
+
+fn level_name(level: CpuLevel) -> &'static str {
+    match level {
+        CpuLevel::Easy => "Easy",
+        CpuLevel::Medium => "Medium",
+        CpuLevel::Hard => "Hard"
+    }
+}
+
+fn result_name(result: MatchResult) -> &'static str {
+    match result {
+        MatchResult::Win => "Win",
+        MatchResult::Loss => "Loss",
+        MatchResult::Tie => "Tie"
+    }
+}
+
+/// Get the value of the select element that changed
+fn select_value(e: &Event) -> String {
+    e.target().unwrap().dyn_into::<web_sys::HtmlSelectElement>().unwrap().value()
+}
+
+/// Render the statistics of a game
+fn game_stats_card(stats: &GameStats) -> Html {
+    let streak = match stats.current_streak {
+        Some(streak) if streak.length > 1 => format!("{} {}s in a row", streak.length, result_name(streak.result)),
+        Some(streak) => format!("Last match was a {}", result_name(streak.result)),
+        None => "-".into()
+    };
+
+    html! {
+        <div class="box">
+            <h2 class="subtitle">{ game_name(stats.game_id) }</h2>
+            <table class="table is-fullwidth">
+                <thead>
+                    <tr>
+                        <th>{"Difficulty"}</th>
+                        <th>{"Wins"}</th>
+                        <th>{"Losses"}</th>
+                        <th>{"Ties"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {
+                        stats.levels.iter().map(|level| html! {
+                            <tr>
+                                <td>{ level_name(level.cpu_level) }</td>
+                                <td>{ level.wins }</td>
+                                <td>{ level.losses }</td>
+                                <td>{ level.ties }</td>
+                            </tr>
+                        }).collect::<Html>()
+                    }
+                </tbody>
+            </table>
+            <div class="level">
+                <div class="level-item has-text-centered">
+                    <div>
+                        <p class="heading">{"Current streak"}</p>
+                        <p>{ streak }</p>
+                    </div>
+                </div>
+                <div class="level-item has-text-centered">
+                    <div>
+                        <p class="heading">{"Longest win streak"}</p>
+                        <p>{ stats.longest_win_streak }</p>
+                    </div>
+                </div>
+                <div class="level-item has-text-centered">
+                    <div>
+                        <p class="heading">{"Average moves to win"}</p>
+                        <p>{ stats.average_moves_to_win.map(|moves| format!("{:.1}", moves)).unwrap_or_else(|| "-".into()) }</p>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/// Draw the matches played on each day as a bar chart
+fn activity_chart(activity: &[DailyActivity]) -> Html {
+    let most = activity.iter().map(|day| day.matches).max().unwrap_or(0).max(1);
+    let bar_width = CHART_WIDTH / activity.len().max(1) as f64;
+
+    html! {
+        <svg viewBox={format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT)} width="100%">
+            {
+                activity.iter().enumerate().map(|(i, day)| {
+                    let height = day.matches as f64 / most as f64 * CHART_HEIGHT;
+                    html! {
+                        <rect x={format!("{:.1}", i as f64 * bar_width)} y={format!("{:.1}", CHART_HEIGHT - height)}
+                            width={format!("{:.1}", bar_width * 0.8)} height={format!("{:.1}", height)} fill="hsl(171, 100%, 41%)">
+                            <title>{ format!("{}: {} matches", day.day, day.matches) }</title>
+                        </rect>
+                    }
+                }).collect::<Html>()
+            }
+        </svg>
+    }
+}
+
+/// Render a match of the match history
+fn record_row(record: &MatchRecord) -> Html {
+    let board_size = match (record.columns, record.rows) {
+        (Some(columns), Some(rows)) => format!("{}x{}", columns, rows),
+        _ => String::from("Unknown")
+    };
+
+    html! {
+        <tr>
+            <td>{ record.finished_at.format("%Y-%m-%d %H:%M").to_string() }</td>
+            <td>{ game_name(record.game_id) }</td>
+            <td>{ level_name(record.cpu_level) }</td>
+            <td>{ result_name(record.result) }</td>
+            <td>{ record.moves }</td>
+            <td>{ board_size }</td>
+        </tr>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ProfileProps {
+    /// User the profile is for
+    pub id: String
+}
+
+/// Profile page component
+///
+/// Shows the statistics of a user along with their match history
+#[function_component(Profile)]
+pub fn profile(props: &ProfileProps) -> Html {
+    // Statistics of the user (None while loading)
+    let stats = use_state_eq(|| None::<Result<UserStats, ServiceError>>);
+
+    let history_options = use_state_eq(|| HistoryOptions {
+        game: None,
+        result: None,
+        cursors: Vec::new()
+    });
+
+    let records = use_mutation_value::<UserMatchRecordQuery>();
+
+    // Reload the statistics whenever we switch users
+    {
+        let stats = stats.clone();
+        let history_options = history_options.clone();
+        use_effect_with_deps(
+            move |id: &String| {
+                let id = id.clone();
+                stats.set(None);
+                history_options.set(HistoryOptions { game: None, result: None, cursors: Vec::new() });
+                spawn_local(async move {
+                    stats.set(Some(get_user_stats(&id).await));
+                });
+                || {}
+            },
+            props.id.clone()
+        );
+    }
+
+    // Reload the match history whenever its options change
+    {
+        let records = records.clone();
+        use_effect_with_deps(
+            move |(id, options): &(String, HistoryOptions)| {
+                let input = UserMatchRecordQueryOptions {
+                    user_id: id.clone(),
+                    options: MatchRecordQueryOptions {
+                        limit: Some(PAGE_SIZE),
+                        offset: None,
+                        cursor: options.cursors.last().cloned(),
+                        filters: Some(MatchQueryFilter {
+                            result: options.result.into_iter().collect(),
+                            game: options.game.into_iter().collect(),
+                            level: Vec::new(),
+                            first_mover: Vec::new(),
+                            board_size: Vec::new()
+                        }),
+                        sort_by: None,
+                        asc: None
+                    }
+                };
+                spawn_local(async move {
+                    let _res = records.run(input).await;
+                });
+                || {}
+            },
+            (props.id.clone(), (*history_options).clone())
+        );
+    }
+
+    let on_game_change = {
+        let history_options = history_options.clone();
+        Callback::from(move |e: Event| {
+            history_options.set(HistoryOptions {
+                game: match select_value(&e).as_str() {
+                    "connect4" => Some(GameType::Connect4),
+                    "ottotoot" => Some(GameType::OttoToot),
+                    _ => None
+                },
+                cursors: Vec::new(),
+                ..(*history_options).clone()
+            })
+        })
+    };
+
+    let on_result_change = {
+        let history_options = history_options.clone();
+        Callback::from(move |e: Event| {
+            history_options.set(HistoryOptions {
+                result: match select_value(&e).as_str() {
+                    "win" => Some(MatchResult::Win),
+                    "loss" => Some(MatchResult::Loss),
+                    "tie" => Some(MatchResult::Tie),
+                    _ => None
+                },
+                cursors: Vec::new(),
+                ..(*history_options).clone()
+            })
+        })
+    };
+
+    // Go to the next page with its cursor, or back to the previous one by forgetting the current cursor
+    let change_page = |cursor: Option<String>| {
+        let history_options = history_options.clone();
+        Callback::from(move |_| {
+            let mut cursors = history_options.cursors.clone();
+            match cursor {
+                Some(ref cursor) => cursors.push(cursor.clone()),
+                None => { cursors.pop(); }
+            }
+            history_options.set(HistoryOptions { cursors, ..(*history_options).clone() })
+        })
+    };
+
+    let stats_view = match *stats {
+        None => html! { <p>{"Loading statistics..."}</p> },
+        Some(Err(ServiceError::Rejected(ref err))) if err.code == 404 => html! { <p>{"This player doesn't exist"}</p> },
+        Some(Err(_)) => html! { <p>{"Error loading statistics"}</p> },
+        Some(Ok(ref stats)) => html! {
+            <>
+                {
+                    if stats.games.is_empty() {
+                        html! { <p>{"No matches played yet"}</p> }
+                    } else {
+                        stats.games.iter().map(game_stats_card).collect::<Html>()
+                    }
+                }
+                <div class="box">
+                    <h2 class="subtitle">{ format!("Activity (past {} days)", stats.activity.len()) }</h2>
+                    { activity_chart(&stats.activity) }
+                </div>
+            </>
+        }
+    };
+
+    let (history_body, next_cursor) = match records.result() {
+        None => (html! { <tr><td colspan="6">{"Loading matches..."}</td></tr> }, None),
+        Some(Err(_)) => (html! { <tr><td colspan="6">{"Error loading matches"}</td></tr> }, None),
+        Some(Ok(ref page)) if page.0.records.is_empty() => (html! { <tr><td colspan="6">{"No matches found"}</td></tr> }, None),
+        Some(Ok(ref page)) => (page.0.records.iter().map(record_row).collect::<Html>(), page.0.next_cursor.clone())
+    };
+
+    html! {
+        <div class="container mt-6" style={"max-width:700px;"}>
+            <h1 class="title has-text-centered mt-6">{ &props.id }</h1>
+            { stats_view }
+            <h2 class="subtitle mt-5">{"Match History"}</h2>
+            <div class="field is-grouped">
+                <div class="control">
+                    <div class="select">
+                        <select onchange={on_game_change}>
+                            <option value="" selected={history_options.game.is_none()}>{"Any game"}</option>
+                            <option value="connect4" selected={history_options.game == Some(GameType::Connect4)}>{"Connect 4"}</option>
+                            <option value="ottotoot" selected={history_options.game == Some(GameType::OttoToot)}>{"TOOT OTTO"}</option>
+                        </select>
+                    </div>
+                </div>
+                <div class="control">
+                    <div class="select">
+                        <select onchange={on_result_change}>
+                            <option value="" selected={history_options.result.is_none()}>{"Any result"}</option>
+                            <option value="win" selected={history_options.result == Some(MatchResult::Win)}>{"Wins"}</option>
+                            <option value="loss" selected={history_options.result == Some(MatchResult::Loss)}>{"Losses"}</option>
+                            <option value="tie" selected={history_options.result == Some(MatchResult::Tie)}>{"Ties"}</option>
+                        </select>
+                    </div>
+                </div>
+            </div>
+            <table class="table is-fullwidth is-hoverable">
+                <thead>
+                    <tr>
+                        <th>{"Finished (UTC)"}</th>
+                        <th>{"Game"}</th>
+                        <th>{"Difficulty"}</th>
+                        <th>{"Result"}</th>
+                        <th>{"Moves"}</th>
+                        <th>{"Board"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { history_body }
+                </tbody>
+            </table>
+            <nav class="pagination is-centered">
+                <button class="button pagination-previous" disabled={history_options.cursors.is_empty()}
+                    onclick={change_page(None)}>{"Previous"}</button>
+                <button class="button pagination-next" disabled={next_cursor.is_none()}
+                    onclick={change_page(next_cursor.clone())}>{"Next"}</button>
+            </nav>
+        </div>
+    }
+}
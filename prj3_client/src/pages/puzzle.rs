@@ -0,0 +1,416 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use bounce::prelude::*;
+
+use wasm_bindgen_futures::spawn_local;
+
+use shared_types::game::*;
+use shared_types::types::{DailyPuzzle, GameType, PuzzleAttempt, PuzzleLeaderboardEntry, PuzzleProgress, Records};
+
+use crate::components::play_screen::render_grid;
+use crate::mutations::ServiceError;
+use crate::mutations::puzzles::{get_daily_puzzle, get_puzzle_leaderboard, start_daily_puzzle, submit_puzzle_attempt};
+use crate::stores::auth::AuthCredentials;
+use crate::Route;
+
+/// Number of entries on each page of the leaderboard
+const PAGE_SIZE: i64 = 10;
+
+/// Disc color of the player (the opponent's discs use the other game color)
+const DISC_COLOR: &str = "#FF8E8E";
+
+/// How the current try at the puzzle is going
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TryState {
+    Playing,
+    Solved,
+    Failed(&'static str)
+}
+
+/// Board of the current try along with the moves the player made
+#[derive(Debug, Clone, PartialEq)]
+struct PuzzleState {
+    board_state: Vec<(i32, String)>,
+    moves: Vec<String>,
+    try_state: TryState
+}
+
+/// Get the puzzle a daily puzzle response is for (None if the setup can't be read)
+fn to_puzzle(daily: &DailyPuzzle) -> Option<Puzzle> {
+    Some(Puzzle {
+        game_type: daily.game_id,
+        columns: daily.columns as usize,
+        rows: daily.rows as usize,
+        win_in: daily.win_in,
+        setup: daily.setup.iter().map(|setup_move| PossibleMove::parse(setup_move)).collect::<Option<Vec<_>>>()?
+    })
+}
+
+/// Get board state (used to render)
+fn board_state(board: &Board) -> Vec<(i32, String)> {
+    board.storage.iter()
+        .map(|slot| (slot.get_player() as i32, slot.to_string(board.game_type)))
+        .collect()
+}
+
+/// Name of the game as expected by [render_grid]
+fn game_name(game: GameType) -> &'static str {
+    match game {
+        GameType::Connect4 => "Connect 4",
+        GameType::OttoToot => "TOOT and OTTO"
+    }
+}
+
+/// Format a number of seconds as minutes and seconds
+fn format_time(seconds: i64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Render a row of the leaderboard
+fn entry_row(entry: &PuzzleLeaderboardEntry) -> Html {
+    html! {
+        <tr>
+            <td>{format!("#{}", entry.rank)}</td>
+            <td>
+                <Link<Route> to={Route::Profile { id: entry.user_id.clone() }}>{&entry.user_id}</Link<Route>>
+            </td>
+            <td>{format_time(entry.solve_seconds)}</td>
+            <td>{entry.attempts}</td>
+        </tr>
+    }
+}
+
+/// Render the user's progress on the puzzle
+fn render_progress(progress: &Option<PuzzleProgress>, is_guest: bool) -> Html {
+    let text = match progress {
+        _ if is_guest => "Login to submit your solutions and join the leaderboard".to_string(),
+        Some(PuzzleProgress { solved: true, solve_seconds: Some(seconds), attempts }) =>
+            format!("Solved in {} after {} attempts", format_time(*seconds), attempts),
+        Some(PuzzleProgress { attempts, .. }) if *attempts > 0 => format!("{} attempts so far", attempts),
+        Some(_) => "The clock is running!".to_string(),
+        None => "Start the clock when you're ready, your time counts from then".to_string()
+    };
+
+    html! { <h6 class="subtitle is-6 has-text-centered">{text}</h6> }
+}
+
+/// Daily puzzle page component
+///
+/// Lets the player try to solve the puzzle of the day, playing the opponent's replies
+/// the same way the server does, and shows who solved it fastest
+#[function_component(DailyPuzzlePage)]
+pub fn daily_puzzle_page() -> Html {
+    let credentials = use_atom_value::<AuthCredentials>();
+    let is_guest = *credentials == AuthCredentials::Guest;
+
+    // Puzzle of the day (None while loading)
+    let daily = use_state_eq(|| None::<Result<DailyPuzzle, ServiceError>>);
+
+    // Board of the current try (None until the puzzle is loaded)
+    let board = use_mut_ref(|| None::<Board>);
+
+    let state = use_state_eq(|| PuzzleState {
+        board_state: Vec::new(),
+        moves: Vec::new(),
+        try_state: TryState::Playing
+    });
+
+    // Letter placed next (TOOT and OTTO only)
+    let is_t = use_state_eq(|| true);
+
+    let progress = use_state_eq(|| None::<PuzzleProgress>);
+    let submit_error = use_state_eq(|| None::<String>);
+
+    let leaderboard = use_state_eq(|| None::<Result<Records<PuzzleLeaderboardEntry>, ServiceError>>);
+    let offset = use_state_eq(|| 0);
+
+    // Load the puzzle, reloading when the user logs in or out so their progress is shown
+    {
+        let daily = daily.clone();
+        let board = board.clone();
+        let state = state.clone();
+        let progress = progress.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    let loaded = get_daily_puzzle().await;
+
+                    if let Ok(ref loaded) = loaded {
+                        *board.borrow_mut() = to_puzzle(loaded).map(|puzzle| puzzle.board());
+                        progress.set(loaded.progress.clone());
+                        state.set(PuzzleState {
+                            board_state: board.borrow().as_ref().map(board_state).unwrap_or_default(),
+                            moves: Vec::new(),
+                            try_state: TryState::Playing
+                        });
+                    }
+
+                    daily.set(Some(loaded));
+                });
+                || {}
+            },
+            credentials.clone()
+        );
+    }
+
+    // Reload the leaderboard whenever the page changes or the user solves the puzzle
+    {
+        let leaderboard = leaderboard.clone();
+        use_effect_with_deps(
+            move |(offset, _): &(i64, bool)| {
+                let offset = *offset;
+                spawn_local(async move {
+                    leaderboard.set(Some(get_puzzle_leaderboard(PAGE_SIZE, offset).await));
+                });
+                || {}
+            },
+            (*offset, progress.as_ref().map_or(false, |progress| progress.solved))
+        );
+    }
+
+    let (daily_puzzle, puzzle) = match *daily {
+        None => return html! { <div class="container mt-6"><p>{"Loading puzzle..."}</p></div> },
+        Some(Err(_)) => return html! { <div class="container mt-6"><p>{"Error loading puzzle"}</p></div> },
+        Some(Ok(ref daily_puzzle)) => match to_puzzle(daily_puzzle) {
+            Some(puzzle) => (daily_puzzle.clone(), Rc::new(puzzle)),
+            None => return html! { <div class="container mt-6"><p>{"Error loading puzzle"}</p></div> }
+        }
+    };
+
+    // Play a move for the player, then the opponent's reply
+    let on_column_clicked = |column: usize| {
+        let board = board.clone();
+        let state = state.clone();
+        let progress = progress.clone();
+        let submit_error = submit_error.clone();
+        let puzzle = puzzle.clone();
+        let day = daily_puzzle.day;
+        let letter = (puzzle.game_type == GameType::OttoToot).then(|| if *is_t { Letter::T } else { Letter::O });
+        Callback::from(move |_| {
+            if state.try_state != TryState::Playing {
+                return;
+            }
+
+            let mut board_ref = board.borrow_mut();
+            let board = match board_ref.as_mut() {
+                Some(board) => board,
+                None => return
+            };
+
+            let possible_move = PossibleMove { column, letter };
+
+            if !is_legal(board, possible_move) {
+                return;
+            }
+
+            board.place_at_column(possible_move, PLAYER_ID);
+
+            let mut moves = state.moves.clone();
+            moves.push(possible_move.to_string());
+            let moves_left = puzzle.win_in - moves.len() as u32;
+
+            let try_state = match board.get_winner() {
+                Some(PLAYER_ID) => TryState::Solved,
+                Some(_) => TryState::Failed("You spelled OTTO"),
+                None if moves_left == 0 => TryState::Failed("Out of moves"),
+                None => match defend(board, moves_left) {
+                    None => TryState::Failed("The board is full"),
+                    Some(reply) => {
+                        board.place_at_column(reply, AI_ID);
+
+                        match board.get_winner() {
+                            Some(PLAYER_ID) => TryState::Solved,
+                            Some(_) => TryState::Failed("The computer won"),
+                            None if board.check_if_no_more_moves() => TryState::Failed("The board is full"),
+                            None => TryState::Playing
+                        }
+                    }
+                }
+            };
+
+            // Every finished try counts as an attempt
+            if try_state != TryState::Playing && !is_guest {
+                let attempt = PuzzleAttempt { day, moves: moves.clone() };
+                let progress = progress.clone();
+                let submit_error = submit_error.clone();
+                spawn_local(async move {
+                    match submit_puzzle_attempt(&attempt).await {
+                        Ok(updated) => {
+                            submit_error.set(None);
+                            progress.set(Some(updated));
+                        },
+                        Err(ServiceError::Rejected(err)) if err.code == 409 =>
+                            submit_error.set(Some("A new puzzle is out, reload the page to play it".into())),
+                        Err(err) => submit_error.set(Some(format!("Unable to submit your solution: {}", err)))
+                    }
+                });
+            }
+
+            state.set(PuzzleState {
+                board_state: board_state(board),
+                moves,
+                try_state
+            });
+        })
+    };
+
+    // Start timing the user (logged in users play once the clock is running)
+    let on_start_clicked = {
+        let progress = progress.clone();
+        let submit_error = submit_error.clone();
+        Callback::from(move |_| {
+            let progress = progress.clone();
+            let submit_error = submit_error.clone();
+            spawn_local(async move {
+                match start_daily_puzzle().await {
+                    Ok(started) => {
+                        submit_error.set(None);
+                        progress.set(Some(started));
+                    },
+                    Err(err) => submit_error.set(Some(format!("Unable to start the puzzle: {}", err)))
+                }
+            });
+        })
+    };
+
+    let on_retry_clicked = {
+        let board = board.clone();
+        let state = state.clone();
+        let puzzle = puzzle.clone();
+        Callback::from(move |_| {
+            let fresh = puzzle.board();
+            state.set(PuzzleState {
+                board_state: board_state(&fresh),
+                moves: Vec::new(),
+                try_state: TryState::Playing
+            });
+            *board.borrow_mut() = Some(fresh);
+        })
+    };
+
+    let on_letter_selected = |letter: bool| {
+        let is_t = is_t.clone();
+        Callback::from(move |_| is_t.set(letter))
+    };
+
+    let change_page = |new_offset: i64| {
+        let offset = offset.clone();
+        Callback::from(move |_| offset.set(new_offset))
+    };
+
+    let error_message = match *submit_error {
+        Some(ref err) => html! { <p class="has-text-danger has-text-centered">{err}</p> },
+        None => html! {}
+    };
+
+    let board_size = format!("{}x{}", puzzle.columns, puzzle.rows);
+    let moves_left = puzzle.win_in - state.moves.len() as u32;
+
+    let (leaderboard_body, total_count) = match *leaderboard {
+        None => (html! { <tr><td colspan="4">{"Loading leaderboard..."}</td></tr> }, 0),
+        Some(Err(_)) => (html! { <tr><td colspan="4">{"Error loading leaderboard"}</td></tr> }, 0),
+        Some(Ok(ref page)) if page.records.is_empty() => (html! { <tr><td colspan="4">{"Nobody has solved today's puzzle yet"}</td></tr> }, 0),
+        Some(Ok(ref page)) => (page.records.iter().map(entry_row).collect::<Html>(), page.total_count.unwrap_or(0))
+    };
+
+    html! {
+        <div class="container" style="max-width:650px">
+            <h1 class="title has-text-centered mt-6">{"Daily Puzzle"}</h1>
+            <p class="has-text-centered">{
+                match puzzle.game_type {
+                    GameType::Connect4 => format!("Connect 4: win in {} moves", puzzle.win_in),
+                    GameType::OttoToot => format!("TOOT and OTTO: spell TOOT in {} moves", puzzle.win_in)
+                }
+            }</p>
+            <p class="has-text-centered has-text-grey mb-3">{ format!("Puzzle for {}", daily_puzzle.day) }</p>
+            { render_progress(&progress, is_guest) }
+            <div class="mt-5">
+                <div class="in-game-player-info">{ format!("{} moves left", moves_left) }</div>
+                {
+                    if puzzle.game_type == GameType::OttoToot {
+                        html! {
+                            <div class="in-game-player-info ml-5" style={"float:right"}>
+                                <div>{"Select letter: "}</div>
+                                <span class="mx-2 is-size-6">
+                                    <input class="color-1 mr-2" type="radio" onclick={on_letter_selected(true)} checked={*is_t} />
+                                    {"T"}
+                                </span>
+                                <span class="mx-2 is-size-6">
+                                    <input class="color-1 mr-2" type="radio" onclick={on_letter_selected(false)} checked={!*is_t} />
+                                    {"O"}
+                                </span>
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+            <div class="card mt-2">
+                { render_grid(board_size, state.board_state.clone(), DISC_COLOR.to_string(), game_name(puzzle.game_type).to_string()) }
+                {
+                    if !is_guest && progress.is_none() {
+                        html! {
+                            <div class="card results-card">
+                                <div class="card-content">
+                                <div class="content">
+                                    <button class="button is-primary block" onclick={on_start_clicked} style={"width: 100%;"}>{"Start the clock"}</button>
+                                    { error_message }
+                                </div>
+                                </div>
+                            </div>
+                        }
+                    } else if state.try_state == TryState::Playing {
+                        html! {
+                            <div class={format!("col-button-container grid-container grid_cols_{}", puzzle.columns)}>
+                                { (0..puzzle.columns).map(|column| html! {
+                                    <div class="col-button" onclick={on_column_clicked(column)} />
+                                }).collect::<Html>() }
+                            </div>
+                        }
+                    } else {
+                        html! {
+                            <div class="card results-card">
+                                <div class="card-content">
+                                <div class="content">
+                                    <h1 class="title has-text-centered">{
+                                        match state.try_state {
+                                            TryState::Failed(reason) => reason,
+                                            _ => "Solved!"
+                                        }
+                                    }</h1>
+                                    <button class="button is-primary block" onclick={on_retry_clicked} style={"width: 100%;"}>{"Try again"}</button>
+                                    { error_message }
+                                </div>
+                                </div>
+                            </div>
+                        }
+                    }
+                }
+            </div>
+            <h2 class="subtitle mt-5">{"Today's Fastest Solvers"}</h2>
+            <table class="table is-fullwidth is-hoverable">
+                <thead>
+                    <tr>
+                        <th>{"Rank"}</th>
+                        <th>{"Player"}</th>
+                        <th>{"Time"}</th>
+                        <th>{"Attempts"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { leaderboard_body }
+                </tbody>
+            </table>
+            <nav class="pagination is-centered">
+                <button class="button pagination-previous" disabled={*offset == 0}
+                    onclick={change_page((*offset - PAGE_SIZE).max(0))}>{"Previous"}</button>
+                <button class="button pagination-next" disabled={*offset + PAGE_SIZE >= total_count}
+                    onclick={change_page(*offset + PAGE_SIZE)}>{"Next"}</button>
+            </nav>
+        </div>
+    }
+}
@@ -0,0 +1,135 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use bounce::prelude::*;
+
+use wasm_bindgen_futures::spawn_local;
+
+use shared_types::rating::PROVISIONAL_MATCHES;
+use shared_types::types::{GameType, UserRating};
+
+use crate::mutations::ratings::get_ratings;
+use crate::stores::auth::AuthCredentials;
+use crate::Route;
+
+/// Number of past ratings to graph for each game
+const HISTORY_LENGTH: i64 = 50;
+
+/// Size of the chart (in SVG units)
+const CHART_WIDTH: f64 = 600.0;
+const CHART_HEIGHT: f64 = 200.0;
+
+/// Space around the chart for the axis labels
+const CHART_PADDING: f64 = 40.0;
+
+/// Draw the rating history of a game as a line chart
+fn rating_chart(rating: &UserRating) -> Html {
+    if rating.history.len() < 2 {
+        return html! { <p class="has-text-grey">{"Play more matches to see how your rating changes"}</p> };
+    }
+
+    let (min, max) = rating.history.iter().fold((f64::MAX, f64::MIN), |(min, max), point| {
+        (min.min(point.rating), max.max(point.rating))
+    });
+
+    // Keep some room above and below the line (and avoid dividing by zero for a flat line)
+    let (min, max) = ((min - 10.0).floor(), (max + 10.0).ceil());
+
+    let x_step = CHART_WIDTH / (rating.history.len() - 1) as f64;
+    let points = rating.history.iter().enumerate()
+        .map(|(i, point)| format!(
+            "{:.1},{:.1}",
+            CHART_PADDING + i as f64 * x_step,
+            CHART_PADDING + (max - point.rating) / (max - min) * CHART_HEIGHT
+        ))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let view_box = format!("0 0 {} {}", CHART_WIDTH + 2.0 * CHART_PADDING, CHART_HEIGHT + 2.0 * CHART_PADDING);
+
+    html! {
+        <svg viewBox={view_box} width="100%">
+            <line x1={CHART_PADDING.to_string()} y1={CHART_PADDING.to_string()}
+                x2={CHART_PADDING.to_string()} y2={(CHART_PADDING + CHART_HEIGHT).to_string()} stroke="grey" />
+            <line x1={CHART_PADDING.to_string()} y1={(CHART_PADDING + CHART_HEIGHT).to_string()}
+                x2={(CHART_PADDING + CHART_WIDTH).to_string()} y2={(CHART_PADDING + CHART_HEIGHT).to_string()} stroke="grey" />
+            <text x="2" y={(CHART_PADDING + 5.0).to_string()} font-size="12">{max}</text>
+            <text x="2" y={(CHART_PADDING + CHART_HEIGHT + 5.0).to_string()} font-size="12">{min}</text>
+            <polyline points={points} fill="none" stroke="hsl(171, 100%, 41%)" stroke-width="2" />
+        </svg>
+    }
+}
+
+/// Render the rating of a game
+fn rating_card(rating: &UserRating) -> Html {
+    html! {
+        <div class="box">
+            <h2 class="subtitle">{
+                match rating.game_id {
+                    GameType::Connect4 => "Connect 4",
+                    GameType::OttoToot => "TOOT OTTO"
+                }
+            }</h2>
+            <p class="is-size-4 has-text-weight-bold">
+                { format!("{:.0}", rating.rating) }
+                {
+                    if rating.matches < PROVISIONAL_MATCHES {
+                        html! { <span class="tag is-warning ml-2">{"Provisional"}</span> }
+                    } else {
+                        html! {}
+                    }
+                }
+            </p>
+            <p class="has-text-grey mb-3">{ format!("Based on {} matches", rating.matches) }</p>
+            { rating_chart(rating) }
+        </div>
+    }
+}
+
+/// Ratings page component
+///
+/// Shows the logged in user's rating for each game they've played and graphs how it changed
+#[function_component(Ratings)]
+pub fn ratings() -> Html {
+    let credentials = use_atom::<AuthCredentials>();
+    let history = use_history().unwrap();
+
+    // Ratings of the user (None while loading)
+    let ratings = use_state_eq(|| None::<Result<Vec<UserRating>, String>>);
+
+    {
+        let ratings = ratings.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    ratings.set(Some(get_ratings(HISTORY_LENGTH).await.map_err(|err| err.to_string())));
+                });
+                || {}
+            },
+            ()
+        );
+    }
+
+    // Only logged in users have ratings
+    if let AuthCredentials::Guest = *credentials {
+        history.push(Route::Login);
+        return html! {};
+    }
+
+    html! {
+        <div class="container mt-6" style={"max-width:700px;"}>
+            <h1 class="title has-text-centered mt-6">{"My Ratings"}</h1>
+            <p class="has-text-centered mb-5">
+                {"Your rating goes up when you beat a CPU and down when you lose, by more the harder the surprise"}
+            </p>
+            {
+                match *ratings {
+                    None => html! { <p>{"Loading ratings..."}</p> },
+                    Some(Err(_)) => html! { <p>{"Error loading ratings"}</p> },
+                    Some(Ok(ref ratings)) if ratings.is_empty() => html! { <p>{"Play a match while logged in to get a rating"}</p> },
+                    Some(Ok(ref ratings)) => ratings.iter().map(rating_card).collect::<Html>()
+                }
+            }
+        </div>
+    }
+}
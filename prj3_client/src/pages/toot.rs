@@ -25,6 +25,12 @@ pub struct Toot {
     //possible disc color options
     disc_colors: Vec<String>,
 
+    //choices for who moves first
+    first_movers: Vec<String>,
+
+    //possible letter inventories
+    letter_modes: Vec<String>,
+
     //chosen difficulty
     selected_difficulty: String,
 
@@ -34,12 +40,18 @@ pub struct Toot {
     //chosen board size
     selected_board_size: String,
 
+    //chosen letter inventory (TOOT and OTTO only)
+    selected_letter_mode: String,
+
+    //chosen first mover
+    selected_first_mover: String,
+
     //should game start
     should_start: bool,
 }
 
 pub enum Msg {
-    StartPressed([String; 3]),
+    StartPressed([String; 5]),
 }
 
 impl Component for Toot {
@@ -57,9 +69,13 @@ impl Component for Toot {
             difficulties: vec!["Easy".to_string(), "Medium".to_string(), "Hard".to_string()],
             board_sizes: vec!["6x4".to_string(), "7x7".to_string()],
             disc_colors: vec!["#FF8E8E".to_string(), "#FFE68E".to_string()],
+            letter_modes: vec!["Unlimited".to_string(), "6 T's and 6 O's".to_string()],
+            first_movers: vec!["You first".to_string(), "CPU first".to_string(), "Random".to_string()],
             selected_difficulty: "Easy".to_string(),
             selected_board_size: "6x4".to_string(),
             selected_disc_color: "#FF8E8E".to_string(),
+            selected_letter_mode: "Unlimited".to_string(),
+            selected_first_mover: "You first".to_string(),
             should_start: false
         }
     }
@@ -71,6 +87,8 @@ impl Component for Toot {
                 self.selected_difficulty = selections[0].clone();
                 self.selected_board_size = selections[1].clone();
                 self.selected_disc_color = selections[2].clone();
+                self.selected_letter_mode = selections[3].clone();
+                self.selected_first_mover = selections[4].clone();
                 self.should_start = true;
                 true
             }
@@ -90,6 +108,8 @@ impl Component for Toot {
                     selected_difficulty = {self.selected_difficulty.clone()}
                     selected_board_size = {self.selected_board_size.clone()}
                     selected_disc_color = {self.selected_disc_color.clone()}
+                    selected_letter_mode = {self.selected_letter_mode.clone()}
+                    selected_first_mover = {self.selected_first_mover.clone()}
                     columns = {columns}
                     rows = {rows}
                 />
@@ -104,6 +124,8 @@ impl Component for Toot {
                     difficulties = {self.difficulties.clone()}
                     board_sizes = {self.board_sizes.clone()}
                     disc_colors = {self.disc_colors.clone()}
+                    first_movers = {self.first_movers.clone()}
+                    letter_modes = {self.letter_modes.clone()}
                     start_pressed = {ctx.link().callback(Msg::StartPressed).clone()}
                 />
             }
@@ -0,0 +1,77 @@
+use serde::{Serialize, Deserialize};
+
+use shared_types::types::{BatchMatchData, ClientMatchData};
+
+/// Local storage key the queue is kept under
+const STORAGE_KEY: &str = "pending_match_records";
+
+/// Match record waiting to be sent to the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingRecord {
+    /// User that played the match (only their session may send it)
+    user_id: String,
+    #[serde(flatten)]
+    data: BatchMatchData
+}
+
+/// Get the local storage of the browser (None if it's disabled)
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Read the queue (empty if it's missing or unreadable)
+fn load() -> Vec<PendingRecord> {
+    storage()
+        .and_then(|storage| storage.get_item(STORAGE_KEY).ok()?)
+        .and_then(|queue| serde_json::from_str(&queue).ok())
+        .unwrap_or_default()
+}
+
+/// Write the queue back
+fn save(queue: &[PendingRecord]) {
+    if let Some(storage) = storage() {
+        let result = if queue.is_empty() {
+            storage.remove_item(STORAGE_KEY)
+        } else {
+            storage.set_item(STORAGE_KEY, &serde_json::to_string(queue).unwrap())
+        };
+
+        if let Err(err) = result {
+            log::error!("Unable to save unsent match records: {:?}", err);
+        }
+    }
+}
+
+/// Queue a match record of a user, giving it a random idempotency key
+/// so it's only added once however many times it's sent
+pub fn push(user_id: &str, record: ClientMatchData) {
+    let mut queue = load();
+
+    queue.push(PendingRecord {
+        user_id: user_id.to_string(),
+        data: BatchMatchData {
+            idempotency_key: format!("{:032x}", rand::random::<u128>()),
+            record
+        }
+    });
+
+    save(&queue);
+}
+
+/// Get up to the given number of a user's queued records (oldest first)
+pub fn take(user_id: &str, limit: usize) -> Vec<BatchMatchData> {
+    load().into_iter()
+        .filter(|pending| pending.user_id == user_id)
+        .take(limit)
+        .map(|pending| pending.data)
+        .collect()
+}
+
+/// Remove the records of a user with the given idempotency keys from the queue
+pub fn remove(user_id: &str, keys: &[String]) {
+    let mut queue = load();
+
+    queue.retain(|pending| pending.user_id != user_id || !keys.contains(&pending.data.idempotency_key));
+
+    save(&queue);
+}
@@ -0,0 +1,314 @@
+use rocket::http::Status;
+use rocket::form::Form;
+
+use rocket::serde::json::Json;
+use rocket::State;
+
+use diesel::Connection;
+use diesel::SqliteConnection;
+
+use super::UserDbConn;
+use super::errors::{error_response, invalid_input, server_error};
+use super::auth::{AdminSession, AuthConfig};
+use super::export::{ExportOptions, RecordExport};
+use super::routes::{match_record_page, match_record_response};
+
+use shared_types::models::{AuditLogModel, UserModel, MatchRecordModel};
+use shared_types::types::*;
+use shared_types::queries::*;
+use shared_types::validation::*;
+
+type QueryResult<T> = Result<T, diesel::result::Error>;
+
+/// Run an admin action that changes data and write it to the audit log
+/// 
+/// The entry is only kept if the action succeeds (reads aren't logged)
+fn audited<T, F>(conn: &SqliteConnection, admin: &AdminSession, action: &str, target: &str, f: F) -> QueryResult<T>
+where
+    F: FnOnce() -> QueryResult<T>
+{
+    conn.transaction(|| {
+        let output = f()?;
+        audit_log::add(conn, AuditLogModel::new(admin.user_id.clone(), action, String::from(target)))?;
+        Ok(output)
+    })
+}
+
+/// List Users Route
+/// 
+/// Query the users whose usernames start with the search string
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized list of usernames
+#[get("/users?<search>&<limit>&<offset>")]
+async fn admin_users(
+    db: UserDbConn,
+    _admin: AdminSession,
+    search: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>
+) -> Result<Json<Records<String>>, Status> {
+    let search = search.unwrap_or_default();
+    let offset = offset.unwrap_or(0);
+
+    db.run(move |c| {
+        users::find_users(c, &search, limit.unwrap_or(10), offset)
+    }).await
+        .map(|(records, total_count)| Json(Records { records, offset, total_count: Some(total_count), next_cursor: None }))
+        .map_err(server_error)
+}
+
+/// Add User Route
+/// 
+/// Registers a new user without logging in as them
+/// 
+/// On Success:
+///  - Return Status 200
+/// 
+/// On Invalid Username or Password (see [shared_types::validation])
+///  - Return status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[post("/users", data="<auth>")]
+async fn admin_user_add(
+    db: UserDbConn,
+    admin: AdminSession,
+    auth: Form<UserAuthForm>,
+    config: &State<AuthConfig>
+) -> Result<Status, (Status, Json<ApiError>)> {
+    use diesel::result::Error::DatabaseError;
+    use diesel::result::DatabaseErrorKind;
+
+    let auth = auth.into_inner();
+
+    validate_new_user(&auth.user_id, &auth.password)
+        .map_err(invalid_input)?;
+
+    let user = match UserModel::generate_new(auth.user_id, auth.password, &config.password_hash_params()) {
+        Ok(user) => user,
+        Err(err) => {
+            error!("{:?}", err);
+            return Ok(Status::InternalServerError);
+        }
+    };
+
+    db.run(move |c| {
+        let uid = user.user_id.clone();
+        match audited(c, &admin, "add_user", &uid, || users::add(c, user)) {
+            Ok(()) => Ok(Status::Ok),
+            Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                let mut errors = ValidationErrors::default();
+                errors.check(USERNAME_FIELD, Err(ValidationError::Taken));
+
+                Err(invalid_input(errors))
+            },
+            Err(err) => Ok(server_error(err))
+        }
+    }).await
+}
+
+/// Delete User Route
+/// 
+/// Deletes a user (see [users::delete])
+/// 
+/// On Success:
+///  - Return Status 200
+/// 
+/// On Unknown User:
+///  - Return Status 404
+#[delete("/users/<id>")]
+async fn admin_user_delete(db: UserDbConn, admin: AdminSession, id: String) -> Status {
+    db.run(move |c| {
+        match users::find_by_id(c, &id) {
+            Ok(Some(user)) => audited(c, &admin, "delete_user", &user.user_id, || users::delete(c, &user.user_id))
+                .map_or_else(server_error, |_| Status::Ok),
+            Ok(None) => Status::NotFound,
+            Err(err) => server_error(err)
+        }
+    }).await
+}
+
+/// List User Records Route
+/// 
+/// Query the records of a user, takes the same query args as the user's own records route
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [MatchRecord] list
+/// 
+/// On Unknown User:
+///  - Return Status 404
+/// 
+/// On Timestamps Out Of Range:
+///  - Return Status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[get("/users/<id>/records?<query..>")]
+async fn admin_user_records(
+    db: UserDbConn,
+    _admin: AdminSession,
+    id: String,
+    query: RecordQuery
+) -> Result<Json<Records<MatchRecord>>, (Status, Json<ApiError>)> {
+    // Get sort options (or use defaults)
+    let (sort_by, asc) = query.sort();
+    let (before, after) = validate_time_range(query.before, query.after).map_err(invalid_input)?;
+
+    // Get offset and cursor if any
+    let offset = query.offset();
+    let page = match_record_page(sort_by, asc, query.cursor.clone(), query.limit(), offset, query.count)
+        .map_err(error_response)?;
+
+    db.run(move |c| {
+        let user = users::find_by_id(c, &id).map_err(server_error)?.ok_or(Status::NotFound)?;
+
+        match_records::find_by_user(
+            c,
+            &user.user_id,
+            query.filter,
+            sort_by,
+            asc,
+            before,
+            after,
+            page
+        ).map_err(server_error)
+    }).await
+        .map(|data| match_record_response(data, offset))
+        .map_err(error_response)
+}
+
+/// Add User Record Route
+/// 
+/// Submits a record for a user using JSON request body data
+/// 
+/// On Success:
+///  - Return Status 200
+/// 
+/// On Unknown User:
+///  - Return Status 404
+/// 
+/// On Invalid Board Size (see [shared_types::validation])
+///  - Return status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[post("/users/<id>/records", format = "json", data = "<record>")]
+async fn admin_user_record_add(
+    db: UserDbConn,
+    admin: AdminSession,
+    id: String,
+    record: Json<ClientMatchData>
+) -> Result<Status, (Status, Json<ApiError>)> {
+    let record = record.into_inner();
+
+    validate_match_data(&record).map_err(invalid_input)?;
+
+    Ok(db.run(move |c| {
+        match users::find_by_id(c, &id) {
+            Ok(Some(user)) => {
+                let match_record = MatchRecordModel::from((UserAuthToken::from(user.user_id.clone()), record));
+                audited(c, &admin, "add_record", &user.user_id, || match_records::add(c, match_record))
+                    .map_or_else(server_error, |_| Status::Ok)
+            },
+            Ok(None) => Status::NotFound,
+            Err(err) => server_error(err)
+        }
+    }).await)
+}
+
+/// Delete Record Route
+/// 
+/// Deletes a match record by its ID
+/// 
+/// On Success:
+///  - Return Status 200
+/// 
+/// On Unknown Record (nothing is written to the audit log):
+///  - Return Status 404
+#[delete("/records/<id>")]
+async fn admin_record_delete(db: UserDbConn, admin: AdminSession, id: i32) -> Status {
+    use diesel::result::Error::NotFound;
+
+    db.run(move |c| {
+        // Failing rolls back the audit log entry
+        audited(c, &admin, "delete_record", &id.to_string(), || {
+            match_records::delete(c, id).and_then(|deleted| if deleted == 0 { Err(NotFound) } else { Ok(()) })
+        })
+    }).await
+        .map_or_else(|err| match err {
+            NotFound => Status::NotFound,
+            err => server_error(err)
+        }, |_| Status::Ok)
+}
+
+/// Export Records Route
+/// 
+/// Streams the records of every user (oldest first) as a file download,
+/// including the records of deleted users (without a user ID)
+/// 
+/// Takes the same filters as the user records export route
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return the records as CSV (by default) or JSON Lines (see [ExportFormat])
+/// 
+/// On Timestamps Out Of Range:
+///  - Return Status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[get("/records/export?<format>&<before>&<after>&<filter>")]
+async fn admin_records_export(
+    db: UserDbConn,
+    _admin: AdminSession,
+    format: Option<ExportFormat>,
+    before: Option<i64>,
+    after: Option<i64>,
+    filter: Option<MatchQueryFilter>
+) -> Result<RecordExport, (Status, Json<ApiError>)> {
+    let (before, after) = validate_time_range(before, after).map_err(invalid_input)?;
+
+    Ok(RecordExport::new(db, format.unwrap_or_default(), ExportOptions {
+        user_id: None,
+        filter,
+        before,
+        after
+    }))
+}
+
+/// Audit Log Route
+/// 
+/// Query the actions performed by admins, most recent first
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [AuditLogEntry] list
+#[get("/audit?<limit>&<offset>")]
+async fn admin_audit(
+    db: UserDbConn,
+    _admin: AdminSession,
+    limit: Option<i64>,
+    offset: Option<i64>
+) -> Result<Json<Records<AuditLogEntry>>, Status> {
+    let offset = offset.unwrap_or(0);
+
+    db.run(move |c| audit_log::find_all(c, limit.unwrap_or(10), offset)).await
+        .map(|mut data| Json(
+            Records {
+                records: data.0.drain(..).map(|e| e.as_entry()).collect(),
+                offset,
+                total_count: Some(data.1),
+                next_cursor: None
+            }
+        ))
+        .map_err(server_error)
+}
+
+/// Returns all the admin routes to serve
+pub fn get_routes() -> Vec<rocket::Route> {
+    routes![
+        admin_users,
+        admin_user_add,
+        admin_user_delete,
+        admin_user_records,
+        admin_user_record_add,
+        admin_record_delete,
+        admin_records_export,
+        admin_audit,
+    ]
+}
@@ -0,0 +1,333 @@
+use rocket::http::{Cookie, SameSite, Status};
+use rocket::outcome::try_outcome;
+use rocket::request::{self, Request, FromRequest};
+use rocket::serde::Deserialize;
+use rocket::State;
+
+use std::sync::OnceLock;
+
+use chrono::{Duration, Utc};
+
+use super::UserDbConn;
+
+use shared_types::models::{ApiTokenModel, PasswordHashParams, SessionModel, UserModel};
+use shared_types::queries::*;
+use shared_types::types::{TokenScope, UserAuthToken, UserRole};
+
+/// Name of the cookie holding the session ID
+pub const SESSION_COOKIE: &str = "session_id";
+
+/// Default number of seconds a session lasts without being used (1 week)
+const DEFAULT_SESSION_LIFETIME: i64 = 7 * 24 * 60 * 60;
+
+/// Default for whether cookies are only sent over HTTPS
+const DEFAULT_SECURE_COOKIES: bool = true;
+
+/// Default number of failed logins of a username before it is locked
+const DEFAULT_LOGIN_USER_THRESHOLD: u32 = 5;
+
+/// Default number of failed logins from an IP address before it is locked
+const DEFAULT_LOGIN_IP_THRESHOLD: u32 = 20;
+
+/// Default number of seconds of the first lockout (doubled for every further failure)
+const DEFAULT_LOGIN_LOCKOUT_BASE: i64 = 30;
+
+/// Default maximum number of seconds of a lockout (1 hour)
+const DEFAULT_LOGIN_LOCKOUT_MAX: i64 = 60 * 60;
+
+/// Default number of seconds after which failed logins are forgotten (15 minutes)
+const DEFAULT_LOGIN_FAILURE_WINDOW: i64 = 15 * 60;
+
+/// Authentication settings read from Rocket.toml
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AuthConfig {
+    /// Number of seconds a session lasts without being used
+    #[serde(default = "default_session_lifetime")]
+    pub session_lifetime: i64,
+    /// Whether cookies are only sent over HTTPS (turn off for local development over HTTP)
+    #[serde(default = "default_secure_cookies")]
+    pub secure_cookies: bool,
+    /// Number of failed logins of a username before it is locked
+    #[serde(default = "default_login_user_threshold")]
+    pub login_user_threshold: u32,
+    /// Number of failed logins from an IP address before it is locked
+    #[serde(default = "default_login_ip_threshold")]
+    pub login_ip_threshold: u32,
+    /// Number of seconds of the first lockout
+    #[serde(default = "default_login_lockout_base")]
+    pub login_lockout_base: i64,
+    /// Maximum number of seconds of a lockout
+    #[serde(default = "default_login_lockout_max")]
+    pub login_lockout_max: i64,
+    /// Number of seconds after which failed logins are forgotten
+    #[serde(default = "default_login_failure_window")]
+    pub login_failure_window: i64,
+    /// KiB of memory used to hash a password
+    #[serde(default = "default_password_memory_cost")]
+    pub password_memory_cost: u32,
+    /// Number of passes used to hash a password
+    #[serde(default = "default_password_time_cost")]
+    pub password_time_cost: u32,
+    /// Number of lanes used to hash a password
+    #[serde(default = "default_password_lanes")]
+    pub password_lanes: u32,
+}
+
+fn default_session_lifetime() -> i64 {
+    DEFAULT_SESSION_LIFETIME
+}
+
+fn default_secure_cookies() -> bool {
+    DEFAULT_SECURE_COOKIES
+}
+
+fn default_login_user_threshold() -> u32 {
+    DEFAULT_LOGIN_USER_THRESHOLD
+}
+
+fn default_login_ip_threshold() -> u32 {
+    DEFAULT_LOGIN_IP_THRESHOLD
+}
+
+fn default_login_lockout_base() -> i64 {
+    DEFAULT_LOGIN_LOCKOUT_BASE
+}
+
+fn default_login_lockout_max() -> i64 {
+    DEFAULT_LOGIN_LOCKOUT_MAX
+}
+
+fn default_login_failure_window() -> i64 {
+    DEFAULT_LOGIN_FAILURE_WINDOW
+}
+
+fn default_password_memory_cost() -> u32 {
+    PasswordHashParams::default().mem_cost
+}
+
+fn default_password_time_cost() -> u32 {
+    PasswordHashParams::default().time_cost
+}
+
+fn default_password_lanes() -> u32 {
+    PasswordHashParams::default().lanes
+}
+
+impl AuthConfig {
+    /// How long a session lasts without being used
+    pub fn session_lifetime(&self) -> Duration {
+        Duration::seconds(self.session_lifetime)
+    }
+
+    /// How long failed logins are remembered
+    pub fn login_failure_window(&self) -> Duration {
+        Duration::seconds(self.login_failure_window)
+    }
+
+    /// Parameters new password hashes are made with
+    pub fn password_hash_params(&self) -> PasswordHashParams {
+        PasswordHashParams {
+            mem_cost: self.password_memory_cost,
+            time_cost: self.password_time_cost,
+            lanes: self.password_lanes
+        }
+    }
+
+    /// User that logins to unknown usernames are verified against, so they take
+    /// as long to reject as a wrong password
+    /// 
+    /// Its password is hashed once with the configured parameters (None if hashing fails)
+    pub fn dummy_user(&self) -> Option<&'static UserModel> {
+        static DUMMY_USER: OnceLock<UserModel> = OnceLock::new();
+
+        if let Some(user) = DUMMY_USER.get() {
+            return Some(user);
+        }
+
+        match UserModel::generate_new(String::new(), String::from("dummy password"), &self.password_hash_params()) {
+            Ok(user) => Some(DUMMY_USER.get_or_init(|| user)),
+            Err(err) => {
+                error!("Hashing the dummy password failed: {:?}", err);
+                None
+            }
+        }
+    }
+}
+
+/// Session of the logged in user
+/// 
+/// Validated against the sessions table on every request, using the
+/// session also pushes back when it expires
+#[derive(Debug)]
+pub struct UserSession {
+    pub session_id: String,
+    pub user_id: String,
+}
+
+impl UserSession {
+    /// Token used for the queries that act on the user
+    pub fn auth_token(&self) -> UserAuthToken {
+        UserAuthToken::from(self.user_id.clone())
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserSession {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let session_id = match req.cookies().get_private(SESSION_COOKIE) {
+            Some(cookie) => String::from(cookie.value()),
+            None => return request::Outcome::Failure((Status::Unauthorized, ()))
+        };
+
+        let db = try_outcome!(req.guard::<UserDbConn>().await);
+        let lifetime = try_outcome!(req.guard::<&State<AuthConfig>>().await).session_lifetime();
+
+        let session = db.run(move |c| {
+            sessions::refresh(c, &session_id, Utc::now().naive_utc(), lifetime)
+        }).await;
+
+        match session {
+            Ok(Some(session)) => request::Outcome::Success(UserSession {
+                session_id: session.session_id,
+                user_id: session.user_id,
+            }),
+            Ok(None) => {
+                // Session expired or was revoked
+                req.cookies().remove_private(Cookie::named(SESSION_COOKIE));
+                request::Outcome::Failure((Status::Unauthorized, ()))
+            },
+            Err(err) => {
+                error!("{:?}", err);
+                request::Outcome::Failure((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+/// User authenticated by either their session cookie or a personal API token
+/// 
+/// Tokens are read from the `Authorization: Bearer <token>` header, if the header
+/// is given the session cookie is ignored
+#[derive(Debug)]
+pub enum UserAuth {
+    Session(UserSession),
+    Token(ApiTokenModel),
+}
+
+impl UserAuth {
+    /// Token used for the queries that act on the user if they are allowed to use the scope
+    /// 
+    /// Sessions can do anything, API tokens only what they were created for
+    pub fn require(&self, scope: TokenScope) -> Result<UserAuthToken, Status> {
+        match self {
+            UserAuth::Session(session) => Ok(session.auth_token()),
+            UserAuth::Token(token) if token.has_scope(scope) => Ok(UserAuthToken::from(token.user_id.clone())),
+            UserAuth::Token(_) => Err(Status::Forbidden)
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserAuth {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let header = match req.headers().get_one("Authorization") {
+            Some(header) => header,
+            None => return req.guard::<UserSession>().await.map(UserAuth::Session)
+        };
+
+        let hash = match header.strip_prefix("Bearer ") {
+            Some(token) => ApiTokenModel::hash_token(token.trim()),
+            None => return request::Outcome::Failure((Status::Unauthorized, ()))
+        };
+
+        let db = try_outcome!(req.guard::<UserDbConn>().await);
+
+        match db.run(move |c| api_tokens::authenticate(c, &hash, Utc::now().naive_utc())).await {
+            Ok(Some(token)) => request::Outcome::Success(UserAuth::Token(token)),
+            Ok(None) => request::Outcome::Failure((Status::Unauthorized, ())),
+            Err(err) => {
+                error!("{:?}", err);
+                request::Outcome::Failure((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+/// Session of a logged in admin
+/// 
+/// Checks the user's role on every request so demoted admins lose access straight away
+#[derive(Debug)]
+pub struct AdminSession {
+    pub user_id: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminSession {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let session = try_outcome!(req.guard::<UserSession>().await);
+        let db = try_outcome!(req.guard::<UserDbConn>().await);
+
+        match db.run(move |c| users::find_by_id(c, &session.user_id)).await {
+            Ok(Some(user)) if user.role == UserRole::Admin => request::Outcome::Success(AdminSession {
+                user_id: user.user_id
+            }),
+            Ok(_) => request::Outcome::Failure((Status::Forbidden, ())),
+            Err(err) => {
+                error!("{:?}", err);
+                request::Outcome::Failure((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+/// User agent of the client (if given)
+pub struct UserAgent(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserAgent {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(UserAgent(req.headers().get_one("User-Agent").map(String::from)))
+    }
+}
+
+impl UserAgent {
+    pub fn into_inner(self) -> Option<String> {
+        self.0
+    }
+}
+
+/// Create a new session for the user
+/// 
+/// Expired sessions are cleaned up at the same time
+/// 
+/// The cookie can't be read by scripts and is only sent to this site (see [super::csrf] for
+/// the rest of the CSRF protection)
+pub fn start_session(
+    conn: &diesel::SqliteConnection,
+    user_id: String,
+    user_agent: Option<String>,
+    config: &AuthConfig
+) -> Result<Cookie<'static>, diesel::result::Error> {
+    let session = SessionModel::generate_new(user_id, user_agent, config.session_lifetime());
+
+    sessions::delete_expired(conn, session.created_at)?;
+    sessions::add(conn, &session)?;
+
+    Ok(
+        Cookie::build(SESSION_COOKIE, session.session_id)
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Strict)
+            .secure(config.secure_cookies)
+            .finish()
+    )
+}
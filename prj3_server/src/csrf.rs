@@ -0,0 +1,191 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Cookie, Method, SameSite, Status};
+use rocket::http::uri::Origin;
+use rocket::serde::json::Json;
+use rocket::{Data, Request, Response};
+
+use shared_types::types::{ApiError, CSRF_COOKIE, CSRF_HEADER};
+
+use super::auth::{AuthConfig, SESSION_COOKIE};
+
+/// Number of random bytes in a CSRF token
+const TOKEN_BYTES: usize = 32;
+
+/// Path of the route requests that fail the CSRF check are sent to
+const REJECTED_PATH: &str = "/api/v1/csrf/rejected";
+
+/// Double-submit CSRF protection
+/// 
+/// Every client is given a random token in the csrf_token cookie, requests that can change
+/// anything (POST, PUT, PATCH and DELETE) must send the same token in the X-CSRF-Token header.
+/// Other sites can make a browser send the cookie but can't read it to set the header.
+/// 
+/// Requests with an API token are exempt since they don't rely on cookies, unless they
+/// also carry a session cookie (routes that only take sessions would still accept them)
+pub struct Csrf;
+
+impl Csrf {
+    /// Returns true if the request has to pass the CSRF check
+    fn is_checked(req: &Request<'_>) -> bool {
+        let uses_token = req.headers().contains("Authorization") && req.cookies().get(SESSION_COOKIE).is_none();
+
+        matches!(req.method(), Method::Post | Method::Put | Method::Patch | Method::Delete) && !uses_token
+    }
+
+    /// Returns true if the header matches the cookie
+    fn is_valid(req: &Request<'_>) -> bool {
+        match (req.cookies().get(CSRF_COOKIE), req.headers().get_one(CSRF_HEADER)) {
+            (Some(cookie), Some(header)) => constant_time_eq(cookie.value().as_bytes(), header.as_bytes()),
+            _ => false
+        }
+    }
+
+    /// Generate a new token cookie
+    fn new_cookie(secure: bool) -> Cookie<'static> {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let token: String = (0..TOKEN_BYTES).map(|_| format!("{:02x}", rng.gen::<u8>())).collect();
+
+        // Not HttpOnly since the client's scripts have to read it
+        Cookie::build(CSRF_COOKIE, token)
+            .path("/")
+            .same_site(SameSite::Strict)
+            .secure(secure)
+            .finish()
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Csrf {
+    fn info(&self) -> Info {
+        Info {
+            name: "CSRF Protection",
+            kind: Kind::Request | Kind::Response
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        if Self::is_checked(req) && !Self::is_valid(req) {
+            // Fairings can't respond directly so send the request somewhere harmless instead
+            req.set_method(Method::Get);
+            req.set_uri(Origin::parse(REJECTED_PATH).unwrap());
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        // Give clients without a token one
+        if req.cookies().get(CSRF_COOKIE).is_none() {
+            let secure = req.rocket().state::<AuthConfig>().map(|config| config.secure_cookies).unwrap_or(true);
+            res.adjoin_header(Self::new_cookie(secure));
+        }
+    }
+}
+
+/// Compare without exiting early so the time taken doesn't leak the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// CSRF Rejected Route
+/// 
+/// Requests that fail the CSRF check are rerouted here instead of reaching their route
+#[get("/csrf/rejected")]
+fn csrf_rejected() -> (Status, Json<ApiError>) {
+    (Status::Forbidden, Json(ApiError::new(403, "Missing or invalid CSRF token")))
+}
+
+/// CSRF Token Route
+/// 
+/// Does nothing, clients without a token call it to be given one in the response
+#[get("/csrf")]
+fn csrf_token() -> Status {
+    Status::NoContent
+}
+
+/// Returns the CSRF routes to serve
+pub fn get_routes() -> Vec<rocket::Route> {
+    routes![csrf_rejected, csrf_token]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rocket::http::Header;
+    use rocket::local::blocking::Client;
+
+    #[post("/change")]
+    fn change() -> Status {
+        Status::Ok
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::build()
+            .attach(Csrf)
+            .mount("/api/v1/", get_routes())
+            .mount("/", routes![change]);
+        Client::untracked(rocket).unwrap()
+    }
+
+    fn token_cookie() -> Cookie<'static> {
+        Cookie::new(CSRF_COOKIE, "token")
+    }
+
+    #[test]
+    fn clients_without_a_token_are_given_one() {
+        let client = client();
+        let response = client.get("/api/v1/csrf").dispatch();
+        assert_eq!(response.status(), Status::NoContent);
+        assert!(response.cookies().get(CSRF_COOKIE).is_some());
+
+        let response = client.get("/api/v1/csrf").cookie(token_cookie()).dispatch();
+        assert!(response.cookies().get(CSRF_COOKIE).is_none());
+    }
+
+    #[test]
+    fn matching_header_is_accepted() {
+        let client = client();
+        let response = client.post("/change")
+            .cookie(token_cookie())
+            .header(Header::new(CSRF_HEADER, "token"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn missing_or_mismatched_header_is_rejected() {
+        let client = client();
+
+        let response = client.post("/change").cookie(token_cookie()).dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+
+        let response = client.post("/change")
+            .cookie(token_cookie())
+            .header(Header::new(CSRF_HEADER, "other"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+
+        // A header without the cookie to match isn't enough either
+        let response = client.post("/change").header(Header::new(CSRF_HEADER, "token")).dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn bearer_requests_are_exempt() {
+        let client = client();
+        let response = client.post("/change")
+            .header(Header::new("Authorization", "Bearer token"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn bearer_requests_with_a_session_cookie_are_checked() {
+        let client = client();
+        let response = client.post("/change")
+            .header(Header::new("Authorization", "Bearer token"))
+            .cookie(Cookie::new(SESSION_COOKIE, "session"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+}
@@ -0,0 +1,48 @@
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::serde::json::Json;
+
+use shared_types::types::ApiError;
+use shared_types::validation::ValidationErrors;
+
+/// Response for a form with invalid fields (see [shared_types::validation])
+pub fn invalid_input(errors: ValidationErrors) -> (Status, Json<ApiError>) {
+    (Status::UnprocessableEntity, Json(ApiError::invalid_input(errors)))
+}
+
+/// Map a query error to a status, logging the details
+pub fn server_error(err: diesel::result::Error) -> Status {
+    error!("{:?}", err);
+    Status::InternalServerError
+}
+
+/// Response for an error status, for routes that can't fail with a bare status
+/// because they also fail with field errors
+pub fn error_response(status: Status) -> (Status, Json<ApiError>) {
+    let message = match status.code {
+        400 => "Malformed request",
+        401 => "Invalid credentials or not logged in",
+        403 => "You are not allowed to do that",
+        404 => "Not found",
+        422 => "Invalid input",
+        429 => "Too many requests, try again later",
+        500..=599 => "Server error occured, please try again later",
+        _ => status.reason_lossy()
+    };
+
+    (status, Json(ApiError::new(status.code, message)))
+}
+
+/// Catcher for every error status a route fails with
+///
+/// Always responds with a JSON serialized [ApiError] so the client can
+/// show what went wrong
+#[catch(default)]
+fn default_catcher(status: Status, _req: &Request) -> (Status, Json<ApiError>) {
+    error_response(status)
+}
+
+/// Returns the catchers to register
+pub fn get_catchers() -> Vec<rocket::Catcher> {
+    catchers![default_catcher]
+}
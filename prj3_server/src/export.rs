@@ -0,0 +1,153 @@
+use std::pin::Pin;
+
+use chrono::{NaiveDateTime, SecondsFormat};
+
+use rocket::futures::Stream;
+use rocket::http::{ContentType, Header};
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket::response::stream::ByteStream;
+use rocket::serde::json::serde_json;
+
+use shared_types::types::*;
+use shared_types::queries::match_records;
+
+use super::UserDbConn;
+
+/// Number of records loaded from the database at once while exporting
+const EXPORT_BATCH_SIZE: i64 = 500;
+
+/// Message written at the end of an export that couldn't load every record
+const EXPORT_ERROR: &str = "Export failed, some records are missing";
+
+/// Columns of a CSV export
+const CSV_HEADER: &str = "user_id,finished_at,game_id,cpu_level,moves,result,first_mover,columns,rows\n";
+
+/// Which records to export
+pub struct ExportOptions {
+    /// User to export the records of (every user if None)
+    pub user_id: Option<String>,
+    pub filter: Option<MatchQueryFilter>,
+    pub before: Option<NaiveDateTime>,
+    pub after: Option<NaiveDateTime>,
+}
+
+/// Streamed download of match records, oldest first
+/// 
+/// Records are loaded in batches with cursors (see [match_records::PageOptions])
+/// so the whole export never has to be held in memory
+pub struct RecordExport {
+    format: ExportFormat,
+    body: ByteStream<Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>>,
+}
+
+impl RecordExport {
+    /// Start exporting the records that match the options
+    pub fn new(db: UserDbConn, format: ExportFormat, options: ExportOptions) -> Self {
+        let body = ByteStream! {
+            if format == ExportFormat::Csv {
+                yield CSV_HEADER.as_bytes().to_vec();
+            }
+
+            let mut cursor = None;
+            loop {
+                let page = match_records::PageOptions {
+                    cursor,
+                    offset: 0,
+                    limit: EXPORT_BATCH_SIZE,
+                    count: false
+                };
+                let user_id = options.user_id.clone();
+                let filter = options.filter.clone();
+                let (before, after) = (options.before, options.after);
+
+                let batch = db.run(move |c| match user_id {
+                    Some(user_id) => match_records::find_by_user(
+                        c, &user_id, filter, MatchQuerySortBy::StartTime, true, before, after, page
+                    ),
+                    None => match_records::find_all_users(
+                        c, filter, MatchQuerySortBy::StartTime, true, before, after, page
+                    )
+                }).await;
+
+                // The status was already sent, so all we can do is mark the file as incomplete
+                let (records, _, next_cursor) = match batch {
+                    Ok(batch) => batch,
+                    Err(err) => {
+                        error!("Exporting match records failed: {:?}", err);
+                        yield export_error(format).into_bytes();
+                        break;
+                    }
+                };
+
+                let mut chunk = String::new();
+                for record in records {
+                    chunk.push_str(&export_line(format, &record.as_record()));
+                }
+                yield chunk.into_bytes();
+
+                match next_cursor {
+                    Some(next_cursor) => cursor = Some(next_cursor),
+                    None => break
+                }
+            }
+        };
+
+        RecordExport { format, body: ByteStream(Box::pin(body.0)) }
+    }
+}
+
+impl<'r> Responder<'r, 'r> for RecordExport {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'r> {
+        let content_type = match self.format {
+            ExportFormat::Csv => ContentType::CSV,
+            ExportFormat::Jsonl => ContentType::new("application", "x-ndjson")
+        };
+        let disposition = format!("attachment; filename=\"match_records.{}\"", self.format.extension());
+
+        Response::build_from(self.body.respond_to(request)?)
+            .header(content_type)
+            .header(Header::new("Content-Disposition", disposition))
+            .ok()
+    }
+}
+
+/// Write a record as a line of the export
+/// 
+/// Matches of unknown board size have empty `columns` and `rows` in CSV (null in JSON Lines)
+fn export_line(format: ExportFormat, record: &MatchRecord) -> String {
+    match format {
+        ExportFormat::Csv => format!(
+            "{},{},{:?},{:?},{},{:?},{:?},{},{}\n",
+            csv_field(record.user_id.as_deref().unwrap_or("")),
+            record.finished_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            record.game_id,
+            record.cpu_level,
+            record.moves,
+            record.result,
+            record.first_mover,
+            record.columns.map(|columns| columns.to_string()).unwrap_or_default(),
+            record.rows.map(|rows| rows.to_string()).unwrap_or_default()
+        ),
+        ExportFormat::Jsonl => format!("{}\n", serde_json::to_string(record).unwrap())
+    }
+}
+
+/// Write the line ending an export that stopped early
+/// 
+/// CSV gets a `#error` comment line, JSON Lines an object with an `error` field
+fn export_error(format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => format!("#error,{}\n", csv_field(EXPORT_ERROR)),
+        ExportFormat::Jsonl => format!("{}\n", serde_json::json!({ "error": EXPORT_ERROR }))
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+use rocket::http::Header;
+use rocket::serde::json::Json;
+
+use chrono::{Duration, NaiveDateTime};
+
+use shared_types::models::LockoutModel;
+use shared_types::types::ApiError;
+
+use super::auth::AuthConfig;
+
+/// Number of tracked addresses (or users) after which stale entries are pruned
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Number of puzzle solutions a user can submit per [PUZZLE_ATTEMPT_WINDOW] seconds
+const PUZZLE_ATTEMPT_LIMIT: u32 = 10;
+
+/// Length in seconds of the window puzzle solutions are counted in
+const PUZZLE_ATTEMPT_WINDOW: i64 = 60;
+
+/// Failed logins from a single client address
+struct Failures {
+    count: u32,
+    last_failure: NaiveDateTime,
+    locked_until: Option<NaiveDateTime>,
+}
+
+/// Limits failed logins per client IP address
+/// 
+/// Kept in memory only, so the limits reset when the server restarts
+#[derive(Default)]
+pub struct LoginLimiter {
+    failures: Mutex<HashMap<IpAddr, Failures>>,
+}
+
+impl LoginLimiter {
+    /// Time left before the address may attempt a login again (if it is locked)
+    pub fn locked_for(&self, ip: IpAddr, now: NaiveDateTime) -> Option<Duration> {
+        let failures = self.failures.lock().unwrap();
+
+        failures.get(&ip).and_then(|f| remaining(f.locked_until, now))
+    }
+
+    /// Count a failed login from the address
+    pub fn record_failure(&self, ip: IpAddr, now: NaiveDateTime, config: &AuthConfig) {
+        let mut failures = self.failures.lock().unwrap();
+
+        // Forget addresses that have stopped failing
+        if failures.len() >= PRUNE_THRESHOLD {
+            failures.retain(|_, f| !is_stale(f.last_failure, f.locked_until, now, config));
+        }
+
+        let previous = failures.get(&ip).map(|f| (f.count, f.last_failure, f.locked_until));
+        let (count, locked_until) = next_failure(previous, now, config.login_ip_threshold, config);
+
+        failures.insert(ip, Failures { count, last_failure: now, locked_until });
+    }
+
+    /// Forget the failed logins of the address
+    pub fn clear(&self, ip: IpAddr) {
+        self.failures.lock().unwrap().remove(&ip);
+    }
+}
+
+/// Puzzle solutions submitted by a user since the start of a window
+struct Attempts {
+    count: u32,
+    window_start: NaiveDateTime,
+}
+
+/// Limits the puzzle solutions each user can submit, since checking one searches the puzzle
+/// 
+/// Kept in memory only, so the limits reset when the server restarts
+#[derive(Default)]
+pub struct PuzzleLimiter {
+    attempts: Mutex<HashMap<String, Attempts>>,
+}
+
+impl PuzzleLimiter {
+    /// Count a solution submitted by the user
+    /// 
+    /// Returns the time left before they may submit another if they're over the limit
+    pub fn record_attempt(&self, user_id: &str, now: NaiveDateTime) -> Option<Duration> {
+        let window = Duration::seconds(PUZZLE_ATTEMPT_WINDOW);
+        let mut attempts = self.attempts.lock().unwrap();
+
+        // Forget users whose window has ended
+        if attempts.len() >= PRUNE_THRESHOLD {
+            attempts.retain(|_, a| now - a.window_start < window);
+        }
+
+        let entry = attempts.entry(String::from(user_id)).or_insert(Attempts { count: 0, window_start: now });
+
+        if now - entry.window_start >= window {
+            *entry = Attempts { count: 0, window_start: now };
+        }
+
+        if entry.count >= PUZZLE_ATTEMPT_LIMIT {
+            return Some(entry.window_start + window - now);
+        }
+
+        entry.count += 1;
+        None
+    }
+}
+
+/// Time left before the username may be logged into again (if it is locked)
+pub fn user_locked_for(lockout: &LockoutModel, now: NaiveDateTime) -> Option<Duration> {
+    remaining(lockout.locked_until, now)
+}
+
+/// Count a failed login of the username, returning the updated lockout
+pub fn record_user_failure(
+    previous: Option<LockoutModel>,
+    user_key: String,
+    now: NaiveDateTime,
+    config: &AuthConfig
+) -> LockoutModel {
+    let previous = previous.map(|l| (l.failures as u32, l.last_failure, l.locked_until));
+    let (count, locked_until) = next_failure(previous, now, config.login_user_threshold, config);
+
+    LockoutModel {
+        user_key,
+        failures: count as i32,
+        last_failure: now,
+        locked_until,
+    }
+}
+
+/// Response to a login attempt while locked
+/// 
+/// Tells the client how many seconds to wait before trying again
+#[derive(Responder)]
+#[response(status = 429)]
+pub struct TooManyAttempts {
+    error: Json<ApiError>,
+    retry_after: Header<'static>,
+}
+
+impl TooManyAttempts {
+    pub fn new(wait: Duration) -> Self {
+        TooManyAttempts {
+            error: Json(ApiError::new(429, "Too many failed logins, try again later")),
+            retry_after: Header::new("Retry-After", wait.num_seconds().max(1).to_string()),
+        }
+    }
+}
+
+/// Time left until the lock ends (None if not locked)
+fn remaining(locked_until: Option<NaiveDateTime>, now: NaiveDateTime) -> Option<Duration> {
+    locked_until
+        .map(|until| until - now)
+        .filter(|wait| *wait > Duration::zero())
+}
+
+/// Whether failures are old enough to be forgotten
+fn is_stale(
+    last_failure: NaiveDateTime,
+    locked_until: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+    config: &AuthConfig
+) -> bool {
+    now - last_failure > config.login_failure_window() && remaining(locked_until, now).is_none()
+}
+
+/// Failure count and lock end after another failed login
+/// 
+/// Failures older than the failure window are forgotten, once the threshold
+/// is reached every further failure doubles the lock time (up to the maximum)
+fn next_failure(
+    previous: Option<(u32, NaiveDateTime, Option<NaiveDateTime>)>,
+    now: NaiveDateTime,
+    threshold: u32,
+    config: &AuthConfig
+) -> (u32, Option<NaiveDateTime>) {
+    let count = match previous {
+        Some((count, last_failure, locked_until)) if !is_stale(last_failure, locked_until, now, config) => count + 1,
+        _ => 1
+    };
+
+    let locked_until = if count >= threshold {
+        let doublings = (count - threshold).min(31);
+        let lockout = config.login_lockout_base
+            .saturating_mul(1i64 << doublings)
+            .min(config.login_lockout_max);
+        Some(now + Duration::seconds(lockout))
+    } else {
+        None
+    };
+
+    (count, locked_until)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Config with the default thresholds, 30s first lockout and 4 minute maximum
+    fn config() -> AuthConfig {
+        let mut config: AuthConfig = rocket::serde::json::from_str("{}").unwrap();
+        config.login_lockout_base = 30;
+        config.login_lockout_max = 4 * 60;
+        config
+    }
+
+    fn time(seconds: i64) -> NaiveDateTime {
+        NaiveDateTime::from_timestamp_opt(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    /// Lock times in seconds after `failures` failures one second apart
+    fn lockouts(failures: u32, threshold: u32, config: &AuthConfig) -> Vec<Option<i64>> {
+        let mut previous = None;
+        (0..failures).map(|i| {
+            let now = time(i as i64);
+            let (count, locked_until) = next_failure(previous, now, threshold, config);
+            previous = Some((count, now, locked_until));
+            locked_until.map(|until| (until - now).num_seconds())
+        }).collect()
+    }
+
+    #[test]
+    fn lockout_doubles_after_the_threshold() {
+        assert_eq!(
+            lockouts(6, 3, &config()),
+            vec![None, None, Some(30), Some(60), Some(120), Some(240)]
+        );
+    }
+
+    #[test]
+    fn lockout_is_capped_at_the_maximum() {
+        let config = config();
+        assert_eq!(lockouts(8, 3, &config)[5..], [Some(240), Some(240), Some(240)]);
+        // Doublings past the width of the lockout don't overflow
+        assert_eq!(lockouts(100, 1, &config).last(), Some(&Some(240)));
+    }
+
+    #[test]
+    fn stale_failures_are_forgotten() {
+        let config = config();
+        let window = config.login_failure_window;
+
+        // Outside the window and not locked, counting starts over
+        let (count, locked_until) = next_failure(Some((2, time(0), None)), time(window + 1), 3, &config);
+        assert_eq!((count, locked_until), (1, None));
+
+        // Inside the window the count carries on
+        let (count, locked_until) = next_failure(Some((2, time(0), None)), time(window), 3, &config);
+        assert_eq!((count, locked_until), (3, Some(time(window + 30))));
+
+        // Still locked, old failures are kept even outside the window
+        let previous = Some((5, time(0), Some(time(window + 10))));
+        let (count, _) = next_failure(previous, time(window + 1), 3, &config);
+        assert_eq!(count, 6);
+    }
+}
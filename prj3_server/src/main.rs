@@ -4,8 +4,15 @@
 use diesel::Connection;
 use diesel::SqliteConnection;
 
+use rocket::fairing::AdHoc;
 use rocket_sync_db_pools::{diesel, database};
 
+mod admin;
+mod auth;
+mod csrf;
+mod errors;
+mod export;
+mod limiter;
 mod routes;
 
 #[database("user_database")]
@@ -15,7 +22,14 @@ pub struct UserDbConn(SqliteConnection);
 fn rocket() -> _ {
     let rocket = rocket::build()
         .attach(UserDbConn::fairing())
-        .mount("/api/v1/", routes::get_routes());
+        .attach(AdHoc::config::<auth::AuthConfig>())
+        .attach(csrf::Csrf)
+        .manage(limiter::LoginLimiter::default())
+        .manage(limiter::PuzzleLimiter::default())
+        .mount("/api/v1/", routes::get_routes())
+        .mount("/api/v1/", csrf::get_routes())
+        .mount("/api/v1/admin/", admin::get_routes())
+        .register("/", errors::get_catchers());
 
     // Run database migrations if "build_database" feature set
     #[cfg(feature = "build_database")]
@@ -1,13 +1,66 @@
+use std::net::IpAddr;
+
 use rocket::http::{Cookie, CookieJar, Status};
 use rocket::form::Form;
+use rocket::State;
 
 use rocket::serde::json::Json;
 
 use super::UserDbConn;
+use super::errors::{error_response, invalid_input, server_error};
+use super::auth::*;
+use super::limiter::*;
+use super::export::{ExportOptions, RecordExport};
+
+use chrono::{NaiveDate, Utc};
 
-use shared_types::models::{UserModel, MatchRecordModel};
+use shared_types::game::{PossibleMove, Puzzle};
+use shared_types::models::{ApiTokenModel, UserModel, MatchRecordModel};
 use shared_types::types::*;
 use shared_types::queries::*;
+use shared_types::validation::*;
+
+/// Number of days back the activity of a user's stats goes
+const STATS_ACTIVITY_DAYS: i64 = 90;
+
+/// Get the page of match records to list from the query args
+/// 
+/// Records are only counted when asked to, or on the first page if not specified
+/// 
+/// Returns Status 400 if the cursor is malformed or was made for another sort order
+pub(crate) fn match_record_page(
+    sort_by: MatchQuerySortBy,
+    asc: bool,
+    cursor: Option<String>,
+    limit: i64,
+    offset: i64,
+    count: Option<bool>
+) -> Result<match_records::PageOptions, Status> {
+    let cursor = match cursor {
+        Some(cursor) => Some(MatchRecordCursor::parse_for(&cursor, sort_by, asc).ok_or(Status::BadRequest)?),
+        None => None
+    };
+
+    Ok(match_records::PageOptions {
+        cursor,
+        offset,
+        limit,
+        count: count.unwrap_or(cursor.is_none())
+    })
+}
+
+/// Build the response for a page of match records
+pub(crate) fn match_record_response(
+    (records, total_count, next_cursor): (Vec<MatchRecordModel>, Option<i64>, Option<MatchRecordCursor>),
+    offset: i64
+) -> Json<Records<MatchRecord>> {
+    Json(Records {
+        records: records.into_iter().map(|r| r.as_record()).collect(),
+        offset,
+        total_count,
+        next_cursor: next_cursor.map(|cursor| cursor.to_string())
+    })
+}
 
 /// User Login Route
 /// 
@@ -15,88 +68,170 @@ use shared_types::queries::*;
 /// 
 /// On Success
 ///  - Returns status 200
-///  - Sets user_auth_form cookie as encrypted value
+///  - Starts a new session and sets the session_id cookie as encrypted value
+/// 
+/// On Failure
+///  - Returns status 401 if the username or password is wrong
+///  - Returns status 429 with a Retry-After header if the username or client
+///    address is locked after too many failed logins
 #[post("/user/login", data="<auth>")]
-async fn user_login(db: UserDbConn, auth: Form<UserAuthForm>, cookies: &CookieJar<'_>) -> Status {
+async fn user_login(
+    db: UserDbConn,
+    auth: Form<UserAuthForm>,
+    user_agent: UserAgent,
+    client_ip: Option<IpAddr>,
+    config: &State<AuthConfig>,
+    limiter: &State<LoginLimiter>,
+    cookies: &CookieJar<'_>
+) -> Result<Status, TooManyAttempts> {
+    let now = Utc::now().naive_utc();
 
-    let (status, auth_cookie) = db.run(move |c| {
-        match users::find_by_id(c, &auth.user_id) {
-            // Find user
-            Ok(Some(user)) => {
-                // Verify password
-                match user.compare(&auth.password) {
-                    Ok(true) => {
-                        (Status::Ok, Some(Cookie::build("user_auth_token", user.user_id).finish()))
-                    },
-                    Ok(false) => {
-                        (Status::Unauthorized, None)
-                    },
+    // Refuse locked addresses before touching the database
+    if let Some(wait) = client_ip.and_then(|ip| limiter.locked_for(ip, now)) {
+        return Err(TooManyAttempts::new(wait));
+    }
+
+    let user_agent = user_agent.into_inner();
+    let auth_config = config.inner().clone();
+
+    let (result, auth_cookie) = db.run(move |c| {
+        let key = username_key(&auth.user_id);
+
+        // Refuse locked usernames before verifying the password
+        let lockout = match lockouts::find(c, &key) {
+            Ok(lockout) => lockout,
+            Err(err) => {
+                error!("{:?}", err);
+                return (Ok(Status::InternalServerError), None);
+            }
+        };
+        if let Some(wait) = lockout.as_ref().and_then(|l| user_locked_for(l, now)) {
+            return (Err(TooManyAttempts::new(wait)), None);
+        }
+
+        // Find user and verify password (unknown usernames against a dummy hash so
+        // they take as long to reject and are locked out the same way)
+        let verified = match users::find_by_id(c, &auth.user_id) {
+            Ok(Some(user)) => user.compare(&auth.password).map(|ok| Some(user).filter(|_| ok)),
+            Ok(None) => match auth_config.dummy_user() {
+                Some(dummy) => dummy.compare(&auth.password).map(|_| None),
+                None => return (Ok(Status::InternalServerError), None)
+            },
+            Err(err) => {
+                error!("{:?}", err);
+                return (Ok(Status::InternalServerError), None);
+            }
+        };
+
+        match verified {
+            Ok(Some(mut user)) => {
+                // Upgrade hashes made with older parameters now that the password is known
+                let hash_params = auth_config.password_hash_params();
+                // (failing to do so is logged but the login still goes through)
+                if user.needs_rehash(&hash_params) {
+                    match user.set_password(&auth.password, &hash_params) {
+                        Ok(()) => if let Err(err) = users::update_password(c, &user.user_id, &user.password) {
+                            error!("{:?}", err);
+                        },
+                        Err(err) => error!("{:?}", err)
+                    }
+                }
+
+                let session = lockouts::delete(c, &key)
+                    .and_then(|_| start_session(c, user.user_id, user_agent, &auth_config));
+                match session {
+                    Ok(cookie) => (Ok(Status::Ok), Some(cookie)),
                     Err(err) => {
-                        eprintln!("{:?}", err);
-                        (Status::InternalServerError, None)
+                        error!("{:?}", err);
+                        (Ok(Status::InternalServerError), None)
                     }
                 }
             },
             Ok(None) => {
-                (Status::Unauthorized, None)
-            }
+                // Count the failure against the username (forgetting old failures of every username)
+                let lockout = record_user_failure(lockout, key, now, &auth_config);
+                match lockouts::delete_stale(c, now - auth_config.login_failure_window(), now)
+                    .and_then(|_| lockouts::save(c, &lockout))
+                {
+                    Ok(()) => (Ok(Status::Unauthorized), None),
+                    Err(err) => {
+                        error!("{:?}", err);
+                        (Ok(Status::InternalServerError), None)
+                    }
+                }
+            },
             Err(err) => {
-                eprintln!("{:?}", err);
-                (Status::InternalServerError, None)
+                error!("{:?}", err);
+                (Ok(Status::InternalServerError), None)
             }
         }
     }).await;
 
-    // Set user_auth_token
+    // Count the failure (or success) against the client address
+    if let Some(ip) = client_ip {
+        match result {
+            Ok(status) if status == Status::Unauthorized => limiter.record_failure(ip, now, config),
+            Ok(status) if status == Status::Ok => limiter.clear(ip),
+            _ => {}
+        }
+    }
+
+    // Set session_id
     if let Some(cookie) = auth_cookie {
         cookies.add_private(cookie);
     }
 
-    status
+    result
 }
 
 /// User Verify Route
 /// 
-/// Checks the value of the user_auth_token cookie and returns the user it is associated with
+/// Checks the session in the session_id cookie and returns the user it is associated with
 /// 
 /// On Success:
 ///  - Returns status 200
 ///  - Returns JSON serialization of UserInfo type
 #[get("/user/verify")]
-async fn user_verify(
-    db: UserDbConn,
-    cookies: &CookieJar<'_>,
-    auth: UserAuthToken
-) -> Result<Json<UserInfo>, Status> {
-    let user_id = auth.into_inner();
-    let user_id_moved = user_id.clone();
-
-    let result = db.run(move |c| 
-        users::find_by_id(c, &user_id_moved).map(|user| user.is_some())
-    ).await;
-
-    match result {
-        Ok(true) => Ok(Json(UserInfo { user_id })),
-        Ok(false) => {
-            cookies.remove_private(Cookie::named("user_auth_token"));
-            Err(Status::Unauthorized)
-        }
+fn user_verify(session: UserSession) -> Json<UserInfo> {
+    Json(UserInfo { user_id: session.user_id })
+}
+
+/// User Logout Route
+/// 
+/// Ends the current session and deletes the session_id cookie effectively "logging" the user out
+/// 
+/// On Success:
+///  - Returns status 200
+#[post("/user/logout")]
+async fn user_logout(db: UserDbConn, cookies: &CookieJar<'_>, session: UserSession) -> Status {
+    cookies.remove_private(Cookie::named(SESSION_COOKIE));
+
+    match db.run(move |c| sessions::delete(c, &session.session_id)).await {
+        Ok(_) => Status::Ok,
         Err(err) => {
-            eprintln!("{:?}", err);
-            Err(Status::InternalServerError)
+            error!("{:?}", err);
+            Status::InternalServerError
         }
     }
 }
 
-/// User Logout Route
+/// Revoke Sessions Route
 /// 
-/// Deletes the user_auth_token cookie effectively "logging" the user out
+/// Ends every session of the authenticated user, logging them out everywhere
 /// 
-/// Always returns status 200
-#[post("/user/logout")]
-fn user_logout(cookies: &CookieJar<'_>, _auth: UserAuthToken) -> Status {
-    cookies.remove_private(Cookie::named("user_auth_token"));
-    Status::Ok
+/// On Success:
+///  - Returns status 200
+#[post("/user/sessions/revoke")]
+async fn user_sessions_revoke(db: UserDbConn, cookies: &CookieJar<'_>, session: UserSession) -> Status {
+    cookies.remove_private(Cookie::named(SESSION_COOKIE));
+
+    match db.run(move |c| sessions::delete_by_user(c, &session.user_id)).await {
+        Ok(_) => Status::Ok,
+        Err(err) => {
+            error!("{:?}", err);
+            Status::InternalServerError
+        }
+    }
 }
 
 /// User Register Route
@@ -105,26 +240,44 @@ fn user_logout(cookies: &CookieJar<'_>, _auth: UserAuthToken) -> Status {
 /// 
 /// On Success
 ///  - Return status 200
-///  - Set user_auth_token cookie (see User Login)
+///  - Set session_id cookie (see User Login)
+/// 
+/// On Invalid Username or Password (see [shared_types::validation])
+///  - Return status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
 #[post("/user/register", data="<auth>")]
-async fn user_register(db: UserDbConn, auth: Form<UserAuthForm>, cookies: &CookieJar<'_>) -> Status {
+async fn user_register(
+    db: UserDbConn,
+    auth: Form<UserAuthForm>,
+    user_agent: UserAgent,
+    config: &State<AuthConfig>,
+    cookies: &CookieJar<'_>
+) -> Result<Status, (Status, Json<ApiError>)> {
     use diesel::result::Error::DatabaseError;
     use diesel::result::DatabaseErrorKind;
 
     let uid = auth.user_id.clone();
     let auth = auth.into_inner();
+    let user_agent = user_agent.into_inner();
+    let auth_config = config.inner().clone();
+
+    validate_new_user(&auth.user_id, &auth.password)
+        .map_err(invalid_input)?;
 
-    match UserModel::generate_new(auth.user_id, auth.password) {
+    match UserModel::generate_new(auth.user_id, auth.password, &auth_config.password_hash_params()) {
         Ok(user) => {
             let (status, auth_cookie) = db.run(move |c| {
-                match users::add(c, user) {
-                    Ok(_) => {
-                        (Status::Ok, Some(Cookie::build("user_auth_token", uid).finish()))
+                match users::add(c, user).and_then(|_| start_session(c, uid, user_agent, &auth_config)) {
+                    Ok(cookie) => (Ok(Status::Ok), Some(cookie)),
+                    Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                        let mut errors = ValidationErrors::default();
+                        errors.check(USERNAME_FIELD, Err(ValidationError::Taken));
+
+                        (Err(invalid_input(errors)), None)
                     },
-                    Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => (Status::Unauthorized, None),
                     Err(err) => {
-                        eprintln!("{:?}", err);
-                        (Status::InternalServerError, None)
+                        error!("{:?}", err);
+                        (Ok(Status::InternalServerError), None)
                     }
                 }
             }).await;
@@ -136,96 +289,480 @@ async fn user_register(db: UserDbConn, auth: Form<UserAuthForm>, cookies: &Cooki
             status
         },
         Err(err) => {
-            eprintln!("{:?}", err);
-            Status::InternalServerError
+            error!("{:?}", err);
+            Ok(Status::InternalServerError)
         }
     }
 
 }
 
+/// Change Password Route
+/// 
+/// Changes the password of the authenticated user, the current password must be given
+/// 
+/// Every other session of the user is ended and all of their API tokens are revoked
+/// 
+/// On Success
+///  - Return status 200
+/// 
+/// On Invalid New Password (see [shared_types::validation])
+///  - Return status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[post("/user/password", data="<form>")]
+async fn user_password(
+    db: UserDbConn,
+    form: Form<PasswordChangeForm>,
+    session: UserSession,
+    config: &State<AuthConfig>
+) -> Result<Status, (Status, Json<ApiError>)> {
+    let form = form.into_inner();
+    let hash_params = config.password_hash_params();
+
+    let mut errors = ValidationErrors::default();
+    errors.check(NEW_PASSWORD_FIELD, validate_password(&session.user_id, &form.new_password));
+    errors.into_result().map_err(invalid_input)?;
+
+    let status = db.run(move |c| {
+        match users::find_by_id(c, &session.user_id) {
+            Ok(Some(mut user)) => {
+                // Verify current password
+                match user.compare(&form.current_password) {
+                    Ok(true) => {
+                        if let Err(err) = user.set_password(&form.new_password, &hash_params) {
+                            error!("{:?}", err);
+                            return Status::InternalServerError;
+                        }
+
+                        // Log out everywhere else and revoke tokens in case the old password was compromised
+                        match users::update_password(c, &user.user_id, &user.password)
+                            .and_then(|_| sessions::delete_other_by_user(c, &user.user_id, &session.session_id))
+                            .and_then(|_| api_tokens::delete_by_user(c, &user.user_id))
+                        {
+                            Ok(_) => Status::Ok,
+                            Err(err) => {
+                                error!("{:?}", err);
+                                Status::InternalServerError
+                            }
+                        }
+                    },
+                    Ok(false) => Status::Unauthorized,
+                    Err(err) => {
+                        error!("{:?}", err);
+                        Status::InternalServerError
+                    }
+                }
+            },
+            Ok(None) => Status::Unauthorized,
+            Err(err) => {
+                error!("{:?}", err);
+                Status::InternalServerError
+            }
+        }
+    }).await;
+
+    Ok(status)
+}
+
+/// Delete Account Route
+/// 
+/// Deletes the authenticated user, the password must be given to confirm
+/// 
+/// The user's sessions are ended and their match records are anonymised
+/// 
+/// On Success
+///  - Return status 200
+///  - Clear session_id cookie
+#[delete("/user", data="<form>")]
+async fn user_delete(
+    db: UserDbConn,
+    form: Form<AccountDeleteForm>,
+    session: UserSession,
+    cookies: &CookieJar<'_>
+) -> Status {
+    let status = db.run(move |c| {
+        match users::find_by_id(c, &session.user_id) {
+            Ok(Some(user)) => {
+                // Verify password
+                match user.compare(&form.password) {
+                    Ok(true) => match users::delete(c, &user.user_id) {
+                        Ok(()) => Status::Ok,
+                        Err(err) => {
+                            error!("{:?}", err);
+                            Status::InternalServerError
+                        }
+                    },
+                    Ok(false) => Status::Unauthorized,
+                    Err(err) => {
+                        error!("{:?}", err);
+                        Status::InternalServerError
+                    }
+                }
+            },
+            Ok(None) => Status::Unauthorized,
+            Err(err) => {
+                error!("{:?}", err);
+                Status::InternalServerError
+            }
+        }
+    }).await;
+
+    if status == Status::Ok {
+        cookies.remove_private(Cookie::named(SESSION_COOKIE));
+    }
+
+    status
+}
+
 /// List User Records Route
 /// 
 /// Query the records for the authenticated user
 /// 
+/// API tokens need the ReadRecords scope
+/// 
 /// For query arg descriptions check the design doc
 /// 
 /// On Success:
 ///  - Return Status 200
 ///  - Return JSON serialized [MatchRecord] list
-#[get("/user/records?<limit>&<offset>&<before>&<after>&<sort_by>&<asc>&<filter>")]
+/// 
+/// On Timestamps Out Of Range:
+///  - Return Status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[get("/user/records?<query..>")]
 async fn user_records(
     db: UserDbConn,
-    auth: UserAuthToken,
-    limit: Option<i64>,
-    offset: Option<i64>,
-    before: Option<i64>,
-    after: Option<i64>,
-    sort_by: Option<MatchQuerySortBy>,
-    asc: Option<bool>,
-    filter: Option<MatchQueryFilter>
-) -> Result<Json<Records<MatchRecord>>, Status> {
+    auth: UserAuth,
+    query: RecordQuery
+) -> Result<Json<Records<MatchRecord>>, (Status, Json<ApiError>)> {
     // Get sort options (or use defaults)
-    let sort_by = sort_by.unwrap_or(MatchQuerySortBy::StartTime);
-    let asc = asc.unwrap_or_else(|| {
-        match sort_by {
-            MatchQuerySortBy::StartTime => false,
-            MatchQuerySortBy::Duration => true
-        }
-    });
+    let (sort_by, asc) = query.sort();
+    let (before, after) = validate_time_range(query.before, query.after).map_err(invalid_input)?;
+
+    let user_id = auth.require(TokenScope::ReadRecords).map_err(error_response)?.into_inner();
 
-    // Get offset if any
-    let offset = offset.unwrap_or(0);
+    // Get offset and cursor if any
+    let offset = query.offset();
+    let page = match_record_page(sort_by, asc, query.cursor.clone(), query.limit(), offset, query.count)
+        .map_err(error_response)?;
 
     db.run(move |c| {
         match_records::find_by_user(
             c,
-            &auth.into_inner(),
-            filter,
+            &user_id,
+            query.filter,
             sort_by,
             asc,
             before,
             after,
-            limit.unwrap_or(10),
-            offset
+            page
         )
     }).await
-        .map(|mut data| Json(
-            Records {
-                records: data.0.drain(..).map(|r| r.as_record()).collect(),
-                offset,
-                total_count: data.1
-            }
-        ))
-        .map_err(|err| {
-            eprintln!("{:?}", err);
-            Status::InternalServerError
-        }
-    )
+        .map(|data| match_record_response(data, offset))
+        .map_err(|err| error_response(server_error(err)))
+}
+
+/// Export User Records Route
+/// 
+/// Streams every record of the authenticated user (oldest first) as a file download
+/// 
+/// API tokens need the ReadRecords scope
+/// 
+/// Takes the same filters as the user's records route
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return the records as CSV (by default) or JSON Lines (see [ExportFormat])
+/// 
+/// On Timestamps Out Of Range:
+///  - Return Status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[get("/user/records/export?<format>&<before>&<after>&<filter>")]
+async fn user_records_export(
+    db: UserDbConn,
+    auth: UserAuth,
+    format: Option<ExportFormat>,
+    before: Option<i64>,
+    after: Option<i64>,
+    filter: Option<MatchQueryFilter>
+) -> Result<RecordExport, (Status, Json<ApiError>)> {
+    let (before, after) = validate_time_range(before, after).map_err(invalid_input)?;
+    let user_id = auth.require(TokenScope::ReadRecords).map_err(error_response)?.into_inner();
+
+    Ok(RecordExport::new(db, format.unwrap_or_default(), ExportOptions {
+        user_id: Some(user_id),
+        filter,
+        before,
+        after
+    }))
 }
 
 /// Add a record for a given user
 /// 
 /// Submits a record for the authenticated user using JSON request body data
 /// 
+/// API tokens need the SubmitRecords scope
+/// 
 /// On Success:
 ///  - Return Status 200
+///  - Return JSON serialized [UnlockedAchievement] list of the achievements the match unlocked
+/// 
+/// On Invalid Board Size (see [shared_types::validation])
+///  - Return status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
 #[post("/user/records/add", format = "json", data = "<record>",)]
-async fn user_record_add(db: UserDbConn, record: Json<ClientMatchData>, auth_token: UserAuthToken, cookies: &CookieJar<'_>) -> Status {
+async fn user_record_add(
+    db: UserDbConn,
+    record: Json<ClientMatchData>,
+    auth: UserAuth,
+    cookies: &CookieJar<'_>
+) -> Result<Json<Vec<UnlockedAchievement>>, (Status, Json<ApiError>)> {
     use diesel::result::Error::DatabaseError;
     use diesel::result::DatabaseErrorKind;
 
+    let auth_token = auth.require(TokenScope::SubmitRecords).map_err(error_response)?;
+
+    validate_match_data(&record).map_err(invalid_input)?;
+
     let match_record = MatchRecordModel::from((auth_token, record.into_inner()));
 
     match db.run(move |c| {
         match_records::add(c, match_record)
     }).await {
-        Ok(_) => Status::Ok,
+        Ok(unlocked) => Ok(Json(unlocked.into_iter().map(|model| model.as_unlocked()).collect())),
         Err(DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
             // We encountered a user that doesn't actually exist
-            cookies.remove_private(Cookie::named("user_auth_token"));
-            Status::Unauthorized
+            cookies.remove_private(Cookie::named(SESSION_COOKIE));
+            Err(error_response(Status::Unauthorized))
         },
-        Err(_) => {
+        Err(err) => Err(error_response(server_error(err)))
+    }
+}
+
+/// Add a batch of records for a given user
+/// 
+/// Submits many records for the authenticated user at once using JSON request body data,
+/// each with an idempotency key so records sent again (after a dropped response) are only added once
+/// 
+/// Invalid records are reported and skipped without stopping the rest of the batch
+/// 
+/// API tokens need the SubmitRecords scope
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [BatchRecordResult] list, in the same order as the batch
+/// 
+/// On Empty Batch or More Than [BATCH_MAX_RECORDS] Records
+///  - Return status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+/// 
+/// On Keys Being Added by Another Request at the Same Time
+///  - Return status 409
+#[post("/user/records/batch", format = "json", data = "<records>")]
+async fn user_record_batch(
+    db: UserDbConn,
+    records: Json<Vec<BatchMatchData>>,
+    auth: UserAuth,
+    cookies: &CookieJar<'_>
+) -> Result<Json<Vec<BatchRecordResult>>, (Status, Json<ApiError>)> {
+    use diesel::result::Error::DatabaseError;
+    use diesel::result::DatabaseErrorKind;
+
+    let user_id = auth.require(TokenScope::SubmitRecords).map_err(error_response)?.into_inner();
+
+    validate_batch_size(&records).map_err(invalid_input)?;
+
+    let entries: Vec<(BatchMatchData, Result<(), ValidationErrors>)> = records.into_inner()
+        .into_iter()
+        .map(|entry| {
+            let validation = validate_batch_match_data(&entry);
+            (entry, validation)
+        })
+        .collect();
+
+    let valid_records = entries.iter()
+        .filter(|(_, validation)| validation.is_ok())
+        .map(|(entry, _)| {
+            MatchRecordModel::from((UserAuthToken::from(user_id.clone()), entry.record))
+                .with_idempotency_key(entry.idempotency_key.clone())
+        })
+        .collect();
+
+    let mut added = match db.run(move |c| {
+        match_records::add_batch(c, valid_records)
+    }).await {
+        Ok(added) => added.into_iter(),
+        Err(DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
+            // We encountered a user that doesn't actually exist
+            cookies.remove_private(Cookie::named(SESSION_COOKIE));
+            return Err(error_response(Status::Unauthorized));
+        },
+        Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            // Another batch with the same keys was added at the same time, sending this one again reports them as duplicates
+            return Err(error_response(Status::Conflict));
+        },
+        Err(err) => return Err(error_response(server_error(err)))
+    };
+
+    // Match the results of the valid records back up with their place in the batch
+    let results = entries.into_iter()
+        .map(|(entry, validation)| {
+            let (status, unlocked, errors) = match validation {
+                Err(errors) => (BatchRecordStatus::Invalid, Vec::new(), Some(errors)),
+                Ok(()) => match added.next().flatten() {
+                    Some(unlocked) => (
+                        BatchRecordStatus::Added,
+                        unlocked.into_iter().map(|model| model.as_unlocked()).collect(),
+                        None
+                    ),
+                    None => (BatchRecordStatus::Duplicate, Vec::new(), None)
+                }
+            };
+
+            BatchRecordResult {
+                idempotency_key: entry.idempotency_key,
+                status,
+                unlocked,
+                errors
+            }
+        })
+        .collect();
+
+    Ok(Json(results))
+}
+
+/// List User Achievements Route
+/// 
+/// Gets the achievements the authenticated user has unlocked (see [shared_types::achievements])
+/// 
+/// API tokens need the ReadRecords scope
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [UnlockedAchievement] list (oldest first)
+#[get("/user/achievements")]
+async fn user_achievements(db: UserDbConn, auth: UserAuth) -> Result<Json<Vec<UnlockedAchievement>>, Status> {
+    let user_id = auth.require(TokenScope::ReadRecords)?.into_inner();
+
+    db.run(move |c| {
+        achievements::find_by_user(c, &user_id)
+    }).await
+        .map(|unlocked| Json(unlocked.into_iter().map(|model| model.as_unlocked()).collect()))
+        .map_err(server_error)
+}
+
+/// List User Ratings Route
+/// 
+/// Gets the rating of the authenticated user for each game they've played
+/// along with how it changed over their most recent matches (see [shared_types::rating])
+/// 
+/// API tokens need the ReadRecords scope
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [UserRating] list
+#[get("/user/ratings?<history>")]
+async fn user_ratings(
+    db: UserDbConn,
+    auth: UserAuth,
+    history: Option<i64>
+) -> Result<Json<Vec<UserRating>>, Status> {
+    let user_id = auth.require(TokenScope::ReadRecords)?.into_inner();
+
+    // Number of points of history to return for each game
+    let history_limit = history.unwrap_or(50).clamp(0, 500);
+
+    db.run(move |c| {
+        ratings::find_by_user(c, &user_id)?.drain(..).map(|rating| {
+            let history = ratings::history(c, &user_id, rating.game_id, history_limit)?;
+            Ok(UserRating {
+                game_id: rating.game_id,
+                rating: rating.rating,
+                matches: rating.matches,
+                history: history.into_iter().map(|point| point.as_point()).collect()
+            })
+        }).collect::<Result<Vec<_>, diesel::result::Error>>()
+    }).await
+        .map(Json)
+        .map_err(|err| {
+            error!("{:?}", err);
+            Status::InternalServerError
+        })
+}
+
+/// List API Tokens Route
+/// 
+/// Lists the personal API tokens of the logged in user (the tokens themselves aren't included)
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [ApiTokenInfo] list
+#[get("/user/tokens")]
+async fn user_tokens(db: UserDbConn, session: UserSession) -> Result<Json<Vec<ApiTokenInfo>>, Status> {
+    db.run(move |c| api_tokens::find_by_user(c, &session.user_id)).await
+        .map(|tokens| Json(tokens.into_iter().map(|t| t.as_info()).collect()))
+        .map_err(|err| {
+            error!("{:?}", err);
+            Status::InternalServerError
+        })
+}
+
+/// Create API Token Route
+/// 
+/// Creates a personal API token for the logged in user, API tokens can't create other tokens
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [NewApiToken], the only time the token is given
+/// 
+/// On Invalid Name or No Scopes
+///  - Return status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[post("/user/tokens", data = "<form>")]
+async fn user_token_add(
+    db: UserDbConn,
+    session: UserSession,
+    form: Form<ApiTokenForm>
+) -> Result<Json<NewApiToken>, (Status, Json<ApiError>)> {
+    use diesel::result::Error::DatabaseError;
+    use diesel::result::DatabaseErrorKind;
+
+    let form = form.into_inner();
+    let scopes = form.scopes();
+    let name = String::from(form.name.trim());
+
+    validate_new_token(&name, &scopes)
+        .map_err(invalid_input)?;
+
+    let (model, token) = ApiTokenModel::generate_new(session.user_id, name, &scopes);
+
+    match db.run(move |c| api_tokens::add(c, &model)).await {
+        Ok(model) => Ok(Json(NewApiToken { token, info: model.as_info() })),
+        Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            let mut errors = ValidationErrors::default();
+            errors.check(TOKEN_NAME_FIELD, Err(ValidationError::Taken));
+
+            Err(invalid_input(errors))
+        },
+        Err(err) => Err(error_response(server_error(err)))
+    }
+}
+
+/// Revoke API Token Route
+/// 
+/// Revokes a personal API token of the logged in user
+/// 
+/// On Success:
+///  - Return Status 200
+/// 
+/// On Unknown Token:
+///  - Return Status 404
+#[delete("/user/tokens/<id>")]
+async fn user_token_delete(db: UserDbConn, session: UserSession, id: i32) -> Status {
+    match db.run(move |c| api_tokens::delete(c, &session.user_id, id)).await {
+        Ok(0) => Status::NotFound,
+        Ok(_) => Status::Ok,
+        Err(err) => {
+            error!("{:?}", err);
             Status::InternalServerError
         }
     }
@@ -240,54 +777,393 @@ async fn user_record_add(db: UserDbConn, record: Json<ClientMatchData>, auth_tok
 /// On Success:
 ///  - Return Status 200
 ///  - Return JSON serialized [MatchRecord] list
-#[get("/games/records?<limit>&<offset>&<before>&<after>&<sort_by>&<asc>&<filter>")]
+/// 
+/// On Timestamps Out Of Range:
+///  - Return Status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[get("/games/records?<query..>")]
 async fn game_records(
     db: UserDbConn,
-    limit: Option<i64>,
-    offset: Option<i64>,
-    before: Option<i64>,
-    after: Option<i64>,
-    sort_by: Option<MatchQuerySortBy>,
-    asc: Option<bool>,
-    filter: Option<MatchQueryFilter>
-) -> Result<Json<Records<MatchRecord>>, Status> {
+    query: RecordQuery
+) -> Result<Json<Records<MatchRecord>>, (Status, Json<ApiError>)> {
 
     // Get sort options (or use defaults)
-    let sort_by = sort_by.unwrap_or(MatchQuerySortBy::StartTime);
-    let asc = asc.unwrap_or_else(|| {
-        match sort_by {
-            MatchQuerySortBy::StartTime => false,
-            MatchQuerySortBy::Duration => true
-        }
-    });
+    let (sort_by, asc) = query.sort();
+    let (before, after) = validate_time_range(query.before, query.after).map_err(invalid_input)?;
 
-    // Get offset if any
-    let offset = offset.unwrap_or(0);
+    // Get offset and cursor if any
+    let offset = query.offset();
+    let page = match_record_page(sort_by, asc, query.cursor.clone(), query.limit(), offset, query.count)
+        .map_err(error_response)?;
 
     db.run(move |c| {
         match_records::find_all_users(
             c,
-            filter,
+            query.filter,
             sort_by,
             asc,
             before,
             after,
-            limit.unwrap_or(10),
-            offset
+            page
         )
     }).await
-        .map(|mut data| Json(
-            Records {
-                records: data.0.drain(..).map(|r| r.as_record()).collect(),
-                offset,
-                total_count: data.1
+        .map(|data| match_record_response(data, offset))
+        .map_err(|err| error_response(server_error(err)))
+}
+
+/// Game Stats Route
+/// 
+/// Counts the wins, losses and ties of every match and their average number of moves,
+/// grouped by the day, week or month (UTC) they finished in (see [game_stats::buckets])
+/// 
+/// Optionally only counts matches of a game, against a CPU level or before and after
+/// timestamps (in seconds, like the records routes)
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [StatsBucket] list (oldest first)
+/// 
+/// On Timestamps Out Of Range:
+///  - Return Status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[get("/games/stats?<group_by>&<game>&<level>&<before>&<after>")]
+async fn game_stats_buckets(
+    db: UserDbConn,
+    group_by: Option<StatsGroupBy>,
+    game: Option<GameType>,
+    level: Option<CpuLevel>,
+    before: Option<i64>,
+    after: Option<i64>
+) -> Result<Json<Vec<StatsBucket>>, (Status, Json<ApiError>)> {
+    let group_by = group_by.unwrap_or_default();
+    let (before, after) = validate_time_range(before, after).map_err(invalid_input)?;
+
+    db.run(move |c| game_stats::buckets(c, group_by, game, level, before, after)).await
+        .map(|rows| Json(rows.into_iter().map(|row| row.as_bucket()).collect()))
+        .map_err(|err| error_response(server_error(err)))
+}
+
+/// Leaderboard Route
+/// 
+/// Ranks every user by their matches of a game (see [leaderboard::find] for the order)
+/// 
+/// Optionally only counts matches against a CPU level, on a board size or within a period
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [Leaderboard], including the logged in user's own entry
+#[get("/leaderboard?<query..>")]
+async fn game_leaderboard(
+    db: UserDbConn,
+    query: LeaderboardQuery,
+    session: Option<UserSession>
+) -> Result<Json<Leaderboard>, Status> {
+    let offset = query.offset();
+    let since = query.since(Utc::now());
+
+    db.run(move |c| {
+        leaderboard::find(c, &query, since, session.as_ref().map(|session| session.user_id.as_str()))
+    }).await
+        .map(|(page, total_count, own_row)| Json(
+            Leaderboard {
+                ranking: Records {
+                    records: page.into_iter().map(|row| row.as_entry()).collect(),
+                    offset,
+                    total_count: Some(total_count),
+                    next_cursor: None
+                },
+                own_entry: own_row.map(|row| row.as_entry())
             }
         ))
         .map_err(|err| {
-            eprintln!("{:?}", err);
+            error!("{:?}", err);
             Status::InternalServerError
-        }
-    )
+        })
+}
+
+/// User Stats Route
+/// 
+/// Summarises the match history of a user: their results against each CPU level,
+/// streaks and average moves to win for each game, and how many matches they played
+/// on each of the past [STATS_ACTIVITY_DAYS] days (including today)
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [UserStats]
+/// 
+/// On Unknown User:
+///  - Return Status 404
+#[get("/users/<id>/stats")]
+async fn user_profile_stats(db: UserDbConn, id: String) -> Result<Json<UserStats>, Status> {
+    let today = Utc::now().naive_utc().date();
+    let since = today - chrono::Duration::days(STATS_ACTIVITY_DAYS - 1);
+
+    db.run(move |c| {
+        let user = users::find_by_id(c, &id).map_err(server_error)?.ok_or(Status::NotFound)?;
+
+        let level_rows = user_stats::level_totals(c, &user.user_id).map_err(server_error)?;
+
+        // Rows are ordered by game so this leaves each game played once
+        let mut games_played = level_rows.iter().map(|row| row.game_id()).collect::<Vec<_>>();
+        games_played.dedup();
+
+        let games = games_played.into_iter()
+            .map(|game_id| {
+                let rows = level_rows.iter().filter(|row| row.game_id() == game_id).collect::<Vec<_>>();
+                let results = user_stats::results(c, &user.user_id, game_id)?;
+                let wins = rows.iter().map(|row| row.wins()).sum::<i64>();
+
+                Ok(GameStats {
+                    game_id,
+                    levels: rows.iter().map(|row| row.as_level_stats()).collect(),
+                    current_streak: Streak::current(&results),
+                    longest_win_streak: Streak::longest(&results, MatchResult::Win),
+                    average_moves_to_win: (wins > 0)
+                        .then(|| rows.iter().map(|row| row.win_moves()).sum::<i64>() as f64 / wins as f64)
+                })
+            })
+            .collect::<Result<Vec<_>, diesel::result::Error>>()
+            .map_err(server_error)?;
+
+        let mut activity = user_stats::activity(c, &user.user_id, since.and_hms(0, 0, 0))
+            .map_err(server_error)?
+            .into_iter()
+            .map(|row| row.as_activity())
+            .peekable();
+
+        // Fill in the days without matches
+        let activity = since.iter_days()
+            .take_while(|&day| day <= today)
+            .map(|day| match activity.next_if(|played| played.day == day) {
+                Some(played) => played,
+                None => DailyActivity { day, matches: 0 }
+            })
+            .collect();
+
+        Ok(UserStats {
+            user_id: user.user_id,
+            games,
+            activity
+        })
+    }).await.map(Json)
+}
+
+/// List Records of a User Route
+/// 
+/// Query the records of any user (for their profile), takes the same query args as the user's own records route
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [MatchRecord] list
+/// 
+/// On Unknown User:
+///  - Return Status 404
+/// 
+/// On Timestamps Out Of Range:
+///  - Return Status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+#[get("/users/<id>/records?<query..>")]
+async fn user_profile_records(
+    db: UserDbConn,
+    id: String,
+    query: RecordQuery
+) -> Result<Json<Records<MatchRecord>>, (Status, Json<ApiError>)> {
+    // Get sort options (or use defaults)
+    let (sort_by, asc) = query.sort();
+    let (before, after) = validate_time_range(query.before, query.after).map_err(invalid_input)?;
+
+    // Get offset and cursor if any
+    let offset = query.offset();
+    let page = match_record_page(sort_by, asc, query.cursor.clone(), query.limit(), offset, query.count)
+        .map_err(error_response)?;
+
+    db.run(move |c| {
+        let user = users::find_by_id(c, &id).map_err(server_error)?.ok_or(Status::NotFound)?;
+
+        match_records::find_by_user(
+            c,
+            &user.user_id,
+            query.filter,
+            sort_by,
+            asc,
+            before,
+            after,
+            page
+        ).map_err(server_error)
+    }).await
+        .map(|data| match_record_response(data, offset))
+        .map_err(error_response)
+}
+
+/// Daily Puzzle Route
+/// 
+/// Gets the puzzle of the day (UTC, see [Puzzle::for_day])
+/// 
+/// Only reads, the user's clock is started separately (see [puzzle_start])
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [DailyPuzzle], including the logged in user's progress
+///    (or that of an API token with the ReadRecords scope) once they've started
+#[get("/puzzles/daily")]
+async fn puzzle_daily(db: UserDbConn, auth: Option<UserAuth>) -> Result<Json<DailyPuzzle>, Status> {
+    let day = Utc::now().naive_utc().date();
+    let puzzle = Puzzle::for_day(day);
+
+    let user_id = auth.and_then(|auth| auth.require(TokenScope::ReadRecords).ok());
+    let progress = match user_id {
+        Some(user_id) => db.run(move |c| {
+            puzzles::find(c, &user_id.into_inner(), day)
+        }).await.map_err(server_error)?.map(|progress| progress.as_progress()),
+        None => None
+    };
+
+    Ok(Json(DailyPuzzle {
+        day,
+        game_id: puzzle.game_type,
+        columns: puzzle.columns as i32,
+        rows: puzzle.rows as i32,
+        win_in: puzzle.win_in,
+        setup: puzzle.setup.iter().map(|setup_move| setup_move.to_string()).collect(),
+        progress
+    }))
+}
+
+/// Daily Puzzle Start Route
+/// 
+/// Starts timing the authenticated user on the puzzle of the day for its leaderboard
+/// 
+/// Starting again doesn't restart the clock, so it can be repeated safely
+/// 
+/// API tokens need the SubmitRecords scope
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [PuzzleProgress] of the user
+#[post("/puzzles/daily/start")]
+async fn puzzle_start(db: UserDbConn, auth: UserAuth) -> Result<Json<PuzzleProgress>, Status> {
+    let user_id = auth.require(TokenScope::SubmitRecords)?.into_inner();
+    let now = Utc::now().naive_utc();
+
+    db.run(move |c| {
+        puzzles::start(c, &user_id, now.date(), now)
+    }).await
+        .map(|progress| Json(progress.as_progress()))
+        .map_err(server_error)
+}
+
+/// Daily Puzzle Attempt Route
+/// 
+/// Checks a solution to the puzzle of the day for the authenticated user using JSON
+/// request body data, playing the opponent's replies (see [Puzzle::check_solution])
+/// 
+/// The user must have started the puzzle first (see [puzzle_start]) since that's when
+/// their solve time starts, and solutions submitted after solving the puzzle don't count
+/// 
+/// Checking a solution searches the opponent's replies, so it runs off the async workers
+/// and each user can only submit a few solutions a minute (see [PuzzleLimiter])
+/// 
+/// API tokens need the SubmitRecords scope
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [PuzzleProgress] of the user
+/// 
+/// On Moves That Can't Be Played
+///  - Return status 422
+///  - Return JSON serialized [ApiError] with the errors of each field
+/// 
+/// On Solution For Another Day's Puzzle Or A Puzzle The User Never Started
+///  - Return status 409
+/// 
+/// On Too Many Solutions Submitted
+///  - Return status 429
+#[post("/puzzles/daily/attempts", format = "json", data = "<attempt>")]
+async fn puzzle_attempt(
+    db: UserDbConn,
+    limiter: &State<PuzzleLimiter>,
+    attempt: Json<PuzzleAttempt>,
+    auth: UserAuth
+) -> Result<Json<PuzzleProgress>, (Status, Json<ApiError>)> {
+    let user_id = auth.require(TokenScope::SubmitRecords).map_err(error_response)?.into_inner();
+    let now = Utc::now().naive_utc();
+
+    // The puzzle changed since the user opened it
+    if attempt.day != now.date() {
+        return Err(error_response(Status::Conflict));
+    }
+
+    if limiter.record_attempt(&user_id, now).is_some() {
+        return Err(error_response(Status::TooManyRequests));
+    }
+
+    let PuzzleAttempt { day, moves } = attempt.into_inner();
+    let solved = rocket::tokio::task::spawn_blocking(move || {
+        moves.iter()
+            .map(|played| PossibleMove::parse(played))
+            .collect::<Option<Vec<_>>>()
+            .and_then(|moves| Puzzle::for_day(day).check_solution(&moves).ok())
+    }).await
+        .map_err(|err| {
+            error!("Checking a puzzle solution failed: {}", err);
+            error_response(Status::InternalServerError)
+        })?
+        .ok_or_else(|| {
+            let mut errors = ValidationErrors::default();
+            errors.check(PUZZLE_MOVES_FIELD, Err(ValidationError::IllegalMoves));
+            invalid_input(errors)
+        })?;
+
+    db.run(move |c| {
+        puzzles::record_attempt(c, &user_id, day, solved, now)
+    }).await
+        .map(|progress| Json(progress.as_progress()))
+        .map_err(|err| match err {
+            diesel::result::Error::NotFound => (
+                Status::Conflict,
+                Json(ApiError::new(409, "Start the puzzle with POST /puzzles/daily/start before submitting a solution"))
+            ),
+            err => error_response(server_error(err))
+        })
+}
+
+/// Daily Puzzle Leaderboard Route
+/// 
+/// Ranks the users that solved the puzzle of a day (today if not given, as `YYYY-MM-DD`)
+/// by solve time then attempts (see [puzzles::leaderboard])
+/// 
+/// On Success:
+///  - Return Status 200
+///  - Return JSON serialized [PuzzleLeaderboardEntry] list
+/// 
+/// On Invalid Day:
+///  - Return Status 400
+#[get("/puzzles/daily/leaderboard?<day>&<limit>&<offset>")]
+async fn puzzle_leaderboard(
+    db: UserDbConn,
+    day: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>
+) -> Result<Json<Records<PuzzleLeaderboardEntry>>, Status> {
+    let day = match day {
+        Some(day) => NaiveDate::parse_from_str(&day, "%Y-%m-%d").map_err(|_| Status::BadRequest)?,
+        None => Utc::now().naive_utc().date()
+    };
+    let limit = limit.unwrap_or(10).clamp(1, 100);
+    let offset = offset.unwrap_or(0).max(0);
+
+    db.run(move |c| {
+        puzzles::leaderboard(c, day, limit, offset)
+    }).await
+        .map(|(rows, total_count)| Json(
+            Records {
+                records: rows.into_iter().map(|row| row.as_entry()).collect(),
+                offset,
+                total_count: Some(total_count),
+                next_cursor: None
+            }
+        ))
+        .map_err(server_error)
 }
 
 /// Returns all the routes to serve
@@ -296,9 +1172,27 @@ pub fn get_routes() -> Vec<rocket::Route> {
         user_login,
         user_verify,
         user_logout,
+        user_sessions_revoke,
         user_register,
+        user_password,
+        user_delete,
         user_records,
+        user_records_export,
         user_record_add,
+        user_record_batch,
+        user_achievements,
+        user_ratings,
+        user_tokens,
+        user_token_add,
+        user_token_delete,
         game_records,
+        game_stats_buckets,
+        game_leaderboard,
+        user_profile_stats,
+        user_profile_records,
+        puzzle_daily,
+        puzzle_start,
+        puzzle_attempt,
+        puzzle_leaderboard,
     ]
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+//! Achievements users unlock by playing
+//!
+//! Each achievement is declared in [ACHIEVEMENTS] with a condition on the user's [Progress]
+//! that the server checks after every recorded match.  Once unlocked an achievement is kept
+//! even if the records that unlocked it are deleted.
+
+use crate::types::{Achievement, CpuLevel, GameType, MatchResult};
+use crate::validation::min_win_moves;
+
+/// Number of matches of a game that need to be won in a row for [Achievement::WinStreak]
+pub const WIN_STREAK_LENGTH: i64 = 10;
+
+/// Most moves a match of the game can be won in for [Achievement::PerfectWin]
+/// 
+/// The fewest a win can take, which is lower in TOOT and OTTO since the opponent's
+/// letters count towards TOOT
+pub fn perfect_win_moves(game: GameType) -> i32 {
+    min_win_moves(game) as i32
+}
+
+/// Progress of a user right after a match
+#[derive(Debug, Clone)]
+pub struct Progress {
+    pub game_id: GameType,
+    pub cpu_level: CpuLevel,
+    pub result: MatchResult,
+    /// Number of moves the user made in the match
+    pub moves: i32,
+    /// Matches of the game the user has won in a row (including this one)
+    pub win_streak: i64,
+    /// Games and CPU levels the user has ever beaten (including this match)
+    pub levels_beaten: Vec<(GameType, CpuLevel)>
+}
+
+/// Description of an achievement and how it is unlocked
+pub struct AchievementInfo {
+    pub achievement: Achievement,
+    pub name: &'static str,
+    pub description: &'static str,
+    unlocked: fn(&Progress) -> bool
+}
+
+impl AchievementInfo {
+
+    /// Whether the progress unlocks the achievement
+    pub fn is_unlocked(&self, progress: &Progress) -> bool {
+        (self.unlocked)(progress)
+    }
+
+}
+
+/// Every achievement that can be unlocked
+pub static ACHIEVEMENTS: [AchievementInfo; 4] = [
+    AchievementInfo {
+        achievement: Achievement::FirstHardWin,
+        name: "Giant Slayer",
+        description: "Beat the Hard CPU",
+        unlocked: |progress| progress.result == MatchResult::Win && progress.cpu_level == CpuLevel::Hard
+    },
+    AchievementInfo {
+        achievement: Achievement::WinStreak,
+        name: "Unstoppable",
+        description: "Win 10 matches of a game in a row",
+        unlocked: |progress| progress.win_streak >= WIN_STREAK_LENGTH
+    },
+    AchievementInfo {
+        achievement: Achievement::PerfectWin,
+        name: "Flawless",
+        description: "Win a match in the fewest moves possible (4 in Connect 4, 2 in TOOT and OTTO)",
+        unlocked: |progress| progress.result == MatchResult::Win && (1..=perfect_win_moves(progress.game_id)).contains(&progress.moves)
+    },
+    AchievementInfo {
+        achievement: Achievement::AllRounder,
+        name: "All-Rounder",
+        description: "Beat every difficulty in both Connect 4 and TOOT OTTO",
+        unlocked: |progress| {
+            [GameType::Connect4, GameType::OttoToot].iter().all(|&game| {
+                [CpuLevel::Easy, CpuLevel::Medium, CpuLevel::Hard].iter()
+                    .all(|&level| progress.levels_beaten.contains(&(game, level)))
+            })
+        }
+    },
+];
+
+/// Get the description of an achievement
+pub fn info(achievement: Achievement) -> &'static AchievementInfo {
+    ACHIEVEMENTS.iter()
+        .find(|info| info.achievement == achievement)
+        .expect("every achievement is declared in ACHIEVEMENTS")
+}
+
+/// Achievements unlocked by the progress
+pub fn unlocked_by(progress: &Progress) -> impl Iterator<Item = Achievement> + '_ {
+    ACHIEVEMENTS.iter()
+        .filter(move |info| info.is_unlocked(progress))
+        .map(|info| info.achievement)
+}
\ No newline at end of file
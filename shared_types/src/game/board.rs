@@ -0,0 +1,1112 @@
+use std::fmt;
+use std::sync::Arc;
+use std::usize;
+
+use rand::prelude::*;
+use serde::Serialize;
+
+use crate::game::{AI_ID, GameType, PLAYER_ID};
+
+use super::GameState;
+use super::book::{BookMove, OpeningBook};
+use super::slot::*;
+
+/// Struct for a possible move.
+/// Contains the column and a letter (for TOOT and OTTO)
+///
+/// Written as `<column>[T|O]` where columns start at 0 and the
+/// letter is only present for TOOT and OTTO (ex: `3` or `3T`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PossibleMove {
+    pub column: usize,
+    pub letter: Option<Letter>,
+}
+
+impl PossibleMove {
+    /// Parse a move from its `<column>[T|O]` notation
+    pub fn parse(text: &str) -> Option<Self> {
+        let (column, letter) = match text.chars().last()? {
+            'T' => (&text[..text.len() - 1], Some(Letter::T)),
+            'O' => (&text[..text.len() - 1], Some(Letter::O)),
+            _ => (text, None)
+        };
+
+        Some(PossibleMove {
+            column: column.parse().ok()?,
+            letter,
+        })
+    }
+}
+
+impl fmt::Display for PossibleMove {
+    /// Writes the move in its `<column>[T|O]` notation
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = match self.letter {
+            Some(Letter::T) => "T",
+            Some(Letter::O) => "O",
+            None => ""
+        };
+
+        write!(f, "{}{}", self.column, letter)
+    }
+}
+
+/// Struct for a move that has been played.
+/// Contains the row and column the piece ends up being played at.
+/// Used to keep track of move history.
+#[derive(Clone, Copy, Debug)]
+pub struct Move {
+    pub row: usize,
+    pub column: usize,
+}
+
+/// Enum for result of checking if a piece can be placed at a column.
+/// Can be either a valid column to place a piece in, full, or
+/// the column may not exist for some reason.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnSelectionResult {
+    Valid,
+    ColumnFull,
+    ColumnDoesNotExist,
+}
+
+/// Configuration for the AI.
+/// search_depth is used for the maximum depth the alpha-beta pruning
+/// algorithm searches. random_iterations is the number of random moves
+/// another algorithm uses while checking for score. book_variety is how
+/// far (as a percentage of the best weight) the AI strays from the best
+/// opening book move, 0 always plays the best move.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct AIConfiguration {
+    search_depth: u32,
+    random_iterations: u32,
+    book_variety: u32,
+}
+
+impl AIConfiguration {
+    pub const fn new(search_depth: u32, random_iterations: u32, book_variety: u32) -> Self {
+        Self {
+            search_depth,
+            random_iterations,
+            book_variety,
+        }
+    }
+}
+
+/// Easy AI config
+pub const AI_EASY: AIConfiguration = AIConfiguration {
+    search_depth: 2,
+    random_iterations: 2,
+    book_variety: 100,
+};
+
+/// Medium AI config
+pub const AI_MEDIUM: AIConfiguration = AIConfiguration {
+    search_depth: 2,
+    random_iterations: 250,
+    book_variety: 50,
+};
+
+/// Hard AI config
+pub const AI_HARD: AIConfiguration = AIConfiguration {
+    search_depth: 3,
+    random_iterations: 500,
+    book_variety: 0,
+};
+
+/// Base score for a won TOOT and OTTO position.
+/// Kept well above any value the positional evaluator can return.
+const TOOT_AND_OTTO_WIN_SCORE: i32 = 1 << 20;
+
+/// Score of a TOOT and OTTO window indexed by how many of its letters are already in place
+const TOOT_AND_OTTO_WINDOW_SCORES: [i32; 4] = [0, 1, 8, 64];
+
+/// Directions (row step, column step) a word can be spelled in
+const WINDOW_DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+/// Number of T's and O's each player gets in official TOOT and OTTO
+pub const OFFICIAL_LETTER_COUNT: u32 = 6;
+
+/// Letters each player has left when playing TOOT and OTTO with a limited inventory.
+/// Indexed by [player index][letter index] (see [player_index] and [letter_index]).
+type LetterInventory = [[u32; 2]; 2];
+
+/// FNV-1a parameters used for hashing positions
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Add a byte to an FNV-1a hash
+fn fnv_write(hash: &mut u64, byte: u8) {
+    *hash ^= byte as u64;
+    *hash = hash.wrapping_mul(FNV_PRIME);
+}
+
+/// Index of a player in a [LetterInventory]
+fn player_index(player: u32) -> usize {
+    if player == PLAYER_ID { 0 } else { 1 }
+}
+
+/// Index of a letter in a [LetterInventory]
+fn letter_index(letter: Letter) -> usize {
+    match letter {
+        Letter::T => 0,
+        Letter::O => 1,
+    }
+}
+
+/// Board definition.
+/// Contains:
+/// rows: # of rows
+/// columns: # of columns
+/// game_type: connect4 or TOOT and OTTO
+/// storage: stores the board state
+/// player_turn: whether it is the player's turn or not
+/// heights: essentially used to quickly determine the number of pieces in a column
+/// moves: number of moves played so far
+/// move_history: stack of past moves
+/// column_order: order in which the AI attempts tries moves
+/// ai: AI configuration
+/// letters: letters left for each player (None if letters are unlimited)
+/// book: opening book the AI consults before searching (None to always search)
+#[derive(Debug)]
+pub struct Board {
+    pub rows: usize,
+    pub columns: usize,
+    pub game_type: GameType,
+    pub storage: Vec<Slot>,
+    player_turn: bool,
+    heights: Vec<usize>,
+    moves: u32,
+    move_history: Vec<Move>,
+    column_order: Vec<usize>,
+    ai: AIConfiguration,
+    letters: Option<LetterInventory>,
+    book: Option<Arc<OpeningBook>>,
+}
+
+impl Board {
+    /// Create an empty board.
+    /// letter_limit is the number of each letter a player gets in TOOT and OTTO,
+    /// None gives both players an unlimited supply.
+    /// first_player is the ID of the player that makes the opening move.
+    pub fn new(
+        rows: usize,
+        columns: usize,
+        game_type: GameType,
+        ai: AIConfiguration,
+        letter_limit: Option<u32>,
+        first_player: u32
+    ) -> Self {
+        // Create board storage
+        let mut storage = Vec::new();
+
+        for _ in 0..(rows * columns) {
+            storage.push(Slot::new());
+        }
+
+        // Create height storage
+        let mut heights = Vec::new();
+
+        for _ in 0..columns {
+            heights.push(0);
+        }
+
+        // Create column order (optimizes AI search)
+        let mut column_order = Vec::new();
+
+        for column in 0..columns as i32 {
+            let order = columns as i32 / 2 + (1 - 2 * (column % 2)) * (column + 1) / 2;
+            column_order.push(order as usize);
+        }
+
+        Self {
+            rows,
+            columns,
+            player_turn: first_player == PLAYER_ID,
+            storage,
+            heights,
+            moves: 0,
+            move_history: Vec::new(),
+            column_order,
+            ai,
+            game_type,
+            letters: letter_limit
+                .filter(|_| game_type == GameType::OttoToot)
+                .map(|count| [[count; 2]; 2]),
+            book: Some(OpeningBook::embedded()),
+        }
+    }
+
+    /// Set the opening book the AI consults (None to always search)
+    pub fn set_opening_book(&mut self, book: Option<Arc<OpeningBook>>) {
+        self.book = book;
+    }
+
+    /// Hash of the position from the view of the player to move.
+    /// Pieces are stored as belonging to the player to move or their opponent
+    /// (Connect 4) or by letter along with the word the player to move is
+    /// spelling (TOOT and OTTO), so one book entry serves either side.
+    pub fn position_hash(&self) -> u64 {
+        let player = if self.player_turn { PLAYER_ID } else { AI_ID };
+        let mut hash = FNV_OFFSET_BASIS;
+
+        fnv_write(&mut hash, self.game_type as u8);
+        fnv_write(&mut hash, self.rows as u8);
+        fnv_write(&mut hash, self.columns as u8);
+
+        if self.game_type == GameType::OttoToot {
+            fnv_write(&mut hash, player as u8);
+        }
+
+        for slot in self.storage.iter() {
+            let value = if !slot.get_occupied() {
+                0
+            } else if self.game_type == GameType::OttoToot {
+                match slot.get_letter() {
+                    Letter::T => 1,
+                    Letter::O => 2,
+                }
+            } else if slot.owned_by(player) {
+                1
+            } else {
+                2
+            };
+
+            fnv_write(&mut hash, value);
+        }
+
+        hash
+    }
+
+    /// Pick a move from the opening book for the current position.
+    /// Moves are picked at random by weight from the ones within the
+    /// configured book variety of the best move.
+    fn get_book_move(&self) -> Option<PossibleMove> {
+        let book = self.book.as_ref()?;
+        let possible_moves = self.get_possible_moves();
+
+        // Ignore book moves that can't be played (ex: letter has run out)
+        let candidates: Vec<BookMove> = book.get(self.position_hash())?
+            .iter()
+            .filter(|book_move| possible_moves.iter().any(
+                |possible_move| possible_move.column == book_move.column && possible_move.letter == book_move.letter
+            ))
+            .cloned()
+            .collect();
+
+        let best = candidates.iter().map(|book_move| book_move.weight).max()?;
+        let cutoff = best * (100 - self.ai.book_variety.min(100)) / 100;
+
+        let candidates: Vec<BookMove> = candidates.into_iter()
+            .filter(|book_move| book_move.weight > 0 && book_move.weight >= cutoff)
+            .collect();
+
+        let total: u32 = candidates.iter().map(|book_move| book_move.weight).sum();
+
+        if total == 0 {
+            return None;
+        }
+
+        let mut pick = random::<u32>() % total;
+
+        for book_move in candidates {
+            if pick < book_move.weight {
+                return Some(PossibleMove {
+                    column: book_move.column,
+                    letter: book_move.letter,
+                });
+            }
+
+            pick -= book_move.weight;
+        }
+
+        None
+    }
+
+    /// Set the AI configuration used to pick moves
+    pub fn set_ai_configuration(&mut self, ai: AIConfiguration) {
+        self.ai = ai;
+    }
+
+    /// Returns the number of the given letter the player has left.
+    /// Returns None if letters are unlimited.
+    pub fn get_letters_remaining(&self, player: u32, letter: Letter) -> Option<u32> {
+        self.letters.map(|letters| letters[player_index(player)][letter_index(letter)])
+    }
+
+    /// Return true if the player still has the given letter to place
+    pub fn has_letter(&self, player: u32, letter: Letter) -> bool {
+        matches!(self.get_letters_remaining(player, letter), None | Some(1..))
+    }
+
+    /// Returns the number of moves
+    pub fn get_moves(&self) -> u32 {
+        self.moves
+    }
+
+    /// Returns true if the player is the one to move next
+    pub fn is_player_turn(&self) -> bool {
+        self.player_turn
+    }
+
+    /// Returns the slot at the given row and column.
+    pub fn get_slot(&self, row: usize, column: usize) -> &Slot {
+        match self.storage.get(row * self.columns + column) {
+            None => {
+                panic!("Slot does not exist at {}, {}", row, column);
+            }
+            Some(slot) => {
+                slot
+            }
+        }
+    }
+
+    /// Returns the slot (mut) at the given row and column.
+    pub fn get_slot_mut(&mut self, row: usize, column: usize) -> &mut Slot {
+        match self.storage.get_mut(row * self.columns + column) {
+            None => {
+                panic!("Slot does not exist at {}, {}", row, column);
+            }
+            Some(slot) => {
+                slot
+            }
+        }
+    }
+
+    /// Check if a given column is valid to place a piece into.
+    pub fn check_column_selection(&self, column: isize) -> ColumnSelectionResult {
+        if column < 0 || column as usize >= self.columns {
+            return ColumnSelectionResult::ColumnDoesNotExist;
+        }
+
+        if self.heights[column as usize] >= self.rows {
+            ColumnSelectionResult::ColumnFull
+        } else {
+            ColumnSelectionResult::Valid
+        }
+    }
+
+    /// Return a vector of possible moves
+    pub fn get_possible_moves(&self) -> Vec<PossibleMove> {
+        let mut possible_moves = Vec::new();
+
+        if self.game_type == GameType::Connect4 {
+            for i in 0..self.columns {
+                let column = self.column_order[i];
+
+                if self.check_column_selection(column as isize) == ColumnSelectionResult::Valid {
+                    possible_moves.push(PossibleMove {
+                        column,
+                        letter: None,
+                    });
+                }
+            }
+        } else if self.game_type == GameType::OttoToot {
+            // Only offer the letters the current player has left
+            let player = if self.player_turn { PLAYER_ID } else { AI_ID };
+            let has_o = self.has_letter(player, Letter::O);
+            let has_t = self.has_letter(player, Letter::T);
+
+            for i in 0..self.columns {
+                let column = self.column_order[i];
+
+                if self.check_column_selection(column as isize) == ColumnSelectionResult::Valid {
+                    if has_o {
+                        possible_moves.push(PossibleMove {
+                            column,
+                            letter: Some(Letter::O),
+                        });
+                    }
+                    if has_t {
+                        possible_moves.push(PossibleMove {
+                            column,
+                            letter: Some(Letter::T),
+                        })
+                    }
+                }
+            }
+        }
+
+        possible_moves
+    }
+
+    /// Place a piece at a given column.
+    /// Assumes the given column is valid to place into.
+    pub fn place_at_column(&mut self, possible_move: PossibleMove, player: u32) {
+        let column = possible_move.column;
+        let letter = possible_move.letter;
+
+        // Get index to place into
+        let lowest_row = self.rows - self.heights[column] - 1;
+
+        // Occupy the slot
+        self.get_slot_mut(lowest_row, column).place(player, letter);
+
+        // Take the letter out of the player's inventory
+        if let (Some(letters), Some(letter)) = (self.letters.as_mut(), letter) {
+            letters[player_index(player)][letter_index(letter)] -= 1;
+        }
+
+        self.heights[column] += 1;
+        self.moves += 1;
+        self.move_history.push(Move {
+            row: lowest_row,
+            column,
+        });
+
+        // Switch turns
+        self.player_turn = !self.player_turn;
+    }
+
+    /// Undo the last move. Used by the AI to scout winning moves.
+    pub fn undo_move(&mut self) {
+        match self.move_history.pop() {
+            None => {
+                panic!("Error undoing last move: no more previous moves");
+            }
+            Some(last_move) => {
+                match self.storage.get_mut(last_move.row * self.columns + last_move.column) {
+                    None => {
+                        panic!("Error undoing last move: slot not found");
+                    }
+                    Some(lowest_slot) => {
+                        // Clear the slot
+                        if lowest_slot.get_occupied() {
+                            // Return the letter to the player's inventory
+                            if let Some(letters) = self.letters.as_mut() {
+                                letters[player_index(lowest_slot.get_player())][letter_index(lowest_slot.get_letter())] += 1;
+                            }
+
+                            lowest_slot.clear();
+                            self.heights[last_move.column] -= 1;
+                            self.moves -= 1;
+                        } else {
+                            println!("Warning: cleared an already empty slot")
+                        }
+
+                        // Switch turns
+                        self.player_turn = !self.player_turn;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check if the given player has four connected pieces
+    /// Return true if the player has won
+    pub fn check_if_won(&self, player: u32) -> bool {
+        return if self.game_type == GameType::Connect4 {
+            self.check_if_won_connect_4(player)
+        } else if self.game_type == GameType::OttoToot {
+            self.check_if_won_toot_and_otto(player)
+        } else {
+            panic!("Unknown game type");
+        };
+    }
+
+    fn check_if_won_connect_4(&self, player: u32) -> bool {
+        // Horizontal check
+        for i in 0..(self.rows) {
+            for j in 0..(self.columns - 3) {
+                if self.get_slot(i, j).owned_by(player)
+                    && self.get_slot(i, j + 1).owned_by(player)
+                    && self.get_slot(i, j + 2).owned_by(player)
+                    && self.get_slot(i, j + 3).owned_by(player)
+                {
+                    return true;
+                }
+            }
+        }
+
+        // Vertical check
+        for j in 0..(self.columns) {
+            for i in 0..(self.rows - 3) {
+                if self.get_slot(i, j).owned_by(player)
+                    && self.get_slot(i + 1, j).owned_by(player)
+                    && self.get_slot(i + 2, j).owned_by(player)
+                    && self.get_slot(i + 3, j).owned_by(player)
+                {
+                    return true;
+                }
+            }
+        }
+
+        // Ascending diagonal check
+        for i in 3..(self.rows) {
+            for j in 0..(self.columns - 3) {
+                if self.get_slot(i, j).owned_by(player)
+                    && self.get_slot(i - 1, j + 1).owned_by(player)
+                    && self.get_slot(i - 2, j + 2).owned_by(player)
+                    && self.get_slot(i - 3, j + 3).owned_by(player)
+                {
+                    return true;
+                }
+            }
+        }
+
+        // Descending diagonal check
+        for i in 3..(self.rows) {
+            for j in 3..(self.columns) {
+                if self.get_slot(i, j).owned_by(player)
+                    && self.get_slot(i - 1, j - 1).owned_by(player)
+                    && self.get_slot(i - 2, j - 2).owned_by(player)
+                    && self.get_slot(i - 3, j - 3).owned_by(player)
+                {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn check_if_won_toot_and_otto(&self, player: u32) -> bool {
+        let win_seq = if player == PLAYER_ID {
+            PLAYER_WINNING_SEQ
+        } else {
+            AI_WINNING_SEQ
+        };
+
+        // Horizontal check
+        for i in 0..(self.rows) {
+            for j in 0..(self.columns - 3) {
+                if self.get_slot(i, j).matches_letter(win_seq[0])
+                    && self.get_slot(i, j + 1).matches_letter(win_seq[1])
+                    && self.get_slot(i, j + 2).matches_letter(win_seq[2])
+                    && self.get_slot(i, j + 3).matches_letter(win_seq[3])
+                {
+                    return true;
+                }
+            }
+        }
+
+        // Vertical check
+        for j in 0..(self.columns) {
+            for i in 0..(self.rows - 3) {
+                if self.get_slot(i, j).matches_letter(win_seq[0])
+                    && self.get_slot(i + 1, j).matches_letter(win_seq[1])
+                    && self.get_slot(i + 2, j).matches_letter(win_seq[2])
+                    && self.get_slot(i + 3, j).matches_letter(win_seq[3])
+                {
+                    return true;
+                }
+            }
+        }
+
+        // Ascending diagonal check
+        for i in 3..(self.rows) {
+            for j in 0..(self.columns - 3) {
+                if self.get_slot(i, j).matches_letter(win_seq[0])
+                    && self.get_slot(i - 1, j + 1).matches_letter(win_seq[1])
+                    && self.get_slot(i - 2, j + 2).matches_letter(win_seq[2])
+                    && self.get_slot(i - 3, j + 3).matches_letter(win_seq[3])
+                {
+                    return true;
+                }
+            }
+        }
+
+        // Descending diagonal check
+        for i in 3..(self.rows) {
+            for j in 3..(self.columns) {
+                if self.get_slot(i, j).matches_letter(win_seq[0])
+                    && self.get_slot(i - 1, j - 1).matches_letter(win_seq[1])
+                    && self.get_slot(i - 2, j - 2).matches_letter(win_seq[2])
+                    && self.get_slot(i - 3, j - 3).matches_letter(win_seq[3])
+                {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Return true if there are no more possible moves.
+    /// With a limited letter inventory this is also the case once
+    /// the player to move has run out of letters.
+    pub fn check_if_no_more_moves(&self) -> bool {
+        if self.game_type == GameType::OttoToot && self.letters.is_some() {
+            let player = if self.player_turn { PLAYER_ID } else { AI_ID };
+
+            if !self.has_letter(player, Letter::T) && !self.has_letter(player, Letter::O) {
+                return true;
+            }
+        }
+
+        for column in 0..self.columns {
+            if self.heights[column] <= self.rows - 1 {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Return true if the given move by the given player would be
+    /// a winning move.
+    pub fn check_if_winning_move(&mut self, possible_move: PossibleMove, player: u32) -> bool {
+        let column = possible_move.column;
+
+        if self.check_column_selection(column as isize) == ColumnSelectionResult::Valid {
+            // Place the piece in the column, check if won, and then remove the piece
+            self.place_at_column(possible_move, player);
+            let won = self.check_if_won(player);
+            self.undo_move();
+
+            return won;
+        }
+
+        false
+    }
+
+    /// Calculate the score of the given position
+    pub fn negamax(&mut self, depth: u32, mut alpha: i32, mut beta: i32) -> i32 {
+        if alpha >= beta {
+            panic!();
+        }
+
+        // Limit the number of recursive calls
+        if depth == 0 {
+            return 0;
+        }
+
+        let rows = self.rows as i32;
+        let columns = self.columns as i32;
+        let moves = self.moves as i32;
+        let player = if self.player_turn { PLAYER_ID } else { AI_ID };
+
+        if self.moves == (rows * columns) as u32 {
+            return 0;
+        }
+
+        // Check if current player can win on next turn
+        for possible_move in self.get_possible_moves() {
+            if self.check_if_winning_move(possible_move, player) {
+                return (rows * columns + 1 - moves) / 2;
+            }
+        }
+
+        // Upper bound of score
+        let max = (rows * columns - 1 - moves) / 2;
+
+        if beta > max {
+            beta = max;
+
+            if alpha >= beta {
+                return beta;
+            }
+        }
+
+        // Compute score of all possible next moves and keep the best one
+        for possible_move in self.get_possible_moves() {
+            self.place_at_column(possible_move, player);
+            let score = -self.negamax(depth - 1, -beta, -alpha);
+            self.undo_move();
+
+            if score >= beta {
+                return score;
+            }
+
+            if score > alpha {
+                alpha = score;
+            }
+        }
+
+        alpha
+    }
+
+    /// Randomly pick moves and determine the score for the given player
+    pub fn random_search(&mut self, player: u32) -> i32 {
+        let scoring_player = player;
+        let mut score = 0;
+
+        for _ in 0..self.ai.random_iterations {
+            let mut moves = 0;
+            let mut scout_state = GameState::Running;
+
+            loop {
+                match scout_state {
+                    GameState::Running => {
+                        // Collect all available moves
+                        let possible_moves = self.get_possible_moves();
+
+                        if possible_moves.len() == 0 {
+                            break;
+                        }
+
+                        let rand_choice = possible_moves[random::<usize>() % possible_moves.len()];
+                        let player = if self.player_turn { PLAYER_ID } else { AI_ID };
+
+                        // Temporarily place piece on board
+                        self.place_at_column(rand_choice, player);
+
+                        if self.game_type == GameType::Connect4 {
+                            if self.check_if_won(player) {
+                                scout_state = GameState::Win(player);
+                            } else if self.check_if_no_more_moves() {
+                                scout_state = GameState::Tie;
+                            }
+                        } else if self.game_type == GameType::OttoToot {
+                            if self.check_if_won(PLAYER_ID) {
+                                scout_state = GameState::Win(PLAYER_ID);
+                            } else if self.check_if_won(AI_ID) {
+                                scout_state = GameState::Win(AI_ID);
+                            } else if self.check_if_no_more_moves() {
+                                scout_state = GameState::Tie;
+                            }
+                        }
+
+                        moves += 1;
+                    }
+                    GameState::Win(player) => {
+                        if player == scoring_player {
+                            score += 1;
+                        } else {
+                            score -= 1;
+                        }
+                        break;
+                    }
+                    GameState::Tie => {
+                        break;
+                    }
+                }
+            }
+
+            // Undo the scout moves
+            for _ in 0..moves {
+                self.undo_move()
+            }
+        }
+
+        score
+    }
+
+    /// Returns the player that has spelled their word, if any.
+    /// Mirrors the order used by the game: if a single piece spells
+    /// both words the player is awarded the win.
+    pub fn get_winner(&self) -> Option<u32> {
+        if self.check_if_won(PLAYER_ID) {
+            Some(PLAYER_ID)
+        } else if self.check_if_won(AI_ID) {
+            Some(AI_ID)
+        } else {
+            None
+        }
+    }
+
+    /// Score of a decided TOOT and OTTO position, faster wins score higher
+    fn toot_and_otto_win_score(&self) -> i32 {
+        TOOT_AND_OTTO_WIN_SCORE + (self.rows * self.columns) as i32 - self.moves as i32
+    }
+
+    /// Score a single window of four slots for the given winning sequence.
+    /// A window only counts if every letter already in it is in the right place.
+    fn score_window(&self, row: usize, column: usize, direction: (isize, isize), seq: [Letter; 4]) -> i32 {
+        let mut matched = 0;
+
+        for (k, letter) in seq.iter().enumerate() {
+            let r = (row as isize + direction.0 * k as isize) as usize;
+            let c = (column as isize + direction.1 * k as isize) as usize;
+            let slot = self.get_slot(r, c);
+
+            if slot.get_occupied() {
+                if !slot.matches_letter(*letter) {
+                    return 0;
+                }
+                matched += 1;
+            }
+        }
+
+        TOOT_AND_OTTO_WINDOW_SCORES[matched.min(3)]
+    }
+
+    /// Positional evaluation of a TOOT and OTTO board from the view of the given player.
+    /// Counts how close every window is to spelling each player's word; windows
+    /// that are already spoiled by a wrong letter are worth nothing to either side.
+    pub fn evaluate_toot_and_otto(&self, player: u32) -> i32 {
+        let (own_seq, opponent_seq) = if player == PLAYER_ID {
+            (PLAYER_WINNING_SEQ, AI_WINNING_SEQ)
+        } else {
+            (AI_WINNING_SEQ, PLAYER_WINNING_SEQ)
+        };
+
+        let mut score = 0;
+
+        for direction in WINDOW_DIRECTIONS {
+            for row in 0..self.rows {
+                for column in 0..self.columns {
+                    let end_row = row as isize + direction.0 * 3;
+                    let end_column = column as isize + direction.1 * 3;
+
+                    if end_row < 0 || end_row >= self.rows as isize
+                        || end_column < 0 || end_column >= self.columns as isize
+                    {
+                        continue;
+                    }
+
+                    score += self.score_window(row, column, direction, own_seq);
+                    score -= self.score_window(row, column, direction, opponent_seq);
+                }
+            }
+        }
+
+        score
+    }
+
+    /// Calculate the score of the given TOOT and OTTO position.
+    /// Unlike [Board::negamax] a move can also lose on the spot by spelling
+    /// the opponent's word, so those moves are scored as a loss instead of
+    /// being searched, and leaf positions use [Board::evaluate_toot_and_otto].
+    pub fn negamax_toot_and_otto(&mut self, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+        // Nothing can score inside an empty window, fail low instead of searching
+        if alpha >= beta {
+            return alpha;
+        }
+
+        let player = if self.player_turn { PLAYER_ID } else { AI_ID };
+
+        if self.check_if_no_more_moves() {
+            return 0;
+        }
+
+        let possible_moves = self.get_possible_moves();
+
+        // Check if current player can win on next turn
+        for possible_move in possible_moves.iter() {
+            self.place_at_column(*possible_move, player);
+            let winner = self.get_winner();
+            self.undo_move();
+
+            if winner == Some(player) {
+                return self.toot_and_otto_win_score() - 1;
+            }
+        }
+
+        // Limit the number of recursive calls
+        if depth == 0 {
+            return self.evaluate_toot_and_otto(player);
+        }
+
+        let mut best = i32::MIN + 1;
+
+        for possible_move in possible_moves {
+            self.place_at_column(possible_move, player);
+
+            let score = match self.get_winner() {
+                // This letter spells the opponent's word
+                Some(_) => -self.toot_and_otto_win_score(),
+                None => -self.negamax_toot_and_otto(depth - 1, -beta, -alpha),
+            };
+
+            self.undo_move();
+
+            if score >= beta {
+                return score;
+            }
+
+            if score > best {
+                best = score;
+            }
+
+            if score > alpha {
+                alpha = score;
+            }
+        }
+
+        best
+    }
+
+    /// Score a TOOT and OTTO move for the given player.
+    /// Winning moves score highest, moves that spell the opponent's word
+    /// score lowest and the rest are ranked by [Board::negamax_toot_and_otto]
+    /// with the random search only used to break ties.
+    fn score_move_toot_and_otto(&mut self, possible_move: PossibleMove, player: u32) -> i32 {
+        let bound = TOOT_AND_OTTO_WIN_SCORE * 2;
+
+        self.place_at_column(possible_move, player);
+
+        let score = match self.get_winner() {
+            Some(winner) if winner == player => self.toot_and_otto_win_score(),
+            Some(_) => -self.toot_and_otto_win_score(),
+            None => -self.negamax_toot_and_otto(self.ai.search_depth, -bound, bound),
+        };
+
+        // Random search is bounded by the number of iterations so it can only break ties
+        let score = (score << 10) + self.random_search(player).clamp(-1023, 1023);
+
+        self.undo_move();
+
+        score
+    }
+
+    /// Score a Connect 4 move for the given player
+    fn score_move_connect_4(&mut self, possible_move: PossibleMove, player: u32) -> i32 {
+        let n = (self.rows * self.columns) as i32;
+
+        // Place the piece in the column, get the score, and then remove the piece
+        self.place_at_column(possible_move, player);
+
+        let mut score = (-self.negamax(self.ai.search_depth, -n / 2, n / 2)) << 14;
+
+        // 0 is returned if we reached maximum search depth
+        // Use random search instead if that happens
+        if score == 0 {
+            score = self.random_search(player);
+        }
+
+        self.undo_move();
+
+        score
+    }
+
+    /// Score every possible move for the player whose turn it is (higher is better)
+    pub fn score_moves(&mut self) -> Vec<(PossibleMove, i32)> {
+        let player = if self.player_turn { PLAYER_ID } else { AI_ID };
+
+        self.get_possible_moves().into_iter().map(|possible_move| {
+            let score = if self.game_type == GameType::OttoToot {
+                self.score_move_toot_and_otto(possible_move, player)
+            } else {
+                self.score_move_connect_4(possible_move, player)
+            };
+
+            (possible_move, score)
+        }).collect()
+    }
+
+    /// Get the next move the AI should play.
+    /// The move is picked for whichever player's turn it is, which is
+    /// the AI during a normal game.
+    pub fn get_ai_move(&mut self) -> PossibleMove {
+        // Known openings don't need to be searched
+        if let Some(book_move) = self.get_book_move() {
+            return book_move;
+        }
+
+        // Play each possible move and find the highest score
+        let mut highest_score = i32::MIN;
+        let mut choice = PossibleMove {
+            column: 0,
+            letter: None
+        };
+
+        for (possible_move, score) in self.score_moves() {
+            if score > highest_score {
+                highest_score = score;
+                choice = possible_move;
+            }
+        }
+
+        choice
+    }
+
+    /// Print the board.
+    pub fn _print(&self) {
+        let latest_move = match self.move_history.last() {
+            Some(latest_move) => {
+                latest_move
+            }
+            None => {
+                &Move {
+                    row: usize::MAX,
+                    column: usize::MAX,
+                }
+            }
+        };
+
+        for i in 0..self.rows {
+            print!("|");
+
+            for j in 0..self.columns {
+                let slot = self.get_slot(i, j);
+
+                if i == latest_move.row && j == latest_move.column {
+                    print!("*{}*|", slot.to_string(self.game_type));
+                } else {
+                    print!(" {} |", slot.to_string(self.game_type));
+                }
+            }
+
+            println!();
+        }
+
+        for j in 0..self.columns {
+            print!("{number:>width$} ", number = j + 1, width = 3);
+        }
+
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a TOOT and OTTO board by playing the given `<column>[T|O]` moves
+    /// in turn, starting with whichever player leaves the AI to move next
+    fn toot_and_otto_board(rows: usize, columns: usize, letter_limit: Option<u32>, setup: &[&str]) -> Board {
+        let first_player = if setup.len() % 2 == 1 { PLAYER_ID } else { AI_ID };
+        let mut board = Board::new(rows, columns, GameType::OttoToot, AIConfiguration::new(2, 0, 0), letter_limit, first_player);
+        board.set_opening_book(None);
+
+        for text in setup {
+            let player = if board.is_player_turn() { PLAYER_ID } else { AI_ID };
+            board.place_at_column(PossibleMove::parse(text).unwrap(), player);
+        }
+
+        assert!(!board.is_player_turn());
+        assert_eq!(board.get_winner(), None);
+
+        board
+    }
+
+    fn parse(text: &str) -> PossibleMove {
+        PossibleMove::parse(text).unwrap()
+    }
+
+    #[test]
+    fn takes_immediate_win() {
+        // Bottom row: O T T _
+        let mut board = toot_and_otto_board(4, 6, None, &["0O", "1T", "2T"]);
+
+        assert_eq!(board.get_ai_move(), parse("3O"));
+    }
+
+    #[test]
+    fn refuses_letter_spelling_opponent_word() {
+        // Bottom row: T O O _, a T spells TOOT and anything else elsewhere lets the player spell it
+        let mut board = toot_and_otto_board(4, 6, None, &["0T", "1O", "2O"]);
+        let scores = board.score_moves();
+        let losing = scores.iter().find(|(possible_move, _)| *possible_move == parse("3T")).unwrap().1;
+
+        assert!(scores.iter().all(|(_, score)| *score >= losing));
+        assert_eq!(board.get_ai_move(), parse("3O"));
+    }
+
+    #[test]
+    fn blocks_forced_win() {
+        // Bottom row: _ O O _ O O _, a T in the middle would leave two TOOTs to complete
+        let mut board = toot_and_otto_board(4, 7, None, &["1O", "2O", "4O", "5O"]);
+        let defences = [parse("0O"), parse("3O"), parse("6O")];
+
+        // Every other move is found to lose, not just ranked lower by the evaluator
+        for (possible_move, score) in board.score_moves() {
+            let lost = score < -(TOOT_AND_OTTO_WIN_SCORE << 9);
+            assert_eq!(lost, !defences.contains(&possible_move), "{:?} scored {}", possible_move, score);
+        }
+
+        let choice = board.get_ai_move();
+        assert!(defences.contains(&choice), "{:?} allows a forced win", choice);
+    }
+
+    #[test]
+    fn plays_without_missing_letter() {
+        // Bottom row: O T T _ _ O, the AI has used both of its O's so it can't complete OTTO
+        let mut board = toot_and_otto_board(4, 6, Some(2), &["0O", "1T", "5O", "2T"]);
+
+        assert_eq!(board.get_letters_remaining(AI_ID, Letter::O), Some(0));
+        assert!(board.get_possible_moves().iter().all(|possible_move| possible_move.letter == Some(Letter::T)));
+        assert_eq!(board.get_ai_move().letter, Some(Letter::T));
+    }
+}
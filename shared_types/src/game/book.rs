@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use super::board::PossibleMove;
+use super::slot::Letter;
+
+/// Opening book bundled with the game (generated using `prj3_cli book`)
+const EMBEDDED_BOOK: &str = include_str!("openings.book");
+
+thread_local! {
+    /// Parsed copy of the embedded book, parsed once per thread on first use
+    static EMBEDDED: Arc<OpeningBook> = Arc::new(
+        OpeningBook::parse(EMBEDDED_BOOK).expect("Embedded opening book is malformed")
+    );
+}
+
+/// Move suggested by the opening book.
+/// weight is how strongly the move is recommended relative to the
+/// other moves stored for the same position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BookMove {
+    pub column: usize,
+    pub letter: Option<Letter>,
+    pub weight: u32,
+}
+
+/// Opening book.
+/// Maps a position hash (see [super::Board::position_hash]) to the weighted
+/// moves the AI should pick from in that position.
+///
+/// Text format, one position per line (lines starting with '#' are comments):
+/// `<hash as 16 hex digits> <column>[T|O]:<weight> ...`
+/// where each move is written as a [PossibleMove].
+#[derive(Debug, Default)]
+pub struct OpeningBook {
+    positions: HashMap<u64, Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    /// Create an empty book
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the book bundled with the game
+    pub fn embedded() -> Arc<OpeningBook> {
+        EMBEDDED.with(|book| book.clone())
+    }
+
+    /// Parse a book from its text format
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut book = Self::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+
+            let hash = parts.next()
+                .and_then(|hash| u64::from_str_radix(hash, 16).ok())
+                .ok_or(format!("Line {}: invalid position hash", i + 1))?;
+
+            let moves = parts.map(|entry| parse_book_move(entry)
+                .ok_or(format!("Line {}: invalid move \"{}\"", i + 1, entry))
+            ).collect::<Result<Vec<BookMove>, String>>()?;
+
+            book.insert(hash, moves);
+        }
+
+        Ok(book)
+    }
+
+    /// Set the moves for a position
+    pub fn insert(&mut self, hash: u64, moves: Vec<BookMove>) {
+        self.positions.insert(hash, moves);
+    }
+
+    /// Get the moves stored for a position
+    pub fn get(&self, hash: u64) -> Option<&[BookMove]> {
+        self.positions.get(&hash).map(|moves| moves.as_slice())
+    }
+
+    /// Number of positions in the book
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns true if the book has no positions
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+impl fmt::Display for OpeningBook {
+    /// Writes the book in its text format, sorted by hash so regenerated books diff cleanly
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hashes: Vec<&u64> = self.positions.keys().collect();
+        hashes.sort();
+
+        for hash in hashes {
+            write!(f, "{:016x}", hash)?;
+
+            for book_move in &self.positions[hash] {
+                let played = PossibleMove {
+                    column: book_move.column,
+                    letter: book_move.letter,
+                };
+                write!(f, " {}:{}", played, book_move.weight)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a single `<column>[T|O]:<weight>` entry
+fn parse_book_move(entry: &str) -> Option<BookMove> {
+    let (played, weight) = entry.split_once(':')?;
+    let played = PossibleMove::parse(played)?;
+
+    Some(BookMove {
+        column: played.column,
+        letter: played.letter,
+        weight: weight.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book_move(column: usize, letter: Option<Letter>, weight: u32) -> BookMove {
+        BookMove { column, letter, weight }
+    }
+
+    #[test]
+    fn text_format_round_trips() {
+        let mut book = OpeningBook::new();
+        book.insert(0x00ff_0000_0000_0001, vec![book_move(3, None, 12), book_move(2, None, 1)]);
+        book.insert(0xabcd_ef01_2345_6789, vec![book_move(0, Some(Letter::T), 5), book_move(6, Some(Letter::O), 0)]);
+
+        let text = book.to_string();
+        assert_eq!(text, "00ff000000000001 3:12 2:1\nabcdef0123456789 0T:5 6O:0\n");
+
+        let parsed = OpeningBook::parse(&text).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed.get(0x00ff_0000_0000_0001), book.get(0x00ff_0000_0000_0001));
+        assert_eq!(parsed.get(0xabcd_ef01_2345_6789), book.get(0xabcd_ef01_2345_6789));
+        assert_eq!(parsed.to_string(), text);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let book = OpeningBook::parse("# comment\n\n  0000000000000010 1:1  \n").unwrap();
+        assert_eq!(book.get(0x10), Some(&[book_move(1, None, 1)][..]));
+    }
+
+    #[test]
+    fn embedded_book_parses_every_line() {
+        let positions = EMBEDDED_BOOK.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .count();
+
+        // Every position line is kept, none are rejected or overwrite another
+        assert!(positions > 0);
+        assert_eq!(OpeningBook::embedded().len(), positions);
+    }
+
+    #[test]
+    fn malformed_lines_are_rejected() {
+        for line in [
+            "xyz 3:1",
+            "0000000000000001 3",
+            "0000000000000001 3:",
+            "0000000000000001 3:-1",
+            "0000000000000001 3X:1",
+            "0000000000000001 :1",
+            "10000000000000000 3:1"
+        ] {
+            assert!(OpeningBook::parse(line).is_err(), "{}", line);
+        }
+
+        // Errors point at the line
+        let err = OpeningBook::parse("0000000000000001 3:1\n0000000000000002 3:one").unwrap_err();
+        assert_eq!(err, "Line 2: invalid move \"3:one\"");
+    }
+}
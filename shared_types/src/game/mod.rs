@@ -1,8 +1,12 @@
 pub use board::*;
-use shared_types::types::GameType;
+pub use book::*;
+pub use puzzle::*;
+use crate::types::{BoardSize, FirstMover, GameType};
 pub use slot::*;
 
 pub mod board;
+pub mod book;
+pub mod puzzle;
 pub mod slot;
 
 pub const PLAYER_ID: u32 = 1;
@@ -22,23 +26,57 @@ pub enum GameState {
 /// game_type: Connect4 or TOOT and OTTO
 /// board: stores the board of the current game
 /// state: current game state (running, win, or tie)
+/// first_mover: who made the opening move
 pub struct Game {
     game_type: GameType,
     board: Board,
     state: GameState,
+    first_mover: FirstMover,
 }
 
 impl Game {
-    pub fn new(rows: usize, columns: usize, game_type: GameType, ai: AIConfiguration) -> Self {
-        let board = Board::new(rows, columns, game_type, ai);
+    /// Create a new game.
+    /// If the CPU moves first the opening move is left to the caller (see [Game::ai_turn])
+    pub fn new(
+        rows: usize,
+        columns: usize,
+        game_type: GameType,
+        ai: AIConfiguration,
+        letter_limit: Option<u32>,
+        first_mover: FirstMover
+    ) -> Self {
+        let first_player = match first_mover {
+            FirstMover::Player => PLAYER_ID,
+            FirstMover::Cpu => AI_ID,
+        };
+        let board = Board::new(rows, columns, game_type, ai, letter_limit, first_player);
 
         Self {
             game_type,
             board,
             state: GameState::Running,
+            first_mover,
         }
     }
 
+    /// Get who made the opening move
+    pub fn get_first_mover(&self) -> FirstMover {
+        self.first_mover
+    }
+
+    /// Get the size of the board
+    pub fn get_board_size(&self) -> BoardSize {
+        BoardSize {
+            columns: self.board.columns as i32,
+            rows: self.board.rows as i32
+        }
+    }
+
+    /// Returns true if it is the player's turn to move
+    pub fn is_player_turn(&self) -> bool {
+        self.board.is_player_turn()
+    }
+
     /// Get game state
     pub fn get_state(&self) -> GameState {
         self.state
@@ -71,9 +109,25 @@ impl Game {
         self.state
     }
 
+    /// Get the number of the given letter the player has left (None if unlimited)
+    pub fn get_letters_remaining(&self, player: u32, letter: Letter) -> Option<u32> {
+        self.board.get_letters_remaining(player, letter)
+    }
+
     /// Begin process for player turn
     /// Returns true on success
     pub fn player_turn(&mut self, column_selection: usize, letter: Option<Letter>) -> bool {
+        if !self.is_player_turn() {
+            return false;
+        }
+
+        // The player can't place a letter they have run out of
+        if let Some(letter) = letter {
+            if self.game_type == GameType::OttoToot && !self.board.has_letter(PLAYER_ID, letter) {
+                return false;
+            }
+        }
+
         if self.board.check_column_selection(column_selection as isize) == ColumnSelectionResult::Valid {
             let possible_move = PossibleMove {
                 column: column_selection,
@@ -96,6 +150,16 @@ impl Game {
         self.board.get_moves()
     }
 
+    /// Get the number of moves made by the player
+    pub fn get_num_player_moves(&self) -> u32 {
+        let moves = self.get_num_moves();
+
+        match self.first_mover {
+            FirstMover::Player => moves / 2 + moves % 2,
+            FirstMover::Cpu => moves / 2,
+        }
+    }
+
     /// Print the board
     pub fn _print_board(&self) {
         println!("Current board:");
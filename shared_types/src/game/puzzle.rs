@@ -0,0 +1,416 @@
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{Datelike, NaiveDate};
+
+use super::board::{AI_EASY, Board, ColumnSelectionResult, PossibleMove};
+use super::{AI_ID, PLAYER_ID};
+use crate::types::GameType;
+
+/// Puzzles bundled with the game (generated using `prj3_cli puzzles`)
+const EMBEDDED_PUZZLES: &str = include_str!("puzzles.txt");
+
+thread_local! {
+    /// Parsed copy of the embedded puzzles, parsed once per thread on first use
+    static EMBEDDED: Arc<Vec<Puzzle>> = Arc::new(
+        Puzzle::parse_all(EMBEDDED_PUZZLES).expect("Embedded puzzles are malformed")
+    );
+}
+
+/// Puzzle: a position where the player can force a win.
+/// The player moves next and spells TOOT in TOOT and OTTO.
+/// Every reply from the opponent is played by [defend].
+///
+/// Text format, one puzzle per line (lines starting with '#' are comments):
+/// `<connect4|ottotoot> <columns>x<rows> <win_in> <move> ...`
+/// where the moves (written as [PossibleMove]s) lead from an empty
+/// board to the puzzle position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    pub game_type: GameType,
+    pub columns: usize,
+    pub rows: usize,
+    pub win_in: u32,
+    pub setup: Vec<PossibleMove>,
+}
+
+impl Puzzle {
+    /// Get the puzzles bundled with the game
+    pub fn embedded() -> Arc<Vec<Puzzle>> {
+        EMBEDDED.with(|puzzles| puzzles.clone())
+    }
+
+    /// Get the puzzle of the given day.
+    /// Cycles through the bundled puzzles so every day has one.
+    pub fn for_day(day: NaiveDate) -> Puzzle {
+        let puzzles = Self::embedded();
+        let index = day.num_days_from_ce().rem_euclid(puzzles.len() as i32) as usize;
+
+        puzzles[index].clone()
+    }
+
+    /// Parse every puzzle from the text format
+    pub fn parse_all(text: &str) -> Result<Vec<Puzzle>, String> {
+        let mut puzzles = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            puzzles.push(line.parse().map_err(|err| format!("Line {}: {}", i + 1, err))?);
+        }
+
+        if puzzles.is_empty() {
+            return Err("No puzzles found".into());
+        }
+
+        Ok(puzzles)
+    }
+
+    /// Get the puzzle position
+    pub fn board(&self) -> Board {
+        let mut board = Board::new(self.rows, self.columns, self.game_type, AI_EASY, None, first_player(&self.setup));
+        board.set_opening_book(None);
+
+        for possible_move in &self.setup {
+            let player = if board.is_player_turn() { PLAYER_ID } else { AI_ID };
+            board.place_at_column(*possible_move, player);
+        }
+
+        board
+    }
+
+    /// Check if the given moves solve the puzzle.
+    /// The opponent's replies are played by [defend] between the moves.
+    /// Returns an error if a move can't be played.
+    pub fn check_solution(&self, moves: &[PossibleMove]) -> Result<bool, String> {
+        let mut board = self.board();
+
+        for (i, possible_move) in moves.iter().enumerate() {
+            if i as u32 >= self.win_in {
+                return Ok(false);
+            }
+
+            if !is_legal(&board, *possible_move) {
+                return Err(format!("Move {} ({}) can't be played", i + 1, possible_move));
+            }
+
+            board.place_at_column(*possible_move, PLAYER_ID);
+
+            let reply = match board.get_winner() {
+                None => defend(&mut board, self.win_in - i as u32 - 1),
+                Some(_) => None
+            };
+
+            match reply {
+                Some(reply) => board.place_at_column(reply, AI_ID),
+                None => {
+                    // The game is over, so this has to be the last move
+                    if i + 1 < moves.len() {
+                        return Err(format!("Move {} ({}) was played after the game ended", i + 2, moves[i + 1]));
+                    }
+                    return Ok(board.get_winner() == Some(PLAYER_ID));
+                }
+            }
+
+            if board.get_winner().is_some() || board.check_if_no_more_moves() {
+                if i + 1 < moves.len() {
+                    return Err(format!("Move {} ({}) was played after the game ended", i + 2, moves[i + 1]));
+                }
+                return Ok(board.get_winner() == Some(PLAYER_ID));
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl std::str::FromStr for Puzzle {
+    type Err = String;
+
+    /// Parse a puzzle from a line of the text format.
+    /// The setup has to be playable and leave the game running.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut parts = line.split_whitespace();
+
+        let game_type = match parts.next() {
+            Some("connect4") => GameType::Connect4,
+            Some("ottotoot") => GameType::OttoToot,
+            _ => return Err("invalid game".into())
+        };
+
+        let (columns, rows) = parts.next()
+            .and_then(|size| size.split_once('x'))
+            .and_then(|(columns, rows)| Some((columns.parse().ok()?, rows.parse().ok()?)))
+            .ok_or("invalid board size")?;
+
+        let win_in = parts.next()
+            .and_then(|win_in| win_in.parse().ok())
+            .filter(|win_in| *win_in > 0)
+            .ok_or("invalid number of moves to win in")?;
+
+        let setup = parts.map(|entry| PossibleMove::parse(entry)
+            .ok_or(format!("invalid move \"{}\"", entry))
+        ).collect::<Result<Vec<PossibleMove>, String>>()?;
+
+        let puzzle = Puzzle { game_type, columns, rows, win_in, setup };
+
+        // Replay the setup to make sure it leads to a running game
+        let mut board = Board::new(rows, columns, game_type, AI_EASY, None, first_player(&puzzle.setup));
+
+        for possible_move in &puzzle.setup {
+            if !is_legal(&board, *possible_move) {
+                return Err(format!("setup move \"{}\" can't be played", possible_move));
+            }
+
+            let player = if board.is_player_turn() { PLAYER_ID } else { AI_ID };
+            board.place_at_column(*possible_move, player);
+
+            if board.get_winner().is_some() {
+                return Err("setup ends the game".into());
+            }
+        }
+
+        if board.check_if_no_more_moves() {
+            return Err("setup ends the game".into());
+        }
+
+        Ok(puzzle)
+    }
+}
+
+impl fmt::Display for Puzzle {
+    /// Writes the puzzle in the text format
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let game = match self.game_type {
+            GameType::Connect4 => "connect4",
+            GameType::OttoToot => "ottotoot"
+        };
+
+        write!(f, "{} {}x{} {}", game, self.columns, self.rows, self.win_in)?;
+
+        for possible_move in &self.setup {
+            write!(f, " {}", possible_move)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the player that opens a puzzle's setup.
+/// Whoever moves first, the player has to be next once the setup is played.
+fn first_player(setup: &[PossibleMove]) -> u32 {
+    if setup.len() % 2 == 0 { PLAYER_ID } else { AI_ID }
+}
+
+/// Return true if the move can be played on the board
+/// (letters are required in TOOT and OTTO and not allowed in Connect 4)
+pub fn is_legal(board: &Board, possible_move: PossibleMove) -> bool {
+    let letter_matches = match board.game_type {
+        GameType::Connect4 => possible_move.letter.is_none(),
+        GameType::OttoToot => possible_move.letter.is_some()
+    };
+
+    letter_matches && board.check_column_selection(possible_move.column as isize) == ColumnSelectionResult::Valid
+}
+
+/// Return true if the player (who has to be next to move) can win
+/// within the given number of their own moves whatever the opponent plays
+pub fn forces_win(board: &mut Board, moves: u32) -> bool {
+    if moves == 0 {
+        return false;
+    }
+
+    let possible_moves = board.get_possible_moves();
+
+    // Winning right away is always the fastest
+    for possible_move in &possible_moves {
+        board.place_at_column(*possible_move, PLAYER_ID);
+        let won = board.get_winner() == Some(PLAYER_ID);
+        board.undo_move();
+
+        if won {
+            return true;
+        }
+    }
+
+    if moves == 1 {
+        return false;
+    }
+
+    for possible_move in possible_moves {
+        board.place_at_column(possible_move, PLAYER_ID);
+
+        let forced = board.get_winner().is_none()
+            && !board.check_if_no_more_moves()
+            && board.get_possible_moves().into_iter().all(|reply| {
+                board.place_at_column(reply, AI_ID);
+
+                let forced = match board.get_winner() {
+                    Some(winner) => winner == PLAYER_ID,
+                    None => !board.check_if_no_more_moves() && forces_win(board, moves - 1)
+                };
+
+                board.undo_move();
+                forced
+            });
+
+        board.undo_move();
+
+        if forced {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Get the fewest moves the player (who has to be next to move)
+/// needs to force a win, None if it takes more than the limit
+pub fn moves_to_force_win(board: &mut Board, limit: u32) -> Option<u32> {
+    (1..=limit).find(|moves| forces_win(board, *moves))
+}
+
+/// Get the opponent's reply in a puzzle when the player has the given number of moves left.
+/// Wins when possible, otherwise escapes the player's forced win or delays it for as long
+/// as possible. Ties go to the first move the board offers so the reply is always the same.
+/// Returns None if the opponent has no moves left.
+pub fn defend(board: &mut Board, moves_left: u32) -> Option<PossibleMove> {
+    let mut choice = None;
+    let mut best = None;
+
+    for reply in board.get_possible_moves() {
+        board.place_at_column(reply, AI_ID);
+
+        let score = match board.get_winner() {
+            Some(AI_ID) => u32::MAX,
+            Some(_) => 0,
+            // The player needs every remaining move, or can't win at all
+            None => moves_to_force_win(board, moves_left).unwrap_or(moves_left + 1)
+        };
+
+        board.undo_move();
+
+        if best.map_or(true, |best| score > best) {
+            best = Some(score);
+            choice = Some(reply);
+        }
+    }
+
+    choice
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> PossibleMove {
+        PossibleMove::parse(text).unwrap()
+    }
+
+    fn moves(texts: &[&str]) -> Vec<PossibleMove> {
+        texts.iter().map(|text| parse(text)).collect()
+    }
+
+    /// Bottom row: P P P _ _ _ A (with two of the opponent's discs on top), win in 1
+    fn connect_4_puzzle() -> Puzzle {
+        "connect4 7x6 1 0 0 1 1 2 6".parse().unwrap()
+    }
+
+    /// Play the puzzle the way [Puzzle::check_solution] does, always taking
+    /// a move that still forces the win against [defend]'s replies
+    fn solve(puzzle: &Puzzle) -> Vec<PossibleMove> {
+        let mut board = puzzle.board();
+        let mut solution = Vec::new();
+
+        for moves_left in (1..=puzzle.win_in).rev() {
+            let next = board.get_possible_moves().into_iter().find(|possible_move| {
+                board.place_at_column(*possible_move, PLAYER_ID);
+                if board.get_winner() == Some(PLAYER_ID) {
+                    board.undo_move();
+                    return true;
+                }
+
+                let forced = board.get_winner().is_none() && match defend(&mut board, moves_left - 1) {
+                    Some(reply) => {
+                        board.place_at_column(reply, AI_ID);
+                        let forced = board.get_winner().is_none() && forces_win(&mut board, moves_left - 1);
+                        board.undo_move();
+                        forced
+                    },
+                    None => false
+                };
+
+                board.undo_move();
+                forced
+            }).expect("Puzzle has no forced win");
+
+            solution.push(next);
+            board.place_at_column(next, PLAYER_ID);
+
+            if board.get_winner() == Some(PLAYER_ID) {
+                return solution;
+            }
+
+            let reply = defend(&mut board, moves_left - 1).unwrap();
+            board.place_at_column(reply, AI_ID);
+        }
+
+        panic!("Puzzle wasn't solved in {} moves", puzzle.win_in)
+    }
+
+    #[test]
+    fn winning_move_solves_the_puzzle() {
+        assert_eq!(connect_4_puzzle().check_solution(&moves(&["3"])), Ok(true));
+    }
+
+    #[test]
+    fn other_moves_dont_solve_the_puzzle() {
+        let puzzle = connect_4_puzzle();
+
+        assert_eq!(puzzle.check_solution(&moves(&["4"])), Ok(false));
+        assert_eq!(puzzle.check_solution(&[]), Ok(false));
+        // Moves past the limit don't count even if they'd win
+        assert_eq!(puzzle.check_solution(&moves(&["4", "3"])), Ok(false));
+    }
+
+    #[test]
+    fn illegal_moves_are_rejected() {
+        let puzzle = connect_4_puzzle();
+
+        // Letters only belong in TOOT and OTTO
+        assert!(puzzle.check_solution(&moves(&["3T"])).is_err());
+        // Outside the board
+        assert!(puzzle.check_solution(&moves(&["7"])).is_err());
+        // After the game was won
+        assert!(puzzle.check_solution(&moves(&["3", "4"])).is_err());
+
+        // Bottom row: T O O _ O O with column 5 full, a T in column 3 spells TOOT
+        let toot_and_otto: Puzzle = "ottotoot 6x4 1 0T 5O 1O 5O 2O 5T 4O 5O".parse().unwrap();
+        assert!(toot_and_otto.check_solution(&moves(&["3"])).is_err());
+        // Full column
+        assert!(toot_and_otto.check_solution(&moves(&["5T"])).is_err());
+        assert_eq!(toot_and_otto.check_solution(&moves(&["3T"])), Ok(true));
+    }
+
+    #[test]
+    fn two_move_puzzles_are_solved_against_the_replies() {
+        let puzzles: Vec<Puzzle> = Puzzle::embedded().iter()
+            .filter(|puzzle| puzzle.win_in == 2)
+            .cloned()
+            .collect();
+        assert!(!puzzles.is_empty());
+
+        for puzzle in puzzles {
+            let solution = solve(&puzzle);
+            assert_eq!(puzzle.check_solution(&solution), Ok(true), "{}", puzzle);
+
+            // Stopping before the last move leaves it unsolved
+            if solution.len() > 1 {
+                assert_eq!(puzzle.check_solution(&solution[..solution.len() - 1]), Ok(false), "{}", puzzle);
+            }
+        }
+    }
+}
@@ -81,6 +81,11 @@ impl Slot {
         self.player
     }
 
+    /// Return the letter of the piece in this slot
+    pub fn get_letter(&self) -> Letter {
+        self.letter
+    }
+
     /// Check if this slot is occupied by a piece owned by the given player
     pub fn owned_by(&self, player: u32) -> bool {
         if self.occupied && self.player == player {
@@ -6,6 +6,11 @@
 #[cfg(feature = "run_migrations")] extern crate diesel_migrations;
 
 pub mod types;
+pub mod validation;
+pub mod rating;
+pub mod achievements;
+#[cfg(feature = "game")]
+pub mod game;
 #[cfg(feature = "database")]
 pub mod models;
 #[cfg(feature = "database")]
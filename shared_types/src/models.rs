@@ -3,19 +3,66 @@
 use super::schema::*;
 use super::types::*;
 
+use chrono::NaiveDate;
 use chrono::NaiveDateTime;
 use chrono::DateTime;
+use chrono::Duration;
 use chrono::Utc;
 
+/// Number of bytes of random salt used for each password hash
+const PASSWORD_SALT_LENGTH: usize = 16;
+
+/// Cost parameters for hashing passwords with argon2id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordHashParams {
+    /// Memory used in KiB
+    pub mem_cost: u32,
+    /// Number of passes over the memory
+    pub time_cost: u32,
+    /// Degree of parallelism
+    pub lanes: u32,
+}
+
+impl Default for PasswordHashParams {
+    /// Recommended minimums for argon2id (19 MiB, 2 passes, 1 lane)
+    fn default() -> Self {
+        PasswordHashParams {
+            mem_cost: 19 * 1024,
+            time_cost: 2,
+            lanes: 1
+        }
+    }
+}
+
+impl PasswordHashParams {
+    /// Argon2 config for hashing with these parameters
+    fn config(&self) -> argon2::Config<'static> {
+        argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            version: argon2::Version::Version13,
+            mem_cost: self.mem_cost,
+            time_cost: self.time_cost,
+            lanes: self.lanes,
+            ..argon2::Config::default()
+        }
+    }
+}
+
 /// Database Model of a User
 #[derive(Debug, Identifiable, Queryable, Insertable)]
 #[table_name = "users"]
 #[primary_key(user_id)]
 pub struct UserModel {
-    /// Username
+    /// Username (as the user typed it when registering)
     pub user_id: String,
     /// Hashed password
     pub password: String,
+    /// Canonical username used for lookups (see [crate::validation::username_key])
+    /// 
+    /// Only missing for users that existed before keys were added and collide with another user
+    pub user_key: Option<String>,
+    /// What the user is allowed to do
+    pub role: UserRole,
 }
 
 impl UserModel {
@@ -25,23 +72,60 @@ impl UserModel {
     /// The generated model will have a hashed version of the raw_password
     /// 
     /// If the hash fails return the error
-    pub fn generate_new(user_id: String, raw_password: String) -> Result<Self, argon2::Error> {
-        // Generate password salt
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        let mut salt = vec![0u8; user_id.len() + raw_password.len()];
-        salt.iter_mut().for_each(|val| *val = rng.gen());
-
+    pub fn generate_new(user_id: String, raw_password: String, params: &PasswordHashParams) -> Result<Self, argon2::Error> {
         // Generate password hash
-        let pwd_hash = argon2::hash_encoded(&raw_password.as_bytes(), &salt, &argon2::Config::default())?;
+        let pwd_hash = Self::hash_password(&raw_password, params)?;
 
         // Return model
         Ok(UserModel {
+            user_key: Some(crate::validation::username_key(&user_id)),
             user_id: user_id,
-            password: pwd_hash
+            password: pwd_hash,
+            role: UserRole::User
         })
     }
 
+    /// Replace the hashed password with the hash of raw_password
+    /// 
+    /// If the hash fails return the error
+    pub fn set_password(&mut self, raw_password: &str, params: &PasswordHashParams) -> Result<(), argon2::Error> {
+        self.password = Self::hash_password(raw_password, params)?;
+        Ok(())
+    }
+
+    /// Hash a raw password with a newly generated salt
+    fn hash_password(raw_password: &str, params: &PasswordHashParams) -> Result<String, argon2::Error> {
+        // Generate password salt
+        use rand::RngCore;
+        let mut salt = [0u8; PASSWORD_SALT_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+
+        argon2::hash_encoded(raw_password.as_bytes(), &salt, &params.config())
+    }
+
+    /// Checks if the hashed password was made with anything other than params
+    /// 
+    /// Older hashes used argon2i with default costs and a salt sized by the
+    /// length of the username and password, these should be rehashed the next
+    /// time the raw password is known (such as on login)
+    pub fn needs_rehash(&self, params: &PasswordHashParams) -> bool {
+        // Encoded hashes look like $argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>
+        let parts: Vec<&str> = self.password.split('$').collect();
+        if parts.len() != 6 {
+            return true;
+        }
+
+        let expected_costs = format!("m={},t={},p={}", params.mem_cost, params.time_cost, params.lanes);
+
+        // Salt is base64 without padding (4 characters for every 3 bytes)
+        let salt_length = parts[4].len() * 3 / 4;
+
+        parts[1] != argon2::Variant::Argon2id.as_lowercase_str()
+            || parts[2] != format!("v={}", argon2::Version::Version13.as_u32())
+            || parts[3] != expected_costs
+            || salt_length != PASSWORD_SALT_LENGTH
+    }
+
     /// Compares the given raw password with the hashed password
     /// 
     /// Returns true if the hashed password is the hash of the raw password
@@ -51,6 +135,309 @@ impl UserModel {
 
 }
 
+/// Database Model of a login Session
+#[derive(Debug, Identifiable, Queryable, Insertable)]
+#[table_name = "sessions"]
+#[primary_key(session_id)]
+pub struct SessionModel {
+    /// Random ID stored in the session cookie
+    pub session_id: String,
+    /// User the session belongs to
+    pub user_id: String,
+    /// When the user logged in
+    pub created_at: NaiveDateTime,
+    /// Last time the session was used
+    pub last_seen: NaiveDateTime,
+    /// When the session stops being valid (pushed back every time it is used)
+    pub expires_at: NaiveDateTime,
+    /// User agent of the browser that logged in (if given)
+    pub user_agent: Option<String>,
+}
+
+impl SessionModel {
+
+    /// Number of random bytes in a session ID
+    const ID_BYTES: usize = 32;
+
+    /// Generate a new session for the user that expires after the given lifetime
+    /// 
+    /// The session ID is generated from random bytes encoded as hex
+    pub fn generate_new(user_id: String, user_agent: Option<String>, lifetime: Duration) -> Self {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let session_id = (0..Self::ID_BYTES).map(|_| format!("{:02x}", rng.gen::<u8>())).collect();
+
+        let now = Utc::now().naive_utc();
+
+        SessionModel {
+            session_id,
+            user_id,
+            created_at: now,
+            last_seen: now,
+            expires_at: now + lifetime,
+            user_agent
+        }
+    }
+
+}
+
+/// Database Model of a personal API token
+#[derive(Debug, Queryable, Insertable)]
+#[table_name = "api_tokens"]
+pub struct ApiTokenModel {
+    /// Database row ID
+    id: Option<i32>,
+    /// User the token acts as
+    pub user_id: String,
+    /// Name given by the user
+    pub name: String,
+    /// SHA-256 hash of the token (as hex)
+    token_hash: String,
+    /// Bit mask of the [TokenScope]s granted
+    scopes: i32,
+    /// When the token was created
+    pub created_at: NaiveDateTime,
+    /// Last time the token was used
+    pub last_used: Option<NaiveDateTime>,
+}
+
+impl ApiTokenModel {
+
+    /// Number of random bytes in a token
+    const TOKEN_BYTES: usize = 32;
+
+    /// Start of every token so they are easy to recognise
+    const TOKEN_PREFIX: &'static str = "prj3_";
+
+    /// Generate a new token for the user with the given scopes
+    /// 
+    /// Returns the model along with the token, only the hash of the token is stored
+    /// so it can't be shown again
+    pub fn generate_new(user_id: String, name: String, scopes: &[TokenScope]) -> (Self, String) {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let token: String = std::iter::once(String::from(Self::TOKEN_PREFIX))
+            .chain((0..Self::TOKEN_BYTES).map(|_| format!("{:02x}", rng.gen::<u8>())))
+            .collect();
+
+        let model = ApiTokenModel {
+            id: None,
+            user_id,
+            name,
+            token_hash: Self::hash_token(&token),
+            scopes: TokenScope::to_mask(scopes),
+            created_at: Utc::now().naive_utc(),
+            last_used: None
+        };
+
+        (model, token)
+    }
+
+    /// Hash a token the way it is stored
+    /// 
+    /// Tokens are random so a fast hash is enough (unlike passwords)
+    pub fn hash_token(token: &str) -> String {
+        use sha2::{Digest, Sha256};
+        Sha256::digest(token.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Returns true if the token was granted the scope
+    pub fn has_scope(&self, scope: TokenScope) -> bool {
+        self.scopes & scope as i32 != 0
+    }
+
+    /// Convert the model into an [ApiTokenInfo]
+    pub fn as_info(self) -> ApiTokenInfo {
+        ApiTokenInfo {
+            id: self.id.unwrap_or_default(),
+            name: self.name,
+            scopes: TokenScope::from_mask(self.scopes),
+            created_at: DateTime::from_utc(self.created_at, Utc),
+            last_used: self.last_used.map(|time| DateTime::from_utc(time, Utc))
+        }
+    }
+
+}
+
+/// Database Model of the failed logins of a username
+#[derive(Debug, Identifiable, Queryable, Insertable)]
+#[table_name = "user_lockouts"]
+#[primary_key(user_key)]
+pub struct LockoutModel {
+    /// Key of the username that was attempted (see [crate::validation::username_key])
+    pub user_key: String,
+    /// Number of failed logins in a row
+    pub failures: i32,
+    /// When the last failed login happened
+    pub last_failure: NaiveDateTime,
+    /// When the username can be logged into again (if locked)
+    pub locked_until: Option<NaiveDateTime>,
+}
+
+/// Database Model of an action performed by an admin
+#[derive(Debug, Queryable, Insertable)]
+#[table_name = "audit_log"]
+pub struct AuditLogModel {
+    /// Database row ID
+    id: Option<i32>,
+    /// Admin that performed the action
+    admin_id: String,
+    /// Name of the action (such as "delete_user")
+    action: String,
+    /// User or record the action was performed on
+    target: String,
+    /// When the action was performed
+    performed_at: NaiveDateTime,
+}
+
+impl AuditLogModel {
+
+    /// Create an entry for an action performed now
+    pub fn new(admin_id: String, action: &str, target: String) -> Self {
+        AuditLogModel {
+            id: None,
+            admin_id,
+            action: String::from(action),
+            target,
+            performed_at: Utc::now().naive_utc(),
+        }
+    }
+
+    /// Convert the model into an [AuditLogEntry]
+    pub fn as_entry(self) -> AuditLogEntry {
+        AuditLogEntry {
+            admin_id: self.admin_id,
+            action: self.action,
+            target: self.target,
+            performed_at: DateTime::from_utc(self.performed_at, Utc),
+        }
+    }
+
+}
+
+/// Database Model of the current rating of a user for a game (see [crate::rating])
+#[derive(Debug, Identifiable, Queryable, Insertable)]
+#[table_name = "ratings"]
+#[primary_key(user_id, game_id)]
+pub struct RatingModel {
+    /// User the rating is for
+    pub user_id: String,
+    /// Game the rating is for
+    pub game_id: GameType,
+    /// Current rating
+    pub rating: f64,
+    /// Number of matches the rating is based on
+    pub matches: i32,
+    /// When the last match was rated
+    pub updated_at: NaiveDateTime,
+}
+
+impl RatingModel {
+
+    /// Rating of a user that hasn't played the game yet
+    pub fn initial(user_id: String, game_id: GameType, now: NaiveDateTime) -> Self {
+        RatingModel {
+            user_id,
+            game_id,
+            rating: crate::rating::INITIAL_RATING,
+            matches: 0,
+            updated_at: now
+        }
+    }
+
+    /// Rating after a match, treating the CPU level as the opponent (see [crate::rating::cpu_rating])
+    pub fn after_match(self, record: &MatchRecordModel) -> Self {
+        let opponent_rating = crate::rating::cpu_rating(record.cpu_level);
+        RatingModel {
+            rating: crate::rating::updated_rating(self.rating, self.matches, opponent_rating, record.result),
+            matches: self.matches + 1,
+            updated_at: record.finished_at,
+            ..self
+        }
+    }
+
+}
+
+/// Database Model of a user's rating after a match
+#[derive(Debug, Queryable, Insertable)]
+#[table_name = "rating_history"]
+pub struct RatingHistoryModel {
+    /// Database row ID
+    id: Option<i32>,
+    /// User the rating is for
+    user_id: String,
+    /// Game the rating is for
+    game_id: GameType,
+    /// Rating after the match
+    rating: f64,
+    /// When the match was rated
+    recorded_at: NaiveDateTime,
+}
+
+impl RatingHistoryModel {
+
+    /// Convert the model into a [RatingPoint]
+    pub fn as_point(self) -> RatingPoint {
+        RatingPoint {
+            rating: self.rating,
+            recorded_at: DateTime::from_utc(self.recorded_at, Utc)
+        }
+    }
+
+}
+
+impl From<&RatingModel> for RatingHistoryModel {
+
+    fn from(rating: &RatingModel) -> Self {
+        RatingHistoryModel {
+            id: None,
+            user_id: rating.user_id.clone(),
+            game_id: rating.game_id,
+            rating: rating.rating,
+            recorded_at: rating.updated_at
+        }
+    }
+
+}
+
+/// Database Model of an achievement unlocked by a user (see [crate::achievements])
+#[derive(Debug, Queryable, Insertable)]
+#[table_name = "achievements"]
+pub struct AchievementModel {
+    /// User that unlocked the achievement
+    user_id: String,
+    /// Achievement unlocked
+    achievement: Achievement,
+    /// When the achievement was unlocked
+    unlocked_at: NaiveDateTime,
+}
+
+impl AchievementModel {
+
+    /// Create a new unlocked achievement
+    pub fn new(user_id: String, achievement: Achievement, unlocked_at: NaiveDateTime) -> Self {
+        AchievementModel {
+            user_id,
+            achievement,
+            unlocked_at
+        }
+    }
+
+    /// Convert the model into an [UnlockedAchievement]
+    pub fn as_unlocked(self) -> UnlockedAchievement {
+        UnlockedAchievement {
+            achievement: self.achievement,
+            unlocked_at: DateTime::from_utc(self.unlocked_at, Utc)
+        }
+    }
+
+    /// Achievement unlocked
+    pub fn achievement(&self) -> Achievement {
+        self.achievement
+    }
+
+}
+
 /// Database Model of a Match Record
 #[derive(Debug, Queryable, Insertable)]
 #[table_name = "match_records"]
@@ -68,11 +455,60 @@ pub struct MatchRecordModel {
     /// Duration (in seconds) of match
     moves: i32,
     /// Result of match
-    result: MatchResult
+    result: MatchResult,
+    /// Who made the opening move
+    first_mover: FirstMover,
+    /// Number of columns of the board (None if the match was recorded before board sizes were)
+    #[column_name = "num_columns"]
+    columns: Option<i32>,
+    /// Number of rows of the board (None if the match was recorded before board sizes were)
+    rows: Option<i32>,
+    /// Key chosen by the client so the record is only added once however many times it's sent
+    idempotency_key: Option<String>
 }
 
 impl MatchRecordModel {
 
+    /// Set the key that stops the record being added twice (see [crate::queries::match_records::add_batch])
+    pub fn with_idempotency_key(self, key: String) -> Self {
+        MatchRecordModel {
+            idempotency_key: Some(key),
+            ..self
+        }
+    }
+
+    /// Key that stops the record being added twice (None if it wasn't sent with one)
+    pub fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+
+    /// User the record is for (None if the user was deleted)
+    pub fn user_id(&self) -> Option<&str> {
+        self.user_id.as_deref()
+    }
+
+    /// Game played
+    pub fn game_id(&self) -> GameType {
+        self.game_id
+    }
+
+    /// When the match was logged to the server
+    pub fn finished_at(&self) -> NaiveDateTime {
+        self.finished_at
+    }
+
+    /// Progress of the user right after the match (see [crate::achievements])
+    pub fn progress(&self, win_streak: i64, levels_beaten: Vec<(GameType, CpuLevel)>) -> crate::achievements::Progress {
+        crate::achievements::Progress {
+            game_id: self.game_id,
+            cpu_level: self.cpu_level,
+            result: self.result,
+            moves: self.moves,
+            win_streak,
+            levels_beaten
+        }
+    }
+
     /// Convert the model into a [MatchRecord]
     pub fn as_record(self) -> MatchRecord {
         MatchRecord {
@@ -81,7 +517,10 @@ impl MatchRecordModel {
             game_id: self.game_id,
             cpu_level: self.cpu_level,
             moves: self.moves,
-            result: self.result
+            result: self.result,
+            first_mover: self.first_mover,
+            columns: self.columns,
+            rows: self.rows
         }
     }
 
@@ -89,6 +528,17 @@ impl MatchRecordModel {
     pub fn get_id(&self) -> Option<i32> {
         self.id
     }
+
+    /// Cursor to continue a list of records sorted by the given order after this record
+    pub fn cursor(&self, sort_by: MatchQuerySortBy, asc: bool) -> MatchRecordCursor {
+        MatchRecordCursor {
+            sort_by,
+            asc,
+            moves: self.moves,
+            finished_at: self.finished_at,
+            id: self.id.unwrap_or_default()
+        }
+    }
 }
 
 impl From<(UserAuthToken, ClientMatchData)> for MatchRecordModel {
@@ -102,7 +552,11 @@ impl From<(UserAuthToken, ClientMatchData)> for MatchRecordModel {
             game_id: record.game_id,
             cpu_level: record.cpu_level,
             moves: record.moves,
-            result: record.result
+            result: record.result,
+            first_mover: record.first_mover,
+            columns: Some(record.columns),
+            rows: Some(record.rows),
+            idempotency_key: None
         }
     }
 }
@@ -118,7 +572,332 @@ impl From<(UserAuthToken, MatchRecord)> for MatchRecordModel {
             game_id: record.game_id,
             cpu_level: record.cpu_level,
             moves: record.moves,
-            result: record.result
+            result: record.result,
+            first_mover: record.first_mover,
+            columns: record.columns,
+            rows: record.rows,
+            idempotency_key: None
+        }
+    }
+}
+
+/// Database Model of a user's row on a leaderboard (see [crate::queries::leaderboard])
+#[derive(Debug, Clone, QueryableByName)]
+pub struct LeaderboardRowModel {
+    /// Position on the leaderboard
+    #[sql_type = "diesel::sql_types::BigInt"]
+    rank: i64,
+    /// User the results are for
+    #[sql_type = "diesel::sql_types::Text"]
+    user_id: String,
+    /// Number of matches won
+    #[sql_type = "diesel::sql_types::BigInt"]
+    wins: i64,
+    /// Number of matches lost
+    #[sql_type = "diesel::sql_types::BigInt"]
+    losses: i64,
+    /// Number of matches tied
+    #[sql_type = "diesel::sql_types::BigInt"]
+    ties: i64,
+    /// Fewest moves taken to win
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Integer>"]
+    best_win: Option<i32>,
+}
+
+impl LeaderboardRowModel {
+
+    /// Convert the model into a [LeaderboardEntry]
+    pub fn as_entry(self) -> LeaderboardEntry {
+        let played = self.wins + self.losses + self.ties;
+        LeaderboardEntry {
+            rank: self.rank,
+            win_rate: if played > 0 { self.wins as f64 / played as f64 } else { 0.0 },
+            user_id: self.user_id,
+            wins: self.wins,
+            losses: self.losses,
+            ties: self.ties,
+            best_win: self.best_win
         }
     }
+
+    /// Position on the leaderboard
+    pub fn rank(&self) -> i64 {
+        self.rank
+    }
+
+    /// User the results are for
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+}
+/// Database Model of a user's results against a CPU level (see [crate::queries::user_stats])
+#[derive(Debug, QueryableByName)]
+pub struct LevelStatsRowModel {
+    /// Game played
+    #[sql_type = "diesel::sql_types::Integer"]
+    game_id: GameType,
+    /// Level of opponent
+    #[sql_type = "diesel::sql_types::Integer"]
+    cpu_level: CpuLevel,
+    /// Number of matches won
+    #[sql_type = "diesel::sql_types::BigInt"]
+    wins: i64,
+    /// Number of matches lost
+    #[sql_type = "diesel::sql_types::BigInt"]
+    losses: i64,
+    /// Number of matches tied
+    #[sql_type = "diesel::sql_types::BigInt"]
+    ties: i64,
+    /// Total moves taken across every win
+    #[sql_type = "diesel::sql_types::BigInt"]
+    win_moves: i64,
+}
+
+impl LevelStatsRowModel {
+
+    /// Convert the model into [LevelStats]
+    pub fn as_level_stats(&self) -> LevelStats {
+        LevelStats {
+            cpu_level: self.cpu_level,
+            wins: self.wins,
+            losses: self.losses,
+            ties: self.ties
+        }
+    }
+
+    /// Game played
+    pub fn game_id(&self) -> GameType {
+        self.game_id
+    }
+
+    /// Level of opponent
+    pub fn cpu_level(&self) -> CpuLevel {
+        self.cpu_level
+    }
+
+    /// Number of matches won
+    pub fn wins(&self) -> i64 {
+        self.wins
+    }
+
+    /// Total moves taken across every win
+    pub fn win_moves(&self) -> i64 {
+        self.win_moves
+    }
+
+}
+
+/// Database Model of the number of matches a user played on a day (see [crate::queries::user_stats])
+#[derive(Debug, QueryableByName)]
+pub struct DailyActivityRowModel {
+    /// Day the matches were played on (UTC)
+    #[sql_type = "diesel::sql_types::Date"]
+    day: NaiveDate,
+    /// Number of matches played
+    #[sql_type = "diesel::sql_types::BigInt"]
+    matches: i64,
+}
+
+impl DailyActivityRowModel {
+
+    /// Convert the model into [DailyActivity]
+    pub fn as_activity(self) -> DailyActivity {
+        DailyActivity {
+            day: self.day,
+            matches: self.matches
+        }
+    }
+
+}
+
+/// Database Model of the results of every match during a period (see [crate::queries::game_stats])
+#[derive(Debug, QueryableByName)]
+pub struct StatsBucketRowModel {
+    /// First day of the period (UTC)
+    #[sql_type = "diesel::sql_types::Date"]
+    start: NaiveDate,
+    /// Number of matches won
+    #[sql_type = "diesel::sql_types::BigInt"]
+    wins: i64,
+    /// Number of matches lost
+    #[sql_type = "diesel::sql_types::BigInt"]
+    losses: i64,
+    /// Number of matches tied
+    #[sql_type = "diesel::sql_types::BigInt"]
+    ties: i64,
+    /// Average number of moves taken in a match
+    #[sql_type = "diesel::sql_types::Double"]
+    average_moves: f64,
+}
+
+impl StatsBucketRowModel {
+
+    /// Convert the model into a [StatsBucket]
+    pub fn as_bucket(self) -> StatsBucket {
+        StatsBucket {
+            start: self.start,
+            wins: self.wins,
+            losses: self.losses,
+            ties: self.ties,
+            average_moves: self.average_moves
+        }
+    }
+
+}
+
+/// Database Model of a user's progress on a daily puzzle (see [crate::game::Puzzle])
+#[derive(Debug, Queryable, Insertable)]
+#[table_name = "puzzle_results"]
+pub struct PuzzleResultModel {
+    /// User solving the puzzle
+    user_id: String,
+    /// Day of the puzzle (UTC)
+    day: NaiveDate,
+    /// When the user first started the puzzle
+    started_at: NaiveDateTime,
+    /// Number of solutions submitted
+    attempts: i32,
+    /// When the user solved the puzzle (None if not solved yet)
+    solved_at: Option<NaiveDateTime>,
+}
+
+impl PuzzleResultModel {
+
+    /// Create the progress of a user that just started the puzzle
+    pub fn new(user_id: String, day: NaiveDate, started_at: NaiveDateTime) -> Self {
+        PuzzleResultModel {
+            user_id,
+            day,
+            started_at,
+            attempts: 0,
+            solved_at: None
+        }
+    }
+
+    /// Progress after submitting a solution
+    /// 
+    /// Solutions submitted after the puzzle was solved don't count
+    pub fn after_attempt(self, solved: bool, now: NaiveDateTime) -> Self {
+        if self.solved_at.is_some() {
+            return self;
+        }
+
+        PuzzleResultModel {
+            attempts: self.attempts + 1,
+            solved_at: if solved { Some(now) } else { None },
+            ..self
+        }
+    }
+
+    /// Convert the model into [PuzzleProgress]
+    pub fn as_progress(&self) -> PuzzleProgress {
+        PuzzleProgress {
+            attempts: self.attempts,
+            solved: self.solved_at.is_some(),
+            solve_seconds: self.solved_at
+                .map(|solved_at| ((solved_at - self.started_at).num_milliseconds() as f64 / 1000.0).round() as i64)
+        }
+    }
+
+}
+
+/// Database Model of a user's row on a daily puzzle leaderboard (see [crate::queries::puzzles])
+#[derive(Debug, QueryableByName)]
+pub struct PuzzleLeaderboardRowModel {
+    /// Position on the leaderboard
+    #[sql_type = "diesel::sql_types::BigInt"]
+    rank: i64,
+    /// User that solved the puzzle
+    #[sql_type = "diesel::sql_types::Text"]
+    user_id: String,
+    /// Seconds from starting the puzzle to solving it
+    #[sql_type = "diesel::sql_types::BigInt"]
+    solve_seconds: i64,
+    /// Number of solutions submitted
+    #[sql_type = "diesel::sql_types::Integer"]
+    attempts: i32,
+}
+
+impl PuzzleLeaderboardRowModel {
+
+    /// Convert the model into a [PuzzleLeaderboardEntry]
+    pub fn as_entry(self) -> PuzzleLeaderboardEntry {
+        PuzzleLeaderboardEntry {
+            rank: self.rank,
+            user_id: self.user_id,
+            solve_seconds: self.solve_seconds,
+            attempts: self.attempts
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_ID: &str = "alice";
+    const PASSWORD: &str = "Password123!";
+
+    fn user_with_hash(password: String) -> UserModel {
+        UserModel {
+            user_id: String::from(USER_ID),
+            password,
+            user_key: Some(crate::validation::username_key(USER_ID)),
+            role: UserRole::User
+        }
+    }
+
+    /// Hash the way users were registered before the hash parameters were configurable
+    fn legacy_user() -> UserModel {
+        let salt = vec![7u8; USER_ID.len() + PASSWORD.len()];
+        user_with_hash(argon2::hash_encoded(PASSWORD.as_bytes(), &salt, &argon2::Config::default()).unwrap())
+    }
+
+    #[test]
+    fn legacy_hash_verifies_and_needs_rehash() {
+        let user = legacy_user();
+
+        assert_ne!(USER_ID.len() + PASSWORD.len(), PASSWORD_SALT_LENGTH);
+        assert!(user.password.starts_with("$argon2i$"));
+        assert!(user.compare(&String::from(PASSWORD)).unwrap());
+        assert!(user.needs_rehash(&PasswordHashParams::default()));
+    }
+
+    #[test]
+    fn current_hash_does_not_need_rehash() {
+        let params = PasswordHashParams::default();
+        let user = UserModel::generate_new(String::from(USER_ID), String::from(PASSWORD), &params).unwrap();
+
+        assert!(!user.needs_rehash(&params));
+    }
+
+    #[test]
+    fn weaker_costs_need_rehash() {
+        let params = PasswordHashParams::default();
+        let weaker = [
+            PasswordHashParams { mem_cost: params.mem_cost / 2, ..params.clone() },
+            PasswordHashParams { time_cost: params.time_cost - 1, ..params.clone() },
+            // Lanes are already at the minimum of 1, so any other count is checked instead
+            PasswordHashParams { lanes: params.lanes + 1, ..params.clone() },
+        ];
+
+        for weaker in weaker {
+            let user = UserModel::generate_new(String::from(USER_ID), String::from(PASSWORD), &weaker).unwrap();
+            assert!(user.needs_rehash(&params), "{:?} wasn't rehashed", weaker);
+        }
+    }
+
+    #[test]
+    fn set_password_upgrades_legacy_hash() {
+        let params = PasswordHashParams::default();
+        let mut user = legacy_user();
+
+        user.set_password(PASSWORD, &params).unwrap();
+
+        assert!(user.password.starts_with("$argon2id$"));
+        assert!(user.compare(&String::from(PASSWORD)).unwrap());
+        assert!(!user.needs_rehash(&params));
+    }
 }
\ No newline at end of file
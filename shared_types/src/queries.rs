@@ -9,6 +9,9 @@ type Result<T> = std::result::Result<T, diesel::result::Error>;
 embed_migrations!();
 
 /// Run the database migrations that haven't been ran yet
+/// 
+/// Also fills in the keys of users that don't have one yet (see [users::backfill_keys])
+/// and fails if any users' keys collide
 #[cfg(feature = "run_migrations")]
 pub fn run_migrations(conn: &SqliteConnection) -> Result<()> {
     use diesel_migrations::RunMigrationsError;
@@ -19,7 +22,19 @@ pub fn run_migrations(conn: &SqliteConnection) -> Result<()> {
             RunMigrationsError::EmptyMigration => panic!("DATABASE INITIALIZATION ERROR:\n\tempty_migration"),
             _ => panic!("DATABASE INITIALIZATION ERROR:\n\tunknown error")
         }
-    )
+    )?;
+
+    let collisions = users::backfill_keys(conn)?;
+
+    if !collisions.is_empty() {
+        let collisions: Vec<String> = collisions.into_iter()
+            .map(|(user, existing)| format!("\tusername \"{}\" collides with \"{}\"", user, existing))
+            .collect();
+
+        panic!("DATABASE INITIALIZATION ERROR:\n{}\nRename or delete these users before starting again", collisions.join("\n"));
+    }
+
+    Ok(())
 }
 
 /// Queries related to a user
@@ -28,14 +43,59 @@ pub mod users {
     use diesel::sqlite::SqliteConnection;
 
     use crate::models::UserModel;
+    use crate::types::UserRole;
+    use crate::validation::username_key;
 
     use super::Result;
 
     /// Get the UserModel that matches the ID
+    /// 
+    /// IDs are compared using their keys so case and Unicode form don't matter,
+    /// users without a key (see [backfill_keys]) have to match exactly
     pub fn find_by_id(conn: &SqliteConnection, id: &str) -> Result<Option<UserModel>> {
         use crate::schema::users::dsl::*;
 
-        users.find(id).first::<UserModel>(conn).optional()
+        // Users without a key only match exactly so they take priority
+        users.filter(user_key.eq(username_key(id)))
+            .or_filter(user_key.is_null().and(user_id.eq(id)))
+            .order(user_key.is_null().desc())
+            .first::<UserModel>(conn)
+            .optional()
+    }
+
+    /// Fill in the keys of users that don't have one
+    /// 
+    /// Users whose key is already used by another user are left without one,
+    /// returns the pairs of (user, user already using the key) that collided
+    pub fn backfill_keys(conn: &SqliteConnection) -> Result<Vec<(String, String)>> {
+        use crate::schema::users::dsl::*;
+
+        conn.transaction(|| {
+            let missing = users.select(user_id)
+                .filter(user_key.is_null())
+                .order(user_id.asc())
+                .load::<String>(conn)?;
+
+            let mut collisions = Vec::new();
+
+            for id in missing {
+                let key = username_key(&id);
+
+                let existing = users.select(user_id)
+                    .filter(user_key.eq(&key))
+                    .first::<String>(conn)
+                    .optional()?;
+
+                match existing {
+                    Some(existing) => collisions.push((id, existing)),
+                    None => {
+                        diesel::update(users.find(&id)).set(user_key.eq(&key)).execute(conn)?;
+                    }
+                }
+            }
+
+            Ok(collisions)
+        })
     }
 
     /// Add a new user to the database
@@ -47,29 +107,68 @@ pub mod users {
         user.insert_into(users).execute(conn).map(|_| ())
     }
 
+    /// Replace the hashed password of a user
+    pub fn update_password(conn: &SqliteConnection, id: &str, password_hash: &str) -> Result<()> {
+        use crate::schema::users::dsl::*;
+
+        diesel::update(users.find(id)).set(password.eq(password_hash)).execute(conn).map(|_| ())
+    }
+
+    /// Change the role of a user
+    /// 
+    /// Returns the number of users changed
+    pub fn update_role(conn: &SqliteConnection, id: &str, new_role: UserRole) -> Result<usize> {
+        use crate::schema::users::dsl::*;
+
+        diesel::update(users.find(id)).set(role.eq(new_role)).execute(conn)
+    }
+
     /// Delete a user
     /// 
-    /// Fails if no user exists
+    /// Also deletes the sessions and API tokens of the user so they are logged out everywhere,
+    /// deletes their ratings, achievements and puzzle progress
+    /// and anonymises their match records so they still count towards game stats
     pub fn delete(conn: &SqliteConnection, id: &str) -> Result<()> {
         use crate::schema::users::dsl::*;
 
-        diesel::delete(users.filter(user_id.eq(id))).execute(conn).map(|_| ())
+        conn.transaction(|| {
+            if let Some(user) = find_by_id(conn, id)? {
+                super::sessions::delete_by_user(conn, &user.user_id)?;
+                super::api_tokens::delete_by_user(conn, &user.user_id)?;
+                super::ratings::delete_by_user(conn, &user.user_id)?;
+                super::achievements::delete_by_user(conn, &user.user_id)?;
+                super::puzzles::delete_by_user(conn, &user.user_id)?;
+                super::match_records::anonymise_by_user(conn, &user.user_id)?;
+                diesel::delete(users.find(&user.user_id)).execute(conn)?;
+            }
+
+            Ok(())
+        })
     }
 
     /// Return partial list of all the users that start with the given prefix
     /// 
+    /// The prefix is compared using keys (see [find_by_id])
+    /// 
     /// id_prefix - start of user_id
     /// limit - number of rows to return
     /// offset - number of rows to skip
     pub fn find_users(conn: &SqliteConnection, id_prefix: &str, limit: i64, offset: i64) -> Result<(Vec<String>, i64)> {
         use crate::schema::users::dsl::*;
 
-        users.select(user_id).filter(user_id.like(&format!("{}%", id_prefix)))
+        let key_pattern = format!("{}%", username_key(id_prefix));
+        let id_pattern = format!("{}%", id_prefix);
+
+        users.select(user_id)
+            .filter(user_key.like(&key_pattern))
+            .or_filter(user_key.is_null().and(user_id.like(&id_pattern)))
+            .order(user_id.asc())
             .limit(limit)
             .offset(offset)
             .load::<String>(conn)
             .and_then(|list| {
-                users.filter(user_id.like(&format!("{}%", id_prefix)))
+                users.filter(user_key.like(&key_pattern))
+                .or_filter(user_key.is_null().and(user_id.like(&id_pattern)))
                 .count()
                 .first::<i64>(conn)
                 .map(|count| (list, count))
@@ -77,6 +176,734 @@ pub mod users {
     }
 }
 
+/// Queries related to login sessions
+pub mod sessions {
+    use diesel::prelude::*;
+    use diesel::sqlite::SqliteConnection;
+
+    use chrono::{Duration, NaiveDateTime};
+
+    use crate::models::SessionModel;
+
+    use super::Result;
+
+    /// Add a new session
+    pub fn add(conn: &SqliteConnection, session: &SessionModel) -> Result<()> {
+        use crate::schema::sessions::dsl::*;
+
+        session.insert_into(sessions).execute(conn).map(|_| ())
+    }
+
+    /// Get the session that matches the ID if it hasn't expired
+    /// 
+    /// The session's expiry is pushed back to [lifetime] from [now]
+    pub fn refresh(conn: &SqliteConnection, sid: &str, now: NaiveDateTime, lifetime: Duration) -> Result<Option<SessionModel>> {
+        use crate::schema::sessions::dsl::*;
+
+        conn.transaction(|| {
+            let session = sessions.find(sid)
+                .filter(expires_at.gt(now))
+                .first::<SessionModel>(conn)
+                .optional()?;
+
+            match session {
+                Some(mut session) => {
+                    session.last_seen = now;
+                    session.expires_at = now + lifetime;
+
+                    diesel::update(sessions.find(sid))
+                        .set((last_seen.eq(session.last_seen), expires_at.eq(session.expires_at)))
+                        .execute(conn)?;
+
+                    Ok(Some(session))
+                },
+                None => Ok(None)
+            }
+        })
+    }
+
+    /// Delete a session
+    pub fn delete(conn: &SqliteConnection, sid: &str) -> Result<()> {
+        use crate::schema::sessions::dsl::*;
+
+        diesel::delete(sessions.find(sid)).execute(conn).map(|_| ())
+    }
+
+    /// Delete every session of a user
+    /// 
+    /// Returns the number of sessions deleted
+    pub fn delete_by_user(conn: &SqliteConnection, uid: &str) -> Result<usize> {
+        use crate::schema::sessions::dsl::*;
+
+        diesel::delete(sessions.filter(user_id.eq(uid))).execute(conn)
+    }
+
+    /// Delete every session of a user except for the given one
+    /// 
+    /// Returns the number of sessions deleted
+    pub fn delete_other_by_user(conn: &SqliteConnection, uid: &str, keep_sid: &str) -> Result<usize> {
+        use crate::schema::sessions::dsl::*;
+
+        diesel::delete(sessions.filter(user_id.eq(uid)).filter(session_id.ne(keep_sid))).execute(conn)
+    }
+
+    /// Delete every session that expired before [now]
+    pub fn delete_expired(conn: &SqliteConnection, now: NaiveDateTime) -> Result<usize> {
+        use crate::schema::sessions::dsl::*;
+
+        diesel::delete(sessions.filter(expires_at.le(now))).execute(conn)
+    }
+}
+
+/// Queries related to personal API tokens
+pub mod api_tokens {
+    use chrono::NaiveDateTime;
+
+    use diesel::prelude::*;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::models::ApiTokenModel;
+
+    use super::Result;
+
+    /// Add a new token, returning it with its ID
+    /// 
+    /// Fails if the user already has a token with the same name
+    pub fn add(conn: &SqliteConnection, token: &ApiTokenModel) -> Result<ApiTokenModel> {
+        use crate::schema::api_tokens::dsl::*;
+
+        conn.transaction(|| {
+            token.insert_into(api_tokens).execute(conn)?;
+
+            api_tokens.filter(user_id.eq(&token.user_id))
+                .filter(name.eq(&token.name))
+                .first::<ApiTokenModel>(conn)
+        })
+    }
+
+    /// Get the token with the given hash and mark it as used
+    pub fn authenticate(conn: &SqliteConnection, hash: &str, now: NaiveDateTime) -> Result<Option<ApiTokenModel>> {
+        use crate::schema::api_tokens::dsl::*;
+
+        conn.transaction(|| {
+            let updated = diesel::update(api_tokens.filter(token_hash.eq(hash)))
+                .set(last_used.eq(now))
+                .execute(conn)?;
+
+            if updated == 0 {
+                return Ok(None);
+            }
+
+            api_tokens.filter(token_hash.eq(hash))
+                .first::<ApiTokenModel>(conn)
+                .optional()
+        })
+    }
+
+    /// Get every token of a user, most recent first
+    pub fn find_by_user(conn: &SqliteConnection, uid: &str) -> Result<Vec<ApiTokenModel>> {
+        use crate::schema::api_tokens::dsl::*;
+
+        api_tokens.filter(user_id.eq(uid))
+            .order((created_at.desc(), id.desc()))
+            .load::<ApiTokenModel>(conn)
+    }
+
+    /// Revoke a token of a user
+    /// 
+    /// Returns the number of tokens deleted (0 if the user has no token with the ID)
+    pub fn delete(conn: &SqliteConnection, uid: &str, tid: i32) -> Result<usize> {
+        use crate::schema::api_tokens::dsl::*;
+
+        diesel::delete(api_tokens.filter(user_id.eq(uid)).filter(id.eq(tid))).execute(conn)
+    }
+
+    /// Revoke every token of a user
+    /// 
+    /// Returns the number of tokens deleted
+    pub fn delete_by_user(conn: &SqliteConnection, uid: &str) -> Result<usize> {
+        use crate::schema::api_tokens::dsl::*;
+
+        diesel::delete(api_tokens.filter(user_id.eq(uid))).execute(conn)
+    }
+}
+
+/// Queries related to the failed logins of usernames
+pub mod lockouts {
+    use chrono::NaiveDateTime;
+    use diesel::prelude::*;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::models::LockoutModel;
+
+    use super::Result;
+
+    /// Get the failed logins of a username key
+    pub fn find(conn: &SqliteConnection, key: &str) -> Result<Option<LockoutModel>> {
+        use crate::schema::user_lockouts::dsl::*;
+
+        user_lockouts.find(key).first::<LockoutModel>(conn).optional()
+    }
+
+    /// Add or replace the failed logins of a username key
+    pub fn save(conn: &SqliteConnection, lockout: &LockoutModel) -> Result<()> {
+        use crate::schema::user_lockouts::dsl::*;
+
+        diesel::replace_into(user_lockouts).values(lockout).execute(conn).map(|_| ())
+    }
+
+    /// Clear the failed logins of a username key (unlocking it)
+    /// 
+    /// Returns the number of rows deleted
+    pub fn delete(conn: &SqliteConnection, key: &str) -> Result<usize> {
+        use crate::schema::user_lockouts::dsl::*;
+
+        diesel::delete(user_lockouts.find(key)).execute(conn)
+    }
+
+    /// Delete the failed logins that happened before [failed_before] and aren't locked at [now]
+    /// 
+    /// Returns the number of rows deleted
+    pub fn delete_stale(conn: &SqliteConnection, failed_before: NaiveDateTime, now: NaiveDateTime) -> Result<usize> {
+        use crate::schema::user_lockouts::dsl::*;
+
+        diesel::delete(
+            user_lockouts
+                .filter(last_failure.lt(failed_before))
+                .filter(locked_until.is_null().or(locked_until.le(now)))
+        ).execute(conn)
+    }
+
+    /// Return partial list of all the usernames with failed logins, most recent first
+    /// 
+    /// limit - number of rows to return
+    /// offset - number of rows to skip
+    pub fn find_all(conn: &SqliteConnection, limit: i64, offset: i64) -> Result<(Vec<LockoutModel>, i64)> {
+        use crate::schema::user_lockouts::dsl::*;
+
+        user_lockouts.order(last_failure.desc())
+            .limit(limit)
+            .offset(offset)
+            .load::<LockoutModel>(conn)
+            .and_then(|list| {
+                user_lockouts.count()
+                .first::<i64>(conn)
+                .map(|count| (list, count))
+            })
+    }
+}
+
+/// Queries related to the audit log of admin actions
+pub mod audit_log {
+    use diesel::prelude::*;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::models::AuditLogModel;
+
+    use super::Result;
+
+    /// Add an entry to the audit log
+    pub fn add(conn: &SqliteConnection, entry: AuditLogModel) -> Result<()> {
+        use crate::schema::audit_log::dsl::*;
+
+        entry.insert_into(audit_log).execute(conn).map(|_| ())
+    }
+
+    /// Return partial list of the audit log, most recent first
+    /// 
+    /// limit - number of rows to return
+    /// offset - number of rows to skip
+    pub fn find_all(conn: &SqliteConnection, limit: i64, offset: i64) -> Result<(Vec<AuditLogModel>, i64)> {
+        use crate::schema::audit_log::dsl::*;
+
+        audit_log.order((performed_at.desc(), id.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load::<AuditLogModel>(conn)
+            .and_then(|list| {
+                audit_log.count()
+                .first::<i64>(conn)
+                .map(|count| (list, count))
+            })
+    }
+}
+
+/// Queries related to the ratings of users (see [crate::rating])
+pub mod ratings {
+    use diesel::prelude::*;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::models::{MatchRecordModel, RatingHistoryModel, RatingModel};
+    use crate::types::GameType;
+
+    use super::Result;
+
+    /// Get the current ratings of a user (one for each game they've played)
+    pub fn find_by_user(conn: &SqliteConnection, uid: &str) -> Result<Vec<RatingModel>> {
+        use crate::schema::ratings::dsl::*;
+
+        ratings.filter(user_id.eq(uid)).order(game_id.asc()).load::<RatingModel>(conn)
+    }
+
+    /// Update the rating of the record's user for the game after the match,
+    /// adding the new rating to their history
+    /// 
+    /// Returns the new rating (None if the record has no user)
+    pub fn record_match(conn: &SqliteConnection, record: &MatchRecordModel) -> Result<Option<RatingModel>> {
+        use crate::schema::ratings::dsl::*;
+
+        let uid = match record.user_id() {
+            Some(uid) => uid,
+            None => return Ok(None)
+        };
+
+        let updated = ratings.find((uid, record.game_id()))
+            .first::<RatingModel>(conn)
+            .optional()?
+            .unwrap_or_else(|| RatingModel::initial(String::from(uid), record.game_id(), record.finished_at()))
+            .after_match(record);
+
+        diesel::replace_into(ratings).values(&updated).execute(conn)?;
+        diesel::insert_into(crate::schema::rating_history::table)
+            .values(RatingHistoryModel::from(&updated))
+            .execute(conn)?;
+
+        Ok(Some(updated))
+    }
+
+    /// Get the most recent ratings of a user for a game (oldest first)
+    /// 
+    /// limit - number of ratings to return
+    pub fn history(conn: &SqliteConnection, uid: &str, game: GameType, limit: i64) -> Result<Vec<RatingHistoryModel>> {
+        use crate::schema::rating_history::dsl::*;
+
+        rating_history
+            .filter(user_id.eq(uid).and(game_id.eq(game)))
+            .order(id.desc())
+            .limit(limit)
+            .load::<RatingHistoryModel>(conn)
+            .map(|mut points| {
+                points.reverse();
+                points
+            })
+    }
+
+    /// Delete the ratings and rating history of a user
+    /// 
+    /// Returns the number of ratings deleted
+    pub fn delete_by_user(conn: &SqliteConnection, uid: &str) -> Result<usize> {
+        diesel::delete(crate::schema::rating_history::table.filter(crate::schema::rating_history::user_id.eq(uid)))
+            .execute(conn)?;
+        diesel::delete(crate::schema::ratings::table.filter(crate::schema::ratings::user_id.eq(uid)))
+            .execute(conn)
+    }
+}
+
+/// Queries related to the achievements of users (see [crate::achievements])
+pub mod achievements {
+    use diesel::prelude::*;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::models::{AchievementModel, MatchRecordModel};
+    use crate::types::{MatchResult, Streak};
+
+    use super::Result;
+
+    /// Get the achievements a user has unlocked (oldest first)
+    pub fn find_by_user(conn: &SqliteConnection, uid: &str) -> Result<Vec<AchievementModel>> {
+        use crate::schema::achievements::dsl::*;
+
+        achievements.filter(user_id.eq(uid))
+            .order((unlocked_at.asc(), achievement.asc()))
+            .load::<AchievementModel>(conn)
+    }
+
+    /// Unlock the achievements the record's user earned with the match
+    /// 
+    /// Expects the record to already be added
+    /// 
+    /// Returns the achievements that were newly unlocked (none if the record has no user)
+    pub fn unlock_after_match(conn: &SqliteConnection, record: &MatchRecordModel) -> Result<Vec<AchievementModel>> {
+        use crate::schema::achievements::dsl::*;
+
+        let uid = match record.user_id() {
+            Some(uid) => uid,
+            None => return Ok(Vec::new())
+        };
+
+        let results = super::user_stats::results(conn, uid, record.game_id())?;
+        let win_streak = Streak::current(&results)
+            .filter(|streak| streak.result == MatchResult::Win)
+            .map_or(0, |streak| streak.length);
+
+        let levels_beaten = super::user_stats::level_totals(conn, uid)?
+            .iter()
+            .filter(|row| row.wins() > 0)
+            .map(|row| (row.game_id(), row.cpu_level()))
+            .collect();
+
+        let unlocked = find_by_user(conn, uid)?;
+
+        let newly_unlocked = crate::achievements::unlocked_by(&record.progress(win_streak, levels_beaten))
+            .filter(|earned| !unlocked.iter().any(|model| model.achievement() == *earned))
+            .map(|earned| AchievementModel::new(String::from(uid), earned, record.finished_at()))
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(achievements).values(&newly_unlocked).execute(conn)?;
+
+        Ok(newly_unlocked)
+    }
+
+    /// Delete the achievements of a user
+    /// 
+    /// Returns the number of achievements deleted
+    pub fn delete_by_user(conn: &SqliteConnection, uid: &str) -> Result<usize> {
+        use crate::schema::achievements::dsl::*;
+
+        diesel::delete(achievements.filter(user_id.eq(uid))).execute(conn)
+    }
+}
+
+/// Queries related to the leaderboards
+pub mod leaderboard {
+    use diesel::prelude::*;
+    use diesel::sql_types::{BigInt, Integer, Nullable, Text, Timestamp};
+    use diesel::sqlite::SqliteConnection;
+
+    use chrono::NaiveDateTime;
+
+    use crate::models::LeaderboardRowModel;
+    use crate::types::LeaderboardQuery;
+
+    use super::Result;
+
+    /// Results of every user that matches the filters, ranked by wins then
+    /// win rate then best (fewest move) win, with ties broken by username
+    /// so ranks stay stable between pages
+    const RANKED: &str = "
+        WITH stats AS (
+            SELECT user_id,
+                SUM(result == 1) AS wins,
+                SUM(result == -1) AS losses,
+                SUM(result == 0) AS ties,
+                MIN(CASE WHEN result == 1 THEN moves END) AS best_win
+            FROM match_records
+            WHERE user_id IS NOT NULL
+                AND game_id == ?
+                AND (? IS NULL OR cpu_level == ?)
+                AND (? IS NULL OR (columns == ? AND rows == ?))
+                AND finished_at >= ?
+            GROUP BY user_id
+        ),
+        ranked AS (
+            SELECT *, ROW_NUMBER() OVER (
+                ORDER BY wins DESC,
+                    CAST(wins AS REAL) / (wins + losses + ties) DESC,
+                    best_win IS NULL,
+                    best_win ASC,
+                    user_id ASC
+            ) AS rank
+            FROM stats
+        )
+    ";
+
+    #[derive(QueryableByName)]
+    struct Count {
+        #[sql_type = "BigInt"]
+        count: i64
+    }
+
+    /// Bind the filters of [RANKED] to a query
+    macro_rules! bind_filters {
+        ($query:expr, $game:expr, $level:expr, $board_size:expr, $since:expr) => {
+            $query
+                .bind::<Integer, _>($game as i32)
+                .bind::<Nullable<Integer>, _>($level.map(|level| level as i32))
+                .bind::<Nullable<Integer>, _>($level.map(|level| level as i32))
+                .bind::<Nullable<Integer>, _>($board_size.map(|size| size.columns))
+                .bind::<Nullable<Integer>, _>($board_size.map(|size| size.columns))
+                .bind::<Nullable<Integer>, _>($board_size.map(|size| size.rows))
+                .bind::<Timestamp, _>($since)
+        };
+    }
+
+    /// Page of a leaderboard along with the row of a given user (if they have one)
+    /// 
+    /// Only matches of the query's game, CPU level (any if None), board size (any if None)
+    /// that finished after since are counted
+    /// 
+    /// Returns the rows of the page, the number of ranked users and the user's row
+    pub fn find(
+        conn: &SqliteConnection,
+        query: &LeaderboardQuery,
+        since: Option<NaiveDateTime>,
+        uid: Option<&str>,
+    ) -> Result<(Vec<LeaderboardRowModel>, i64, Option<LeaderboardRowModel>)> {
+        let (game, level, board_size) = (query.game, query.level, query.board_size);
+        let (limit, offset) = (query.limit(), query.offset());
+        let since = since.unwrap_or_else(|| NaiveDateTime::from_timestamp(0, 0));
+
+        // Rows of the page and the user's row (even when they're off the page)
+        let rows = bind_filters!(
+            diesel::sql_query(format!(
+                "{} SELECT * FROM ranked WHERE (rank > ? AND rank <= ?) OR user_id IS ? ORDER BY rank", RANKED
+            )),
+            game, level, board_size, since
+        )
+            .bind::<BigInt, _>(offset)
+            .bind::<BigInt, _>(offset + limit)
+            .bind::<Nullable<Text>, _>(uid)
+            .load::<LeaderboardRowModel>(conn)?;
+
+        let total_count = bind_filters!(
+            diesel::sql_query(format!("{} SELECT COUNT(*) AS count FROM ranked", RANKED)),
+            game, level, board_size, since
+        )
+            .get_result::<Count>(conn)?
+            .count;
+
+        // The user's row is only part of the page if it's in range
+        let (page, off_page): (Vec<_>, Vec<_>) = rows.into_iter()
+            .partition(|row| row.rank() > offset && row.rank() <= offset + limit);
+        let own_row = page.iter()
+            .chain(off_page.iter())
+            .find(|row| Some(row.user_id()) == uid)
+            .cloned();
+
+        Ok((page, total_count, own_row))
+    }
+}
+
+/// Queries related to the statistics of a single user
+pub mod user_stats {
+    use diesel::prelude::*;
+    use diesel::sql_types::{Text, Timestamp};
+    use diesel::sqlite::SqliteConnection;
+
+    use chrono::NaiveDateTime;
+
+    use crate::models::{DailyActivityRowModel, LevelStatsRowModel};
+    use crate::types::{GameType, MatchResult};
+
+    use super::Result;
+
+    /// Results of a user against each CPU level of each game they've played
+    pub fn level_totals(conn: &SqliteConnection, uid: &str) -> Result<Vec<LevelStatsRowModel>> {
+        diesel::sql_query("
+            SELECT game_id, cpu_level,
+                SUM(result == 1) AS wins,
+                SUM(result == -1) AS losses,
+                SUM(result == 0) AS ties,
+                COALESCE(SUM(CASE WHEN result == 1 THEN moves END), 0) AS win_moves
+            FROM match_records
+            WHERE user_id == ?
+            GROUP BY game_id, cpu_level
+            ORDER BY game_id, cpu_level
+        ")
+            .bind::<Text, _>(uid)
+            .load::<LevelStatsRowModel>(conn)
+    }
+
+    /// Results of every match of a user for a game (oldest first)
+    pub fn results(conn: &SqliteConnection, uid: &str, game: GameType) -> Result<Vec<MatchResult>> {
+        use crate::schema::match_records::dsl::*;
+
+        match_records.select(result)
+            .filter(user_id.eq(uid).and(game_id.eq(game)))
+            .order((finished_at.asc(), id.asc()))
+            .load::<MatchResult>(conn)
+    }
+
+    /// Number of matches a user played on each day (UTC) since a time
+    /// 
+    /// Days without matches are left out
+    pub fn activity(conn: &SqliteConnection, uid: &str, since: NaiveDateTime) -> Result<Vec<DailyActivityRowModel>> {
+        diesel::sql_query("
+            SELECT date(finished_at) AS day, COUNT(*) AS matches
+            FROM match_records
+            WHERE user_id == ? AND finished_at >= ?
+            GROUP BY day
+            ORDER BY day
+        ")
+            .bind::<Text, _>(uid)
+            .bind::<Timestamp, _>(since)
+            .load::<DailyActivityRowModel>(conn)
+    }
+}
+
+/// Queries related to the statistics of every user's matches
+pub mod game_stats {
+    use diesel::prelude::*;
+    use diesel::sql_types::{Integer, Nullable, Timestamp};
+    use diesel::sqlite::SqliteConnection;
+
+    use chrono::NaiveDateTime;
+
+    use crate::models::StatsBucketRowModel;
+    use crate::types::{CpuLevel, GameType, StatsGroupBy};
+
+    use super::Result;
+
+    /// Results of the matches of a game (any if None) against a CPU level (any if None)
+    /// grouped by the period they finished in (oldest first)
+    /// 
+    /// Only counts matches that ended before and after the given times,
+    /// like the filters of [super::match_records::find_all_users]
+    /// 
+    /// Periods without matches are left out
+    pub fn buckets(
+        conn: &SqliteConnection,
+        group_by: StatsGroupBy,
+        game: Option<GameType>,
+        level: Option<CpuLevel>,
+        before: Option<NaiveDateTime>,
+        after: Option<NaiveDateTime>,
+    ) -> Result<Vec<StatsBucketRowModel>> {
+        // Modifiers that move a time to the first day of its period
+        let modifiers = match group_by {
+            StatsGroupBy::Day => "",
+            StatsGroupBy::Week => ", 'weekday 0', '-6 days'",
+            StatsGroupBy::Month => ", 'start of month'"
+        };
+
+        diesel::sql_query(format!("
+            SELECT strftime('%Y-%m-%d', finished_at{}) AS start,
+                SUM(result == 1) AS wins,
+                SUM(result == -1) AS losses,
+                SUM(result == 0) AS ties,
+                AVG(moves) AS average_moves
+            FROM match_records
+            WHERE (? IS NULL OR game_id == ?)
+                AND (? IS NULL OR cpu_level == ?)
+                AND (? IS NULL OR finished_at < ?)
+                AND (? IS NULL OR finished_at > ?)
+            GROUP BY start
+            ORDER BY start
+        ", modifiers))
+            .bind::<Nullable<Integer>, _>(game.map(|game| game as i32))
+            .bind::<Nullable<Integer>, _>(game.map(|game| game as i32))
+            .bind::<Nullable<Integer>, _>(level.map(|level| level as i32))
+            .bind::<Nullable<Integer>, _>(level.map(|level| level as i32))
+            .bind::<Nullable<Timestamp>, _>(before)
+            .bind::<Nullable<Timestamp>, _>(before)
+            .bind::<Nullable<Timestamp>, _>(after)
+            .bind::<Nullable<Timestamp>, _>(after)
+            .load::<StatsBucketRowModel>(conn)
+    }
+}
+
+/// Queries related to the daily puzzles (see [crate::game::Puzzle])
+pub mod puzzles {
+    use diesel::prelude::*;
+    use diesel::sql_types::{BigInt, Date};
+    use diesel::sqlite::SqliteConnection;
+
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    use crate::models::{PuzzleLeaderboardRowModel, PuzzleResultModel};
+
+    use super::Result;
+
+    /// Users that solved the puzzle of a day, ranked by solve time then attempts,
+    /// with ties broken by username so ranks stay stable between pages
+    const RANKED: &str = "
+        WITH ranked AS (
+            SELECT user_id, attempts,
+                CAST(ROUND((julianday(solved_at) - julianday(started_at)) * 86400) AS INTEGER) AS solve_seconds,
+                ROW_NUMBER() OVER (
+                    ORDER BY julianday(solved_at) - julianday(started_at) ASC,
+                        attempts ASC,
+                        user_id ASC
+                ) AS rank
+            FROM puzzle_results
+            WHERE day == ? AND solved_at IS NOT NULL
+        )
+    ";
+
+    #[derive(QueryableByName)]
+    struct Count {
+        #[sql_type = "BigInt"]
+        count: i64
+    }
+
+    /// Get a user's progress on the puzzle of a day
+    pub fn find(conn: &SqliteConnection, uid: &str, puzzle_day: NaiveDate) -> Result<Option<PuzzleResultModel>> {
+        use crate::schema::puzzle_results::dsl::*;
+
+        puzzle_results.find((uid, puzzle_day)).first::<PuzzleResultModel>(conn).optional()
+    }
+
+    /// Start timing a user on the puzzle of a day, unless they've already started it
+    /// 
+    /// Returns the user's progress
+    pub fn start(conn: &SqliteConnection, uid: &str, puzzle_day: NaiveDate, now: NaiveDateTime) -> Result<PuzzleResultModel> {
+        use crate::schema::puzzle_results::dsl::*;
+
+        diesel::insert_or_ignore_into(puzzle_results)
+            .values(&PuzzleResultModel::new(String::from(uid), puzzle_day, now))
+            .execute(conn)?;
+
+        puzzle_results.find((uid, puzzle_day)).first::<PuzzleResultModel>(conn)
+    }
+
+    /// Count a solution submitted by a user for the puzzle of a day
+    /// 
+    /// Fails with NotFound if the user never started the puzzle (see [start]) since they
+    /// haven't been timed
+    /// 
+    /// Returns the user's progress
+    pub fn record_attempt(
+        conn: &SqliteConnection,
+        uid: &str,
+        puzzle_day: NaiveDate,
+        solved: bool,
+        now: NaiveDateTime
+    ) -> Result<PuzzleResultModel> {
+        use crate::schema::puzzle_results::dsl::*;
+
+        conn.transaction(|| {
+            let updated = find(conn, uid, puzzle_day)?
+                .ok_or(diesel::result::Error::NotFound)?
+                .after_attempt(solved, now);
+            diesel::replace_into(puzzle_results).values(&updated).execute(conn)?;
+
+            Ok(updated)
+        })
+    }
+
+    /// Page of the leaderboard of the puzzle of a day (see [RANKED] for the order)
+    /// 
+    /// Returns the rows of the page and the number of users that solved the puzzle
+    pub fn leaderboard(
+        conn: &SqliteConnection,
+        puzzle_day: NaiveDate,
+        limit: i64,
+        offset: i64
+    ) -> Result<(Vec<PuzzleLeaderboardRowModel>, i64)> {
+        let rows = diesel::sql_query(format!("{} SELECT * FROM ranked ORDER BY rank LIMIT ? OFFSET ?", RANKED))
+            .bind::<Date, _>(puzzle_day)
+            .bind::<BigInt, _>(limit)
+            .bind::<BigInt, _>(offset)
+            .load::<PuzzleLeaderboardRowModel>(conn)?;
+
+        let total_count = diesel::sql_query(format!("{} SELECT COUNT(*) AS count FROM ranked", RANKED))
+            .bind::<Date, _>(puzzle_day)
+            .get_result::<Count>(conn)?
+            .count;
+
+        Ok((rows, total_count))
+    }
+
+    /// Delete the puzzle progress of a user
+    /// 
+    /// Returns the number of days deleted
+    pub fn delete_by_user(conn: &SqliteConnection, uid: &str) -> Result<usize> {
+        use crate::schema::puzzle_results::dsl::*;
+
+        diesel::delete(puzzle_results.filter(user_id.eq(uid))).execute(conn)
+    }
+}
+
 /// Queries related to the match records
 pub mod match_records {
     use diesel::prelude::*;
@@ -84,104 +911,187 @@ pub mod match_records {
 
     use chrono::NaiveDateTime;
 
-    use crate::models::MatchRecordModel;
-    use crate::types::{MatchQueryFilter, MatchQuerySortBy, MatchResult, GameType, CpuLevel};
+    use crate::models::{AchievementModel, MatchRecordModel};
+    use crate::types::{MatchQueryFilter, MatchQuerySortBy, MatchRecordCursor, MatchResult, GameType, CpuLevel, FirstMover, BoardSize};
 
     use super::Result;
 
     /// Add a match record
-    pub fn add(conn: &SqliteConnection, record: MatchRecordModel) -> Result<()> {
+    /// 
+    /// Also updates the rating of the record's user (see [super::ratings::record_match])
+    /// and unlocks their achievements (see [super::achievements::unlock_after_match])
+    /// 
+    /// Returns the achievements the match unlocked
+    pub fn add(conn: &SqliteConnection, record: MatchRecordModel) -> Result<Vec<AchievementModel>> {
+        use crate::schema::match_records::dsl::*;
+
+        conn.transaction(|| {
+            (&record).insert_into(match_records).execute(conn)?;
+            super::ratings::record_match(conn, &record)?;
+            super::achievements::unlock_after_match(conn, &record)
+        })
+    }
+
+    /// Add a batch of match records that have idempotency keys (see [MatchRecordModel::with_idempotency_key])
+    /// 
+    /// Records whose key was already used by their user are skipped, including
+    /// keys repeated within the batch. Everything is rolled back if any record fails.
+    /// 
+    /// Returns the achievements each record unlocked, None for the skipped records
+    pub fn add_batch(conn: &SqliteConnection, records: Vec<MatchRecordModel>) -> Result<Vec<Option<Vec<AchievementModel>>>> {
         use crate::schema::match_records::dsl::*;
 
-        record.insert_into(match_records).execute(conn).map(|_| ())
+        conn.transaction(|| {
+            records.into_iter().map(|record| {
+                let existing = match_records.select(id)
+                    .filter(user_id.eq(record.user_id()).and(idempotency_key.eq(record.idempotency_key())))
+                    .first::<Option<i32>>(conn)
+                    .optional()?;
+
+                match existing {
+                    Some(_) => Ok(None),
+                    None => add(conn, record).map(Some)
+                }
+            }).collect()
+        })
     }
 
     /// Delete a match record
     /// 
-    /// Fails if [rid] doesn't match a record in the database
-    pub fn delete(conn: &SqliteConnection, rid: i32) -> Result<()> {
+    /// Returns the number of records deleted (0 if [rid] doesn't match a record)
+    pub fn delete(conn: &SqliteConnection, rid: i32) -> Result<usize> {
         use crate::schema::match_records::dsl::*;
 
-        diesel::delete(match_records.filter(id.eq(rid))).execute(conn).map(|_| ())
+        diesel::delete(match_records.filter(id.eq(rid))).execute(conn)
+    }
+
+    /// Remove the user from all of their match records
+    /// 
+    /// Returns the number of records anonymised
+    pub fn anonymise_by_user(conn: &SqliteConnection, uid: &str) -> Result<usize> {
+        use crate::schema::match_records::dsl::*;
+
+        diesel::update(match_records.filter(user_id.eq(uid)))
+            .set(user_id.eq(None::<String>))
+            .execute(conn)
+    }
+
+    /// Which page of a list of match records to get
+    #[derive(Debug, Clone, Copy)]
+    pub struct PageOptions {
+        /// Only get the records that come after this one (see [MatchRecordCursor])
+        pub cursor: Option<MatchRecordCursor>,
+        /// Number of records to skip (after the cursor)
+        pub offset: i64,
+        /// Number of records to return
+        pub limit: i64,
+        /// Whether to count every record that matches the filters, which
+        /// needs a second query that gets slower the more records there are
+        pub count: bool,
     }
 
     /// Partial list of records for a given user
     /// 
     /// Uses the given options to filter results
+    /// 
+    /// Returns the records, the number of records that match the filters (if counted)
+    /// and the cursor to get the next page with (None on the last page)
     pub fn find_by_user(
         conn: &SqliteConnection,
         uid: &str,
         filter: Option<MatchQueryFilter>,
         sort_by: MatchQuerySortBy,
         asc: bool,
-        before: Option<i64>,
-        after: Option<i64>,
-        limit: i64,
-        offset: i64,
-    ) -> Result<(Vec<MatchRecordModel>, i64)> {
+        before: Option<NaiveDateTime>,
+        after: Option<NaiveDateTime>,
+        page: PageOptions,
+    ) -> Result<(Vec<MatchRecordModel>, Option<i64>, Option<MatchRecordCursor>)> {
         use crate::schema::match_records::dsl::*;
 
-        build_match_record_query(
-            &filter,
-            sort_by,
-            asc,
-            before,
-            after
-        )
-        .filter(user_id.eq(uid))
-        .limit(limit)
-        .offset(offset)
-        .load::<MatchRecordModel>(conn)
-        .and_then(|records| 
-            build_match_record_query(
-                &filter,
-                sort_by,
-                asc,
-                before,
-                after
-            )
-            .filter(user_id.eq(uid))
-            .count()
-            .first::<i64>(conn)
-            .map(|count| (records, count))
-        )
+        let build_query = || build_match_record_query(&filter, sort_by, asc, before, after).filter(user_id.eq(uid));
+
+        find_page(conn, build_query, sort_by, asc, page)
     }
 
     /// Partial list of records
     /// 
     /// Uses the given options to filter results
+    /// 
+    /// Returns the records, the number of records that match the filters (if counted)
+    /// and the cursor to get the next page with (None on the last page)
     pub fn find_all_users(
         conn: &SqliteConnection,
         filter: Option<MatchQueryFilter>,
         sort_by: MatchQuerySortBy,
         asc: bool,
-        before: Option<i64>,
-        after: Option<i64>,
-        limit: i64,
-        offset: i64,
-    ) -> Result<(Vec<MatchRecordModel>, i64)> {
-        build_match_record_query(
-            &filter,
-            sort_by,
-            asc,
-            before,
-            after
-        )
-        .limit(limit)
-        .offset(offset)
-        .load::<MatchRecordModel>(conn)
-        .and_then(|records|
-            build_match_record_query(
-                &filter,
-                sort_by,
-                asc,
-                before,
-                after
-            )
-            .count()
-            .first::<i64>(conn)
-            .map(|count| (records, count))
-        )
+        before: Option<NaiveDateTime>,
+        after: Option<NaiveDateTime>,
+        page: PageOptions,
+    ) -> Result<(Vec<MatchRecordModel>, Option<i64>, Option<MatchRecordCursor>)> {
+        let build_query = || build_match_record_query(&filter, sort_by, asc, before, after);
+
+        find_page(conn, build_query, sort_by, asc, page)
+    }
+
+    /// Helper function for getting a page of the records a query matches
+    /// 
+    /// Records are sorted by (finished_at, id) or (moves, finished_at, id), so
+    /// continuing after a cursor doesn't skip or repeat records added while paging
+    fn find_page<'a>(
+        conn: &SqliteConnection,
+        build_query: impl Fn() -> crate::schema::match_records::BoxedQuery<'a, diesel::sqlite::Sqlite>,
+        sort_by: MatchQuerySortBy,
+        asc: bool,
+        page: PageOptions,
+    ) -> Result<(Vec<MatchRecordModel>, Option<i64>, Option<MatchRecordCursor>)> {
+        use diesel::dsl::sql;
+        use diesel::sql_types::{Bool, Integer, Timestamp};
+
+        let mut query = build_query();
+
+        if let Some(cursor) = page.cursor {
+            // Compare row values so ties on the first columns fall to the next ones
+            let comparison = if asc { ">" } else { "<" };
+            query = match sort_by {
+                MatchQuerySortBy::StartTime => query.filter(
+                    sql::<Bool>(&format!("(finished_at, id) {} (", comparison))
+                        .bind::<Timestamp, _>(cursor.finished_at)
+                        .sql(", ")
+                        .bind::<Integer, _>(cursor.id)
+                        .sql(")")
+                ),
+                MatchQuerySortBy::Duration => query.filter(
+                    sql::<Bool>(&format!("(moves, finished_at, id) {} (", comparison))
+                        .bind::<Integer, _>(cursor.moves)
+                        .sql(", ")
+                        .bind::<Timestamp, _>(cursor.finished_at)
+                        .sql(", ")
+                        .bind::<Integer, _>(cursor.id)
+                        .sql(")")
+                )
+            };
+        }
+
+        // Get one extra record to know if there's a next page
+        let mut records = query
+            .limit(page.limit + 1)
+            .offset(page.offset)
+            .load::<MatchRecordModel>(conn)?;
+
+        let next_cursor = if records.len() as i64 > page.limit {
+            records.truncate(page.limit.max(0) as usize);
+            records.last().map(|record| record.cursor(sort_by, asc))
+        } else {
+            None
+        };
+
+        let total_count = if page.count {
+            Some(build_query().count().first::<i64>(conn)?)
+        } else {
+            None
+        };
+
+        Ok((records, total_count, next_cursor))
     }
 
     /// Helper function for building the match record query
@@ -189,8 +1099,8 @@ pub mod match_records {
         filter: &Option<MatchQueryFilter>,
         sort_by: MatchQuerySortBy,
         asc: bool,
-        before: Option<i64>,
-        after: Option<i64>,
+        before: Option<NaiveDateTime>,
+        after: Option<NaiveDateTime>,
     ) -> crate::schema::match_records::BoxedQuery<'a, diesel::sqlite::Sqlite> {
         use crate::schema::match_records::dsl::*;
         use itertools::Itertools;
@@ -216,34 +1126,123 @@ pub mod match_records {
             if level_filters.len() > 0 {
                 query = query.filter(cpu_level.eq_any(level_filters));
             }
+
+            // Filter first movers
+            let first_mover_filters: Vec<FirstMover> = filters.first_mover.iter().unique().cloned().collect();
+            if !first_mover_filters.is_empty() {
+                query = query.filter(first_mover.eq_any(first_mover_filters));
+            }
+
+            // Filter board sizes (matching any of the given sizes)
+            let size_filters: Vec<BoardSize> = filters.board_size.iter().unique().cloned().collect();
+            if let Some((first, rest)) = size_filters.split_first() {
+                use diesel::sql_types::Bool;
+                use diesel::sqlite::Sqlite;
+
+                let mut size_filter: Box<dyn BoxableExpression<match_records, Sqlite, SqlType = Bool>> =
+                    Box::new(num_columns.eq(first.columns).and(rows.eq(first.rows)));
+                for size in rest {
+                    size_filter = Box::new(size_filter.or(num_columns.eq(size.columns).and(rows.eq(size.rows))));
+                }
+                query = query.filter(size_filter);
+            }
         }
 
         // Filter matches that ended before the timestamp
-        if let Some(before_ts) = before {
-            query = query.filter(finished_at.lt(NaiveDateTime::from_timestamp(before_ts, 0)));
+        if let Some(before) = before {
+            query = query.filter(finished_at.lt(before));
         }
 
         // Filter matches that ended after the timestamp
-        if let Some(after_ts) = after {
-            query = query.filter(finished_at.gt(NaiveDateTime::from_timestamp(after_ts, 0)));
+        if let Some(after) = after {
+            query = query.filter(finished_at.gt(after));
         }
 
-        // Apply sorting rules
+        // Apply sorting rules (ending with the ID so every record has its own place for cursors)
         query = match sort_by {
             MatchQuerySortBy::StartTime =>
                 if asc {
-                    query.order(finished_at.asc())
+                    query.order((finished_at.asc(), id.asc()))
                 } else {
-                    query.order(finished_at.desc())
+                    query.order((finished_at.desc(), id.desc()))
                 },
             MatchQuerySortBy::Duration =>
                 if asc {
-                    query.order((moves.asc(), finished_at.desc()))
+                    query.order((moves.asc(), finished_at.asc(), id.asc()))
                 } else {
-                    query.order((moves.desc(), finished_at.desc()))
+                    query.order((moves.desc(), finished_at.desc(), id.desc()))
                 }
         };
     
         query
     }
-}
\ No newline at end of file
+}
+#[cfg(all(test, feature = "run_migrations"))]
+mod tests {
+    use diesel::prelude::*;
+    use diesel::sqlite::SqliteConnection;
+
+    use crate::types::{MatchQuerySortBy, MatchRecordCursor};
+
+    use super::match_records::{self, PageOptions};
+
+    /// Fresh in-memory database with every migration ran
+    fn connection() -> SqliteConnection {
+        let conn = SqliteConnection::establish(":memory:").unwrap();
+        super::run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn pages_neither_overlap_nor_skip_tied_records() {
+        let conn = connection();
+
+        // Most records tie on moves, finished_at or both, so only the ID tells them apart
+        for (moves, finished_at) in [
+            (10, "2026-10-19 04:00:00"), (10, "2026-10-19 04:00:00"), (12, "2026-10-19 04:00:00"),
+            (10, "2026-10-19 05:00:00"), (10, "2026-10-19 04:00:00"), (12, "2026-10-19 05:00:00"),
+            (10, "2026-10-19 05:00:00"), (12, "2026-10-19 04:00:00"), (10, "2026-10-19 04:00:00"),
+        ] {
+            diesel::sql_query(
+                "INSERT INTO match_records (user_id, finished_at, game_id, cpu_level, moves, result, first_mover) \
+                 VALUES (NULL, ?, 1, 3, ?, 1, 1)"
+            )
+                .bind::<diesel::sql_types::Text, _>(finished_at)
+                .bind::<diesel::sql_types::Integer, _>(moves)
+                .execute(&conn)
+                .unwrap();
+        }
+
+        for sort_by in [MatchQuerySortBy::StartTime, MatchQuerySortBy::Duration] {
+            for asc in [true, false] {
+                let (everything, _, _) = match_records::find_all_users(
+                    &conn, None, sort_by, asc, None, None,
+                    PageOptions { cursor: None, offset: 0, limit: 100, count: false }
+                ).unwrap();
+                let expected: Vec<_> = everything.iter().map(|record| record.get_id()).collect();
+                assert_eq!(expected.len(), 9);
+
+                // Follow the cursors through the client's opaque string like the routes do
+                let mut paged = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let (records, _, next_cursor) = match_records::find_all_users(
+                        &conn, None, sort_by, asc, None, None,
+                        PageOptions { cursor, offset: 0, limit: 2, count: false }
+                    ).unwrap();
+                    paged.extend(records.iter().map(|record| record.get_id()));
+                    assert!(paged.len() <= expected.len(), "paging by {:?} (asc: {}) repeated records", sort_by, asc);
+
+                    match next_cursor {
+                        Some(next_cursor) => cursor = Some(
+                            MatchRecordCursor::parse_for(&next_cursor.to_string(), sort_by, asc).unwrap()
+                        ),
+                        None => break
+                    }
+                }
+
+                assert_eq!(paged, expected, "paging by {:?} (asc: {})", sort_by, asc);
+            }
+        }
+    }
+}
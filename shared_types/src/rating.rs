@@ -0,0 +1,102 @@
+//! Elo ratings of players
+//!
+//! Every user has a rating for each game, starting at [INITIAL_RATING] and updated after
+//! every match by how surprising the result was.  CPU levels are opponents with fixed
+//! ratings (see [cpu_rating]) so beating a harder CPU is worth more than beating an easier one.
+
+use crate::types::{CpuLevel, MatchResult};
+
+/// Rating of a user before their first match of a game
+pub const INITIAL_RATING: f64 = 1200.0;
+
+/// Number of matches a rating is provisional for
+///
+/// Provisional ratings move faster so new players reach their level sooner
+pub const PROVISIONAL_MATCHES: i32 = 20;
+
+/// Most a provisional rating can change from one match
+const PROVISIONAL_K_FACTOR: f64 = 40.0;
+
+/// Most an established rating can change from one match
+const K_FACTOR: f64 = 20.0;
+
+/// Fixed rating of a CPU level when played as an opponent
+pub fn cpu_rating(level: CpuLevel) -> f64 {
+    match level {
+        CpuLevel::Easy => 800.0,
+        CpuLevel::Medium => 1200.0,
+        CpuLevel::Hard => 1600.0
+    }
+}
+
+/// Chance of a player beating an opponent (ties counting as half a win)
+pub fn expected_score(rating: f64, opponent_rating: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent_rating - rating) / 400.0))
+}
+
+/// Score of a match result (1 for a win, 0.5 for a tie and 0 for a loss)
+pub fn score(result: MatchResult) -> f64 {
+    match result {
+        MatchResult::Win => 1.0,
+        MatchResult::Tie => 0.5,
+        MatchResult::Loss => 0.0
+    }
+}
+
+/// New rating of a player after a match against an opponent (a CPU level or another player)
+///
+/// matches - number of matches the player had played before this one
+pub fn updated_rating(rating: f64, matches: i32, opponent_rating: f64, result: MatchResult) -> f64 {
+    let k_factor = if matches < PROVISIONAL_MATCHES {
+        PROVISIONAL_K_FACTOR
+    } else {
+        K_FACTOR
+    };
+
+    rating + k_factor * (score(result) - expected_score(rating, opponent_rating))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 0.01, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn expected_score_follows_the_rating_gap() {
+        assert_close(expected_score(1200.0, 1200.0), 0.5);
+        // 400 points is ten to one
+        assert_close(expected_score(1600.0, 1200.0), 10.0 / 11.0);
+        assert_close(expected_score(1200.0, 1600.0), 1.0 / 11.0);
+        assert_close(expected_score(1000.0, 1300.0) + expected_score(1300.0, 1000.0), 1.0);
+    }
+
+    #[test]
+    fn provisional_ratings_move_by_up_to_40() {
+        let medium = cpu_rating(CpuLevel::Medium);
+        assert_close(updated_rating(INITIAL_RATING, 0, medium, MatchResult::Win), 1220.0);
+        assert_close(updated_rating(INITIAL_RATING, 0, medium, MatchResult::Loss), 1180.0);
+        assert_close(updated_rating(INITIAL_RATING, 0, medium, MatchResult::Tie), 1200.0);
+
+        // Upsets move the rating further than expected results
+        let hard = cpu_rating(CpuLevel::Hard);
+        assert_close(updated_rating(INITIAL_RATING, PROVISIONAL_MATCHES - 1, hard, MatchResult::Win), 1200.0 + 40.0 * 10.0 / 11.0);
+        assert_close(updated_rating(INITIAL_RATING, PROVISIONAL_MATCHES - 1, hard, MatchResult::Loss), 1200.0 - 40.0 / 11.0);
+        assert_close(updated_rating(INITIAL_RATING, PROVISIONAL_MATCHES - 1, hard, MatchResult::Tie), 1200.0 + 40.0 * (0.5 - 1.0 / 11.0));
+    }
+
+    #[test]
+    fn established_ratings_move_by_up_to_20() {
+        let medium = cpu_rating(CpuLevel::Medium);
+        assert_close(updated_rating(INITIAL_RATING, PROVISIONAL_MATCHES, medium, MatchResult::Win), 1210.0);
+        assert_close(updated_rating(INITIAL_RATING, PROVISIONAL_MATCHES, medium, MatchResult::Loss), 1190.0);
+        assert_close(updated_rating(INITIAL_RATING, PROVISIONAL_MATCHES, medium, MatchResult::Tie), 1200.0);
+
+        let easy = cpu_rating(CpuLevel::Easy);
+        assert_close(updated_rating(INITIAL_RATING, 100, easy, MatchResult::Win), 1200.0 + 20.0 / 11.0);
+        assert_close(updated_rating(INITIAL_RATING, 100, easy, MatchResult::Loss), 1200.0 - 20.0 * 10.0 / 11.0);
+        assert_close(updated_rating(INITIAL_RATING, 100, easy, MatchResult::Tie), 1200.0 - 20.0 * (10.0 / 11.0 - 0.5));
+    }
+}
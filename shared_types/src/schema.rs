@@ -1,3 +1,33 @@
+table! {
+    achievements (user_id, achievement) {
+        user_id -> Text,
+        achievement -> Integer,
+        unlocked_at -> Timestamp,
+    }
+}
+
+table! {
+    api_tokens (id) {
+        id -> Nullable<Integer>,
+        user_id -> Text,
+        name -> Text,
+        token_hash -> Text,
+        scopes -> Integer,
+        created_at -> Timestamp,
+        last_used -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    audit_log (id) {
+        id -> Nullable<Integer>,
+        admin_id -> Text,
+        action -> Text,
+        target -> Text,
+        performed_at -> Timestamp,
+    }
+}
+
 table! {
     match_records (id) {
         id -> Nullable<Integer>,
@@ -7,6 +37,61 @@ table! {
         cpu_level -> Integer,
         moves -> Integer,
         result -> Integer,
+        first_mover -> Integer,
+        #[sql_name = "columns"]
+        num_columns -> Nullable<Integer>,
+        rows -> Nullable<Integer>,
+        idempotency_key -> Nullable<Text>,
+    }
+}
+
+table! {
+    puzzle_results (user_id, day) {
+        user_id -> Text,
+        day -> Date,
+        started_at -> Timestamp,
+        attempts -> Integer,
+        solved_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    rating_history (id) {
+        id -> Nullable<Integer>,
+        user_id -> Text,
+        game_id -> Integer,
+        rating -> Double,
+        recorded_at -> Timestamp,
+    }
+}
+
+table! {
+    ratings (user_id, game_id) {
+        user_id -> Text,
+        game_id -> Integer,
+        rating -> Double,
+        matches -> Integer,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    sessions (session_id) {
+        session_id -> Text,
+        user_id -> Text,
+        created_at -> Timestamp,
+        last_seen -> Timestamp,
+        expires_at -> Timestamp,
+        user_agent -> Nullable<Text>,
+    }
+}
+
+table! {
+    user_lockouts (user_key) {
+        user_key -> Text,
+        failures -> Integer,
+        last_failure -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
     }
 }
 
@@ -14,12 +99,28 @@ table! {
     users (user_id) {
         user_id -> Text,
         password -> Text,
+        user_key -> Nullable<Text>,
+        role -> Integer,
     }
 }
 
+joinable!(achievements -> users (user_id));
+joinable!(api_tokens -> users (user_id));
 joinable!(match_records -> users (user_id));
+joinable!(puzzle_results -> users (user_id));
+joinable!(rating_history -> users (user_id));
+joinable!(ratings -> users (user_id));
+joinable!(sessions -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    achievements,
+    api_tokens,
+    audit_log,
     match_records,
+    puzzle_results,
+    rating_history,
+    ratings,
+    sessions,
+    user_lockouts,
     users,
 );
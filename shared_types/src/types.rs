@@ -5,19 +5,29 @@ use diesel::deserialize::{self, FromSql};
 #[cfg(feature = "diesel")]
 use diesel::sql_types::Integer;
 
-#[cfg(feature = "rocket")]
-use rocket::request::{self, Request, FromRequest};
-#[cfg(feature = "rocket")]
-use rocket::http::Status;
-
 #[cfg(feature = "diesel")]
 use std::io::Write;
 
 use serde::{Serialize, Deserialize};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use chrono::serde::ts_seconds;
 
+/// Serialize an optional date as a UTC timestamp in seconds (like [ts_seconds])
+mod ts_seconds_option {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error> {
+        time.map(|time| time.timestamp()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Ok(Option::<i64>::deserialize(deserializer)?
+            .map(|secs| DateTime::from_utc(NaiveDateTime::from_timestamp(secs, 0), Utc)))
+    }
+}
+
 /// Game type
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "diesel", derive(FromSqlRow, AsExpression))]
@@ -130,6 +140,148 @@ where
     }
 }
 
+/// Player that made the opening move of a match
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "diesel", derive(FromSqlRow, AsExpression))]
+#[cfg_attr(feature = "rocket", derive(FromFormField))]
+#[cfg_attr(feature = "diesel", sql_type = "Integer")]
+pub enum FirstMover {
+    Player = 1,
+    Cpu = 2
+}
+
+impl Default for FirstMover {
+    /// Matches recorded before the first mover could be chosen were always started by the player
+    fn default() -> Self { FirstMover::Player }
+}
+
+#[cfg(feature = "diesel")]
+impl<DB> ToSql<Integer, DB> for FirstMover
+where
+    DB: diesel::backend::Backend,
+    i32: ToSql<Integer, DB>,
+{
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        (*self as i32).to_sql(out)
+    }
+}
+
+#[cfg(feature = "diesel")]
+impl<DB> FromSql<Integer, DB> for FirstMover
+where
+    DB: diesel::backend::Backend,
+    i32: FromSql<Integer, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        match i32::from_sql(bytes)? {
+            1 => Ok(FirstMover::Player),
+            2 => Ok(FirstMover::Cpu),
+            x => Err(format!("Unrecognized FirstMover variant {}", x).into()),
+        }
+    }
+}
+
+/// Achievement a user can unlock (see [crate::achievements])
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "diesel", derive(FromSqlRow, AsExpression))]
+#[cfg_attr(feature = "diesel", sql_type = "Integer")]
+pub enum Achievement {
+    FirstHardWin = 1,
+    WinStreak = 2,
+    PerfectWin = 3,
+    AllRounder = 4
+}
+
+#[cfg(feature = "diesel")]
+impl<DB> ToSql<Integer, DB> for Achievement
+where
+    DB: diesel::backend::Backend,
+    i32: ToSql<Integer, DB>,
+{
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        (*self as i32).to_sql(out)
+    }
+}
+
+#[cfg(feature = "diesel")]
+impl<DB> FromSql<Integer, DB> for Achievement
+where
+    DB: diesel::backend::Backend,
+    i32: FromSql<Integer, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        match i32::from_sql(bytes)? {
+            1 => Ok(Achievement::FirstHardWin),
+            2 => Ok(Achievement::WinStreak),
+            3 => Ok(Achievement::PerfectWin),
+            4 => Ok(Achievement::AllRounder),
+            x => Err(format!("Unrecognized Achievement variant {}", x).into()),
+        }
+    }
+}
+
+/// Role of a user account
+#[derive(Debug, Default, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "diesel", derive(FromSqlRow, AsExpression))]
+#[cfg_attr(feature = "rocket", derive(FromFormField))]
+#[cfg_attr(feature = "diesel", sql_type = "Integer")]
+pub enum UserRole {
+    #[default]
+    User = 1,
+    Admin = 2
+}
+
+#[cfg(feature = "diesel")]
+impl<DB> ToSql<Integer, DB> for UserRole
+where
+    DB: diesel::backend::Backend,
+    i32: ToSql<Integer, DB>,
+{
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        (*self as i32).to_sql(out)
+    }
+}
+
+#[cfg(feature = "diesel")]
+impl<DB> FromSql<Integer, DB> for UserRole
+where
+    DB: diesel::backend::Backend,
+    i32: FromSql<Integer, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        match i32::from_sql(bytes)? {
+            1 => Ok(UserRole::User),
+            2 => Ok(UserRole::Admin),
+            x => Err(format!("Unrecognized UserRole variant {}", x).into()),
+        }
+    }
+}
+
+/// Permission granted to an API token
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TokenScope {
+    ReadRecords = 1,
+    SubmitRecords = 2
+}
+
+impl TokenScope {
+    /// Every scope a token can have
+    pub const ALL: [TokenScope; 2] = [TokenScope::ReadRecords, TokenScope::SubmitRecords];
+
+    /// Combine scopes into the bit mask stored in the database
+    pub fn to_mask(scopes: &[TokenScope]) -> i32 {
+        scopes.iter().fold(0, |mask, scope| mask | *scope as i32)
+    }
+
+    /// Scopes set in a bit mask stored in the database
+    pub fn from_mask(mask: i32) -> Vec<TokenScope> {
+        TokenScope::ALL.iter()
+            .copied()
+            .filter(|scope| mask & *scope as i32 != 0)
+            .collect()
+    }
+}
+
 /// Sort type for finding match record query
 #[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "rocket", derive(FromFormField))]
@@ -138,13 +290,146 @@ pub enum MatchQuerySortBy {
     Duration,
 }
 
+/// Position of a match record in a sorted list of records, used to get
+/// the records that come after it without counting the ones before
+/// 
+/// Sent to clients as an opaque string that only works for the same sort order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchRecordCursor {
+    pub sort_by: MatchQuerySortBy,
+    pub asc: bool,
+    pub moves: i32,
+    pub finished_at: NaiveDateTime,
+    pub id: i32
+}
+
+impl MatchRecordCursor {
+
+    /// Parse a cursor, only accepting it if it was made for the given sort order
+    pub fn parse_for(cursor: &str, sort_by: MatchQuerySortBy, asc: bool) -> Option<Self> {
+        cursor.parse::<Self>()
+            .ok()
+            .filter(|cursor| cursor.sort_by == sort_by && cursor.asc == asc)
+    }
+
+}
+
+impl std::fmt::Display for MatchRecordCursor {
+
+    /// Writes the cursor as hex so clients don't rely on what's inside
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        let sort_by = match self.sort_by {
+            MatchQuerySortBy::StartTime => 's',
+            MatchQuerySortBy::Duration => 'd'
+        };
+        let fields = format!(
+            "{}{} {} {} {}",
+            sort_by,
+            if self.asc { 'a' } else { 'd' },
+            self.moves,
+            self.finished_at.timestamp_nanos(),
+            self.id
+        );
+
+        for byte in fields.bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+
+}
+
+impl std::str::FromStr for MatchRecordCursor {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() % 2 != 0 || !s.is_ascii() {
+            return Err(());
+        }
+
+        let bytes = (0..s.len()).step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| ())?;
+        let fields = String::from_utf8(bytes).map_err(|_| ())?;
+
+        let mut parts = fields.split(' ');
+        let mut order = parts.next().ok_or(())?.chars();
+
+        let sort_by = match order.next() {
+            Some('s') => MatchQuerySortBy::StartTime,
+            Some('d') => MatchQuerySortBy::Duration,
+            _ => return Err(())
+        };
+        let asc = match order.next() {
+            Some('a') => true,
+            Some('d') => false,
+            _ => return Err(())
+        };
+        let moves = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let nanos: i64 = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let id = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+
+        if order.next().is_some() || parts.next().is_some() {
+            return Err(());
+        }
+
+        Ok(MatchRecordCursor {
+            sort_by,
+            asc,
+            moves,
+            finished_at: NaiveDateTime::from_timestamp(nanos.div_euclid(1_000_000_000), nanos.rem_euclid(1_000_000_000) as u32),
+            id
+        })
+    }
+}
+
+/// Size of the board a match was played on
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BoardSize {
+    pub columns: i32,
+    pub rows: i32
+}
+
+impl std::fmt::Display for BoardSize {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{}x{}", self.columns, self.rows)
+    }
+
+}
+
+impl std::str::FromStr for BoardSize {
+    type Err = std::num::ParseIntError;
+
+    /// Parse a board size written as COLUMNSxROWS (such as 7x6)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (columns, rows) = s.split_once('x').unwrap_or((s, ""));
+        Ok(BoardSize {
+            columns: columns.trim().parse()?,
+            rows: rows.trim().parse()?
+        })
+    }
+}
+
+#[cfg(feature = "rocket")]
+impl<'v> rocket::form::FromFormField<'v> for BoardSize {
+    fn from_value(field: rocket::form::ValueField<'v>) -> rocket::form::Result<'v, Self> {
+        field.value.parse()
+            .map_err(|_| rocket::form::Error::validation("board size must be written as COLUMNSxROWS").into())
+    }
+}
+
 /// Filters for match query
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "rocket", derive(FromForm))]
 pub struct MatchQueryFilter {
     pub result: Vec<MatchResult>,
     pub game: Vec<GameType>,
-    pub level: Vec<CpuLevel>
+    pub level: Vec<CpuLevel>,
+    pub first_mover: Vec<FirstMover>,
+    pub board_size: Vec<BoardSize>
 }
 
 impl ToQueryPairs for MatchQueryFilter {
@@ -174,13 +459,110 @@ impl ToQueryPairs for MatchQueryFilter {
                 CpuLevel::Medium => "medium",
                 CpuLevel::Hard => "hard"
             }))
+        )
+        .chain(
+            self.first_mover.iter()
+            .unique()
+            .map(|value| ("filter.first_mover", match value {
+                FirstMover::Player => "player",
+                FirstMover::Cpu => "cpu"
+            }))
         ).map(
             |(key, value)| (String::from(key), String::from(value))
+        ).chain(
+            self.board_size.iter()
+            .unique()
+            .map(|value| (String::from("filter.board_size"), value.to_string()))
         ).collect()
     }
 }
 
-/// User auth token cookie
+/// Query args of the match record routes (check the design doc for their descriptions)
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "rocket", derive(FromForm))]
+pub struct RecordQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub cursor: Option<String>,
+    pub count: Option<bool>,
+    pub before: Option<i64>,
+    pub after: Option<i64>,
+    pub sort_by: Option<MatchQuerySortBy>,
+    pub asc: Option<bool>,
+    pub filter: Option<MatchQueryFilter>,
+}
+
+impl RecordQuery {
+    /// Number of records on a page (10 by default, at most 100)
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(10).clamp(1, 100)
+    }
+
+    /// Number of records skipped
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// Sort column and direction, most recent or quickest matches first by default
+    pub fn sort(&self) -> (MatchQuerySortBy, bool) {
+        let sort_by = self.sort_by.unwrap_or(MatchQuerySortBy::StartTime);
+        let asc = self.asc.unwrap_or(match sort_by {
+            MatchQuerySortBy::StartTime => false,
+            MatchQuerySortBy::Duration => true
+        });
+
+        (sort_by, asc)
+    }
+}
+
+/// Name of the cookie holding the CSRF token
+pub const CSRF_COOKIE: &str = "csrf_token";
+
+/// Header requests that change anything must copy the CSRF token into
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+
+/// Body of every error response from the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiError {
+    /// HTTP status code of the response
+    pub code: u16,
+    /// Description of what went wrong that can be shown to the user
+    pub message: String,
+    /// Errors for each invalid field of a submitted form (empty for other errors)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub field_errors: Vec<crate::validation::FieldError>,
+}
+
+impl ApiError {
+
+    /// Error without any field errors
+    pub fn new<T: Into<String>>(code: u16, message: T) -> Self {
+        ApiError {
+            code,
+            message: message.into(),
+            field_errors: Vec::new()
+        }
+    }
+
+    /// Error for a form with invalid fields (status 422)
+    pub fn invalid_input(errors: crate::validation::ValidationErrors) -> Self {
+        ApiError {
+            code: 422,
+            message: "Invalid input".into(),
+            field_errors: errors.field_errors
+        }
+    }
+
+    /// Errors for each invalid field of the submitted form
+    pub fn validation_errors(&self) -> crate::validation::ValidationErrors {
+        crate::validation::ValidationErrors {
+            field_errors: self.field_errors.clone()
+        }
+    }
+
+}
+
+/// Authenticated user (see the server's session guard)
 #[derive(Debug)]
 pub struct UserAuthToken(String);
 
@@ -199,28 +581,50 @@ impl From<String> for UserAuthToken {
 
 }
 
-#[cfg_attr(feature = "rocket", rocket::async_trait)]
-#[cfg(feature = "rocket")]
-impl<'r> FromRequest<'r> for UserAuthToken {
-    type Error = ();
-
-    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
-        match req.cookies().get_private("user_auth_token") {
-            Some(cookie) => request::Outcome::Success(
-                UserAuthToken(String::from(cookie.value()))
-            ),
-            None => request::Outcome::Failure((Status::Unauthorized, ()))
-        }
-    }
-}
-
 /// Match record data reported from client
 #[derive(Copy, Clone, Debug, Deserialize, Serialize)]
 pub struct ClientMatchData {
     pub game_id: GameType,
     pub cpu_level: CpuLevel,
     pub moves: i32,
-    pub result: MatchResult
+    pub result: MatchResult,
+    #[serde(default)]
+    pub first_mover: FirstMover,
+    pub columns: i32,
+    pub rows: i32
+}
+
+/// Match record reported from client as part of a batch
+/// 
+/// The idempotency key is chosen by the client (unique among its user's records)
+/// so a record sent again after a dropped response is only added once
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BatchMatchData {
+    pub idempotency_key: String,
+    #[serde(flatten)]
+    pub record: ClientMatchData
+}
+
+/// What happened to a record of a batch
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BatchRecordStatus {
+    /// The record was added
+    Added,
+    /// A record with the same idempotency key was already added
+    Duplicate,
+    /// The record failed validation and was not added
+    Invalid
+}
+
+/// Result of a record of a batch (in the same order as the batch)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchRecordResult {
+    pub idempotency_key: String,
+    pub status: BatchRecordStatus,
+    /// Achievements the record unlocked (empty unless it was added)
+    pub unlocked: Vec<UnlockedAchievement>,
+    /// Why the record failed validation (None unless it's invalid)
+    pub errors: Option<crate::validation::ValidationErrors>
 }
 
 /// Match record taken from database
@@ -232,7 +636,51 @@ pub struct MatchRecord {
     pub game_id: GameType,
     pub cpu_level: CpuLevel,
     pub moves: i32,
-    pub result: MatchResult
+    pub result: MatchResult,
+    pub first_mover: FirstMover,
+    /// Board size, None for matches recorded before board sizes were
+    pub columns: Option<i32>,
+    pub rows: Option<i32>
+}
+
+/// File format of a match record export
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "rocket", derive(FromFormField))]
+pub enum ExportFormat {
+    /// Comma separated values with a header row
+    #[default]
+    Csv,
+    /// One JSON serialized [MatchRecord] per line
+    Jsonl
+}
+
+impl ExportFormat {
+    /// File extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl"
+        }
+    }
+}
+
+/// Rating of a user at a point in time
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct RatingPoint {
+    pub rating: f64,
+    #[serde(with = "ts_seconds")]
+    pub recorded_at: DateTime<Utc>
+}
+
+/// Rating of a user for a game along with how it changed (see [crate::rating])
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct UserRating {
+    pub game_id: GameType,
+    pub rating: f64,
+    /// Number of matches the rating is based on
+    pub matches: i32,
+    /// Ratings after each of the most recent matches (oldest first)
+    pub history: Vec<RatingPoint>
 }
 
 /// Partial list data for query from database
@@ -240,7 +688,235 @@ pub struct MatchRecord {
 pub struct Records<T> {
     pub records: Vec<T>,
     pub offset: i64,
-    pub total_count: i64
+    /// Number of records in the whole list (None if they weren't counted)
+    pub total_count: Option<i64>,
+    /// Cursor to get the next page with (None on the last page or if the list is paged by offset)
+    #[serde(default)]
+    pub next_cursor: Option<String>
+}
+
+/// Time period a leaderboard covers (counting back from now)
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "rocket", derive(FromFormField))]
+pub enum LeaderboardPeriod {
+    Day,
+    Week,
+    Month,
+    #[default]
+    All
+}
+
+impl LeaderboardPeriod {
+    /// How far back the period goes (None for all time)
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        match self {
+            LeaderboardPeriod::Day => Some(chrono::Duration::days(1)),
+            LeaderboardPeriod::Week => Some(chrono::Duration::weeks(1)),
+            LeaderboardPeriod::Month => Some(chrono::Duration::days(30)),
+            LeaderboardPeriod::All => None
+        }
+    }
+}
+
+/// Query args of the leaderboard route
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "rocket", derive(FromForm))]
+pub struct LeaderboardQuery {
+    pub game: GameType,
+    pub level: Option<CpuLevel>,
+    pub board_size: Option<BoardSize>,
+    pub period: Option<LeaderboardPeriod>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl LeaderboardQuery {
+    /// Number of entries on a page (10 by default, at most 100)
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(10).clamp(1, 100)
+    }
+
+    /// Number of entries skipped
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// Earliest time a match counts from (None for all time)
+    pub fn since(&self, now: DateTime<Utc>) -> Option<NaiveDateTime> {
+        self.period.unwrap_or_default()
+            .duration()
+            .map(|duration| (now - duration).naive_utc())
+    }
+}
+
+/// Results of a single user on a leaderboard
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct LeaderboardEntry {
+    /// Position on the leaderboard (starting at 1)
+    pub rank: i64,
+    pub user_id: String,
+    pub wins: i64,
+    pub losses: i64,
+    pub ties: i64,
+    /// Fraction of matches won (0 to 1)
+    pub win_rate: f64,
+    /// Fewest moves taken to win (None if never won)
+    pub best_win: Option<i32>
+}
+
+/// Page of a leaderboard along with the requesting user's own entry
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Leaderboard {
+    pub ranking: Records<LeaderboardEntry>,
+    /// Entry of the logged in user (None for guests or users without matches)
+    pub own_entry: Option<LeaderboardEntry>
+}
+
+/// Results of a user against a CPU level
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct LevelStats {
+    pub cpu_level: CpuLevel,
+    pub wins: i64,
+    pub losses: i64,
+    pub ties: i64
+}
+
+/// Run of consecutive matches with the same result
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct Streak {
+    pub result: MatchResult,
+    pub length: i64
+}
+
+impl Streak {
+
+    /// Streak at the end of a list of results (oldest first)
+    pub fn current(results: &[MatchResult]) -> Option<Self> {
+        let result = *results.last()?;
+        let length = results.iter().rev().take_while(|&&other| other == result).count() as i64;
+        Some(Streak { result, length })
+    }
+
+    /// Longest run of a result in a list of results (oldest first)
+    pub fn longest(results: &[MatchResult], result: MatchResult) -> i64 {
+        results.split(|&other| other != result)
+            .map(|run| run.len() as i64)
+            .max()
+            .unwrap_or(0)
+    }
+
+}
+
+/// Statistics of a user for a game
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct GameStats {
+    pub game_id: GameType,
+    /// Results against each CPU level played
+    pub levels: Vec<LevelStats>,
+    /// Streak the user is currently on
+    pub current_streak: Option<Streak>,
+    /// Most matches won in a row
+    pub longest_win_streak: i64,
+    /// Average number of moves taken to win (None if never won)
+    pub average_moves_to_win: Option<f64>
+}
+
+/// Number of matches a user played on a day (UTC)
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct DailyActivity {
+    pub day: NaiveDate,
+    pub matches: i64
+}
+
+/// Length of the periods matches are grouped into for aggregate statistics (UTC)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "rocket", derive(FromFormField))]
+pub enum StatsGroupBy {
+    #[default]
+    Day,
+    /// Weeks starting on Monday
+    Week,
+    Month
+}
+
+/// Results of every match that finished during a period
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct StatsBucket {
+    /// First day of the period (UTC)
+    pub start: NaiveDate,
+    pub wins: i64,
+    pub losses: i64,
+    pub ties: i64,
+    /// Average number of moves taken in a match
+    pub average_moves: f64
+}
+
+/// Summary of a user's match history
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct UserStats {
+    pub user_id: String,
+    /// Statistics of each game played
+    pub games: Vec<GameStats>,
+    /// Matches played on each recent day (oldest first)
+    pub activity: Vec<DailyActivity>
+}
+
+/// Achievement unlocked by a user
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct UnlockedAchievement {
+    pub achievement: Achievement,
+    #[serde(with = "ts_seconds")]
+    pub unlocked_at: DateTime<Utc>
+}
+
+/// Progress of a user on a daily puzzle
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct PuzzleProgress {
+    /// Number of solutions submitted (including the one that solved it)
+    pub attempts: i32,
+    pub solved: bool,
+    /// Seconds from starting the puzzle to solving it (None until solved)
+    pub solve_seconds: Option<i64>
+}
+
+/// Puzzle of the day (see [crate::game::Puzzle])
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct DailyPuzzle {
+    /// Day the puzzle is for (UTC)
+    pub day: NaiveDate,
+    pub game_id: GameType,
+    pub columns: i32,
+    pub rows: i32,
+    /// Number of moves the player has to win in
+    pub win_in: u32,
+    /// Moves leading to the puzzle position, written as `<column>[T|O]`
+    /// (see [crate::game::PossibleMove])
+    pub setup: Vec<String>,
+    /// Progress of the logged in user (None for guests and until they start the puzzle)
+    pub progress: Option<PuzzleProgress>
+}
+
+/// Solution submitted for the puzzle of the day
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct PuzzleAttempt {
+    /// Day of the puzzle being solved, so a solution for yesterday's
+    /// puzzle isn't checked against today's
+    pub day: NaiveDate,
+    /// Moves of the player written as `<column>[T|O]`
+    /// (the opponent's replies are played by the server)
+    pub moves: Vec<String>
+}
+
+/// User that solved a daily puzzle
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct PuzzleLeaderboardEntry {
+    /// Position on the leaderboard (starting at 1)
+    pub rank: i64,
+    pub user_id: String,
+    /// Seconds from starting the puzzle to solving it
+    pub solve_seconds: i64,
+    /// Number of solutions submitted (including the one that solved it)
+    pub attempts: i32
 }
 
 /// User authentication form
@@ -251,6 +927,77 @@ pub struct UserAuthForm {
     pub password: String,
 }
 
+/// Password change form
+#[derive(Debug, Eq, PartialEq, Hash, Serialize)]
+#[cfg_attr(feature = "rocket", derive(FromForm))]
+pub struct PasswordChangeForm {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// Account deletion form (the password is asked for again to confirm)
+#[derive(Debug, Eq, PartialEq, Hash, Serialize)]
+#[cfg_attr(feature = "rocket", derive(FromForm))]
+pub struct AccountDeleteForm {
+    pub password: String,
+}
+
+/// Action performed by an admin, as recorded in the audit log
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct AuditLogEntry {
+    /// Admin that performed the action
+    pub admin_id: String,
+    /// Name of the action (such as "delete_user")
+    pub action: String,
+    /// User or record the action was performed on
+    pub target: String,
+    #[serde(with = "ts_seconds")]
+    pub performed_at: DateTime<Utc>,
+}
+
+/// API token creation form
+#[derive(Debug, Eq, PartialEq, Hash, Serialize)]
+#[cfg_attr(feature = "rocket", derive(FromForm))]
+pub struct ApiTokenForm {
+    pub name: String,
+    pub read_records: bool,
+    pub submit_records: bool,
+}
+
+impl ApiTokenForm {
+    /// Scopes selected in the form
+    pub fn scopes(&self) -> Vec<TokenScope> {
+        let mut scopes = Vec::new();
+        if self.read_records {
+            scopes.push(TokenScope::ReadRecords);
+        }
+        if self.submit_records {
+            scopes.push(TokenScope::SubmitRecords);
+        }
+        scopes
+    }
+}
+
+/// API token as listed to its owner (the token itself is only shown when created)
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ApiTokenInfo {
+    pub id: i32,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    #[serde(with = "ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "ts_seconds_option")]
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// Newly created API token
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct NewApiToken {
+    /// Token to send in the Authorization header (`Bearer <token>`)
+    pub token: String,
+    pub info: ApiTokenInfo,
+}
+
 /// Client helper function
 pub trait ToQueryPairs {
     type Output: serde::Serialize;
@@ -261,4 +1008,77 @@ pub trait ToQueryPairs {
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct UserInfo {
     pub user_id: String
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(sort_by: MatchQuerySortBy, asc: bool) -> MatchRecordCursor {
+        MatchRecordCursor {
+            sort_by,
+            asc,
+            moves: 12,
+            finished_at: NaiveDate::from_ymd(2026, 10, 19).and_hms_nano(4, 34, 45, 123_456_789),
+            id: 42
+        }
+    }
+
+    /// Hex encode cursor fields the way [MatchRecordCursor] writes them
+    fn encode(fields: &str) -> String {
+        fields.bytes().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        for sort_by in [MatchQuerySortBy::StartTime, MatchQuerySortBy::Duration] {
+            for asc in [true, false] {
+                let original = cursor(sort_by, asc);
+                let written = original.to_string();
+
+                assert_eq!(written.parse::<MatchRecordCursor>(), Ok(original));
+                assert_eq!(MatchRecordCursor::parse_for(&written, sort_by, asc), Some(original));
+            }
+        }
+
+        // Times before the epoch keep their sub-second part
+        let early = MatchRecordCursor {
+            finished_at: NaiveDate::from_ymd(1969, 12, 31).and_hms_nano(23, 59, 59, 500_000_000),
+            ..cursor(MatchQuerySortBy::StartTime, true)
+        };
+        assert_eq!(early.to_string().parse::<MatchRecordCursor>(), Ok(early));
+    }
+
+    #[test]
+    fn cursor_rejects_malformed_input() {
+        let written = cursor(MatchQuerySortBy::StartTime, false).to_string();
+
+        // Odd length
+        assert!(written[1..].parse::<MatchRecordCursor>().is_err());
+        // Not hex
+        assert!(format!("zz{}", &written[2..]).parse::<MatchRecordCursor>().is_err());
+        assert!(format!("é{}", &written[2..]).parse::<MatchRecordCursor>().is_err());
+        // Empty
+        assert!("".parse::<MatchRecordCursor>().is_err());
+    }
+
+    #[test]
+    fn cursor_rejects_wrong_fields() {
+        assert!(encode("sd 12 0 42").parse::<MatchRecordCursor>().is_ok());
+
+        // Extra fields or order letters
+        assert!(encode("sd 12 0 42 7").parse::<MatchRecordCursor>().is_err());
+        assert!(encode("sdd 12 0 42").parse::<MatchRecordCursor>().is_err());
+        // Missing or unknown fields
+        assert!(encode("sd 12 0").parse::<MatchRecordCursor>().is_err());
+        assert!(encode("xd 12 0 42").parse::<MatchRecordCursor>().is_err());
+        assert!(encode("sd twelve 0 42").parse::<MatchRecordCursor>().is_err());
+    }
+
+    #[test]
+    fn cursor_only_parses_for_its_sort_order() {
+        let written = cursor(MatchQuerySortBy::Duration, true).to_string();
+
+        assert_eq!(MatchRecordCursor::parse_for(&written, MatchQuerySortBy::Duration, false), None);
+        assert_eq!(MatchRecordCursor::parse_for(&written, MatchQuerySortBy::StartTime, true), None);
+    }
+}
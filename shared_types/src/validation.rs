@@ -0,0 +1,492 @@
+//! Validation rules for user input
+//!
+//! Shared by the server, client and CLI so they all enforce the same rules
+
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use caseless::Caseless;
+use unicode_normalization::UnicodeNormalization;
+
+/// Minimum number of characters in a username
+pub const USERNAME_MIN_LENGTH: usize = 3;
+/// Maximum number of characters in a username
+pub const USERNAME_MAX_LENGTH: usize = 32;
+/// Minimum number of characters in a password
+pub const PASSWORD_MIN_LENGTH: usize = 8;
+/// Maximum number of characters in a password
+pub const PASSWORD_MAX_LENGTH: usize = 128;
+
+/// Symbols allowed in a username (along with any letter or number)
+pub const USERNAME_SYMBOLS: [char; 3] = ['_', '-', '.'];
+
+/// Usernames that can't be registered (compared using [username_key])
+pub const RESERVED_USERNAMES: [&str; 10] = [
+    "admin",
+    "administrator",
+    "anonymous",
+    "cpu",
+    "guest",
+    "moderator",
+    "null",
+    "root",
+    "support",
+    "system",
+];
+
+/// Name of the username field in [crate::types::UserAuthForm]
+pub const USERNAME_FIELD: &str = "user_id";
+/// Name of the password field in [crate::types::UserAuthForm]
+pub const PASSWORD_FIELD: &str = "password";
+/// Name of the new password field in [crate::types::PasswordChangeForm]
+pub const NEW_PASSWORD_FIELD: &str = "new_password";
+
+/// Maximum number of characters in the name of an API token
+pub const TOKEN_NAME_MAX_LENGTH: usize = 64;
+
+/// Name of the name field in [crate::types::ApiTokenForm]
+pub const TOKEN_NAME_FIELD: &str = "name";
+
+/// Field reported when no scope is selected in [crate::types::ApiTokenForm]
+pub const TOKEN_SCOPES_FIELD: &str = "scopes";
+
+/// Smallest number of columns or rows of a board
+pub const BOARD_MIN_DIMENSION: usize = 4;
+/// Largest number of columns or rows of a board
+pub const BOARD_MAX_DIMENSION: usize = 10;
+
+/// Name of the columns field in [crate::types::ClientMatchData]
+pub const COLUMNS_FIELD: &str = "columns";
+/// Name of the rows field in [crate::types::ClientMatchData]
+pub const ROWS_FIELD: &str = "rows";
+/// Name of the moves field in [crate::types::ClientMatchData]
+pub const MOVES_FIELD: &str = "moves";
+
+/// Fewest moves of the player that can win a game of Connect 4 (four discs in a row)
+pub const CONNECT_4_MIN_WIN_MOVES: usize = 4;
+/// Fewest moves of the player that can win a game of TOOT and OTTO
+/// 
+/// The opponent's letters count towards TOOT, so two of the player's letters can complete it
+pub const TOOT_AND_OTTO_MIN_WIN_MOVES: usize = 2;
+
+/// Maximum number of characters in the idempotency key of a [crate::types::BatchMatchData]
+pub const IDEMPOTENCY_KEY_MAX_LENGTH: usize = 64;
+/// Maximum number of records in a batch
+pub const BATCH_MAX_RECORDS: usize = 100;
+
+/// Name of the idempotency key field in [crate::types::BatchMatchData]
+pub const IDEMPOTENCY_KEY_FIELD: &str = "idempotency_key";
+/// Field reported when a batch has too few or too many records
+pub const RECORDS_FIELD: &str = "records";
+
+/// Name of the moves field in [crate::types::PuzzleAttempt]
+pub const PUZZLE_MOVES_FIELD: &str = "moves";
+
+/// Name of the before timestamp query arg of the match record and stats routes
+pub const BEFORE_FIELD: &str = "before";
+/// Name of the after timestamp query arg of the match record and stats routes
+pub const AFTER_FIELD: &str = "after";
+
+/// Reason a field failed validation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ValidationError {
+    Empty,
+    TooShort { min: usize },
+    TooLong { max: usize },
+    OutOfRange { min: usize, max: usize },
+    InvalidCharacters,
+    Reserved,
+    Taken,
+    Weak,
+    ContainsUsername,
+    IllegalMoves,
+    InvalidTime,
+}
+
+impl std::fmt::Display for ValidationError {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            ValidationError::Empty => write!(f, "must not be empty"),
+            ValidationError::TooShort { min } => write!(f, "must be at least {} characters long", min),
+            ValidationError::TooLong { max } => write!(f, "must be at most {} characters long", max),
+            ValidationError::OutOfRange { min, max } => write!(f, "must be between {} and {}", min, max),
+            ValidationError::InvalidCharacters => write!(f, "may only contain letters, numbers, '_', '-' and '.'"),
+            ValidationError::Reserved => write!(f, "is reserved"),
+            ValidationError::Taken => write!(f, "is already in use"),
+            ValidationError::Weak => write!(f, "must contain both letters and numbers or symbols"),
+            ValidationError::ContainsUsername => write!(f, "must not contain the username"),
+            ValidationError::IllegalMoves => write!(f, "must only contain moves that can be played"),
+            ValidationError::InvalidTime => write!(f, "must be a valid timestamp"),
+        }
+    }
+
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Validation error for a single field of a form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    #[serde(flatten)]
+    pub error: ValidationError,
+}
+
+/// Validation errors for every field of a form that failed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationErrors {
+    pub field_errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+
+    /// Add an error for the field if the result failed
+    pub fn check(&mut self, field: &str, result: Result<(), ValidationError>) {
+        if let Err(error) = result {
+            self.field_errors.push(FieldError { field: field.to_string(), error });
+        }
+    }
+
+    /// Get the error for a field (if any)
+    pub fn get(&self, field: &str) -> Option<&ValidationError> {
+        self.field_errors.iter()
+            .find(|field_error| field_error.field == field)
+            .map(|field_error| &field_error.error)
+    }
+
+    /// Returns true if no field failed
+    pub fn is_empty(&self) -> bool {
+        self.field_errors.is_empty()
+    }
+
+    /// Ok if no field failed, otherwise returns the errors
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+}
+
+/// Canonical form of a username used to check uniqueness and look users up
+/// 
+/// The username is case folded and NFKC normalised the way Unicode compatibility caseless
+/// matching does it (folding twice since normalising can undo it) so "Alice", "ALICE",
+/// "ａｌｉｃｅ" are the same user and so are "straße" and "STRASSE"
+pub fn username_key(username: &str) -> String {
+    username.nfd().default_case_fold().nfkd().default_case_fold().nfkc().collect()
+}
+
+/// Check that the username is allowed to be registered
+pub fn validate_username(username: &str) -> Result<(), ValidationError> {
+    let length = username.chars().count();
+
+    if length == 0 {
+        Err(ValidationError::Empty)
+    } else if length < USERNAME_MIN_LENGTH {
+        Err(ValidationError::TooShort { min: USERNAME_MIN_LENGTH })
+    } else if length > USERNAME_MAX_LENGTH {
+        Err(ValidationError::TooLong { max: USERNAME_MAX_LENGTH })
+    } else if !username.chars().all(|c| c.is_alphanumeric() || USERNAME_SYMBOLS.contains(&c)) {
+        Err(ValidationError::InvalidCharacters)
+    } else if RESERVED_USERNAMES.contains(&username_key(username).as_str()) {
+        Err(ValidationError::Reserved)
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that the password is strong enough to be used by the user
+/// 
+/// The username is looked for using [username_key] so any spelling of it that would
+/// log in as the user is caught
+pub fn validate_password(username: &str, password: &str) -> Result<(), ValidationError> {
+    let length = password.chars().count();
+
+    // Passwords need letters along with something that isn't a letter
+    let has_letter = password.chars().any(char::is_alphabetic);
+    let has_other = password.chars().any(|c| !c.is_alphabetic());
+
+    if length == 0 {
+        Err(ValidationError::Empty)
+    } else if length < PASSWORD_MIN_LENGTH {
+        Err(ValidationError::TooShort { min: PASSWORD_MIN_LENGTH })
+    } else if length > PASSWORD_MAX_LENGTH {
+        Err(ValidationError::TooLong { max: PASSWORD_MAX_LENGTH })
+    } else if !has_letter || !has_other {
+        Err(ValidationError::Weak)
+    } else if !username.is_empty() && username_key(password).contains(&username_key(username)) {
+        Err(ValidationError::ContainsUsername)
+    } else {
+        Ok(())
+    }
+}
+
+/// Check the username and password of a new user
+pub fn validate_new_user(username: &str, password: &str) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    errors.check(USERNAME_FIELD, validate_username(username));
+    errors.check(PASSWORD_FIELD, validate_password(username, password));
+
+    errors.into_result()
+}
+
+/// Check the name and scopes of a new API token
+pub fn validate_new_token(name: &str, scopes: &[crate::types::TokenScope]) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    errors.check(TOKEN_NAME_FIELD, if name.trim().is_empty() {
+        Err(ValidationError::Empty)
+    } else if name.chars().count() > TOKEN_NAME_MAX_LENGTH {
+        Err(ValidationError::TooLong { max: TOKEN_NAME_MAX_LENGTH })
+    } else {
+        Ok(())
+    });
+
+    errors.check(TOKEN_SCOPES_FIELD, if scopes.is_empty() {
+        Err(ValidationError::Empty)
+    } else {
+        Ok(())
+    });
+
+    errors.into_result()
+}
+
+/// Fewest moves of the player that can win a game
+pub fn min_win_moves(game: crate::types::GameType) -> usize {
+    match game {
+        crate::types::GameType::Connect4 => CONNECT_4_MIN_WIN_MOVES,
+        crate::types::GameType::OttoToot => TOOT_AND_OTTO_MIN_WIN_MOVES
+    }
+}
+
+/// Check the board size and number of moves of a reported match
+/// 
+/// Moves only count the player's own, so at most half the cells (rounded up) can be theirs,
+/// and a win takes at least [min_win_moves]
+pub fn validate_match_data(record: &crate::types::ClientMatchData) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    let check_dimension = |value: i32| {
+        if (BOARD_MIN_DIMENSION as i32..=BOARD_MAX_DIMENSION as i32).contains(&value) {
+            Ok(())
+        } else {
+            Err(ValidationError::OutOfRange { min: BOARD_MIN_DIMENSION, max: BOARD_MAX_DIMENSION })
+        }
+    };
+
+    errors.check(COLUMNS_FIELD, check_dimension(record.columns));
+    errors.check(ROWS_FIELD, check_dimension(record.rows));
+
+    // Every move fills a cell, taking turns with the opponent (the largest board is assumed if the size is invalid)
+    let cells = if errors.is_empty() {
+        (record.columns * record.rows) as usize
+    } else {
+        BOARD_MAX_DIMENSION * BOARD_MAX_DIMENSION
+    };
+    let max = (cells + 1) / 2;
+    let min = match record.result {
+        crate::types::MatchResult::Win => min_win_moves(record.game_id),
+        _ => 1
+    };
+    errors.check(MOVES_FIELD, if (min as i32..=max as i32).contains(&record.moves) {
+        Ok(())
+    } else {
+        Err(ValidationError::OutOfRange { min, max })
+    });
+
+    errors.into_result()
+}
+
+/// Convert the before and after timestamps (in seconds) of a query into times
+/// 
+/// Fails for timestamps too far from the epoch to be represented
+pub fn validate_time_range(
+    before: Option<i64>,
+    after: Option<i64>
+) -> Result<(Option<NaiveDateTime>, Option<NaiveDateTime>), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    let mut to_time = |field: &str, timestamp: Option<i64>| {
+        let time = timestamp.map(|timestamp| NaiveDateTime::from_timestamp_opt(timestamp, 0));
+        errors.check(field, match time {
+            Some(None) => Err(ValidationError::InvalidTime),
+            _ => Ok(())
+        });
+        time.flatten()
+    };
+
+    let before = to_time(BEFORE_FIELD, before);
+    let after = to_time(AFTER_FIELD, after);
+
+    errors.into_result().map(|_| (before, after))
+}
+
+/// Check that a record of a batch is valid, including its idempotency key
+pub fn validate_batch_match_data(entry: &crate::types::BatchMatchData) -> Result<(), ValidationErrors> {
+    let mut errors = validate_match_data(&entry.record).err().unwrap_or_default();
+
+    let length = entry.idempotency_key.chars().count();
+    errors.check(IDEMPOTENCY_KEY_FIELD, if length == 0 {
+        Err(ValidationError::Empty)
+    } else if length > IDEMPOTENCY_KEY_MAX_LENGTH {
+        Err(ValidationError::TooLong { max: IDEMPOTENCY_KEY_MAX_LENGTH })
+    } else {
+        Ok(())
+    });
+
+    errors.into_result()
+}
+
+/// Check that a batch has at least one record and at most [BATCH_MAX_RECORDS]
+pub fn validate_batch_size(entries: &[crate::types::BatchMatchData]) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    errors.check(RECORDS_FIELD, if (1..=BATCH_MAX_RECORDS).contains(&entries.len()) {
+        Ok(())
+    } else {
+        Err(ValidationError::OutOfRange { min: 1, max: BATCH_MAX_RECORDS })
+    });
+
+    errors.into_result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ClientMatchData, CpuLevel, FirstMover, GameType, MatchResult};
+
+    fn match_data(game_id: GameType, result: MatchResult, moves: i32, columns: i32, rows: i32) -> ClientMatchData {
+        ClientMatchData {
+            game_id,
+            cpu_level: CpuLevel::Hard,
+            moves,
+            result,
+            first_mover: FirstMover::Player,
+            columns,
+            rows
+        }
+    }
+
+    fn moves_error(record: &ClientMatchData) -> Option<ValidationError> {
+        validate_match_data(record).err()?
+            .field_errors.into_iter()
+            .find(|error| error.field == MOVES_FIELD)
+            .map(|error| error.error)
+    }
+
+    #[test]
+    fn username_key_folds_case_and_width() {
+        assert_eq!(username_key("Alice"), "alice");
+        assert_eq!(username_key("ALICE"), "alice");
+        assert_eq!(username_key("ａｌｉｃｅ"), "alice");
+        assert_eq!(username_key("straße"), username_key("STRASSE"));
+        assert_ne!(username_key("alice"), username_key("alise"));
+    }
+
+    #[test]
+    fn reserved_usernames_are_compared_by_key() {
+        for username in ["admin", "Admin", "ROOT", "ＡＤＭＩＮ", "System"] {
+            assert_eq!(validate_username(username), Err(ValidationError::Reserved), "{}", username);
+        }
+        assert_eq!(validate_username("admin2"), Ok(()));
+    }
+
+    #[test]
+    fn username_length_and_characters() {
+        assert_eq!(validate_username(""), Err(ValidationError::Empty));
+        assert_eq!(validate_username("ab"), Err(ValidationError::TooShort { min: USERNAME_MIN_LENGTH }));
+        assert_eq!(validate_username(&"a".repeat(USERNAME_MAX_LENGTH + 1)), Err(ValidationError::TooLong { max: USERNAME_MAX_LENGTH }));
+        assert_eq!(validate_username(&"a".repeat(USERNAME_MAX_LENGTH)), Ok(()));
+        // Lengths count characters, not bytes
+        assert_eq!(validate_username("éèê"), Ok(()));
+
+        for username in ["two words", "at@sign", "semi;colon", "slash/"] {
+            assert_eq!(validate_username(username), Err(ValidationError::InvalidCharacters), "{}", username);
+        }
+        assert_eq!(validate_username("under_score-dot.9"), Ok(()));
+    }
+
+    #[test]
+    fn password_length_and_strength() {
+        assert_eq!(validate_password("alice", ""), Err(ValidationError::Empty));
+        assert_eq!(validate_password("alice", "Pass123"), Err(ValidationError::TooShort { min: PASSWORD_MIN_LENGTH }));
+        assert_eq!(
+            validate_password("alice", &"a1".repeat(PASSWORD_MAX_LENGTH / 2 + 1)),
+            Err(ValidationError::TooLong { max: PASSWORD_MAX_LENGTH })
+        );
+        assert_eq!(validate_password("alice", &"a1".repeat(PASSWORD_MAX_LENGTH / 2)), Ok(()));
+
+        assert_eq!(validate_password("alice", "onlyletters"), Err(ValidationError::Weak));
+        assert_eq!(validate_password("alice", "12345678"), Err(ValidationError::Weak));
+        assert_eq!(validate_password("alice", "Password 123"), Ok(()));
+    }
+
+    #[test]
+    fn password_containing_the_username_is_compared_by_key() {
+        assert_eq!(validate_password("alice", "myALICE123"), Err(ValidationError::ContainsUsername));
+        assert_eq!(validate_password("Alice", "ａｌｉｃｅ2024!"), Err(ValidationError::ContainsUsername));
+        assert_eq!(validate_password("straße", "STRASSE2024"), Err(ValidationError::ContainsUsername));
+        assert_eq!(validate_password("alice", "Password123"), Ok(()));
+    }
+
+    #[test]
+    fn moves_capped_at_half_the_cells() {
+        // 7x6 has 42 cells, so the player makes at most 21 moves
+        assert_eq!(moves_error(&match_data(GameType::Connect4, MatchResult::Tie, 21, 7, 6)), None);
+        assert_eq!(
+            moves_error(&match_data(GameType::Connect4, MatchResult::Tie, 22, 7, 6)),
+            Some(ValidationError::OutOfRange { min: 1, max: 21 })
+        );
+
+        // An odd number of cells rounds up since the player may have moved first
+        assert_eq!(moves_error(&match_data(GameType::OttoToot, MatchResult::Tie, 25, 7, 7)), None);
+        assert_eq!(
+            moves_error(&match_data(GameType::OttoToot, MatchResult::Tie, 26, 7, 7)),
+            Some(ValidationError::OutOfRange { min: 1, max: 25 })
+        );
+
+        // The largest board is assumed when the size itself is invalid
+        assert_eq!(
+            moves_error(&match_data(GameType::Connect4, MatchResult::Loss, 51, 11, 6)),
+            Some(ValidationError::OutOfRange { min: 1, max: 50 })
+        );
+    }
+
+    #[test]
+    fn wins_need_a_game_specific_minimum() {
+        assert_eq!(
+            moves_error(&match_data(GameType::Connect4, MatchResult::Win, 1, 7, 6)),
+            Some(ValidationError::OutOfRange { min: CONNECT_4_MIN_WIN_MOVES, max: 21 })
+        );
+        assert_eq!(moves_error(&match_data(GameType::Connect4, MatchResult::Win, 4, 7, 6)), None);
+
+        assert_eq!(
+            moves_error(&match_data(GameType::OttoToot, MatchResult::Win, 1, 6, 4)),
+            Some(ValidationError::OutOfRange { min: TOOT_AND_OTTO_MIN_WIN_MOVES, max: 12 })
+        );
+        assert_eq!(moves_error(&match_data(GameType::OttoToot, MatchResult::Win, 2, 6, 4)), None);
+
+        // Losses and ties only need a move
+        assert_eq!(moves_error(&match_data(GameType::Connect4, MatchResult::Loss, 1, 7, 6)), None);
+        assert_eq!(
+            moves_error(&match_data(GameType::Connect4, MatchResult::Loss, 0, 7, 6)),
+            Some(ValidationError::OutOfRange { min: 1, max: 21 })
+        );
+    }
+
+    #[test]
+    fn time_range_rejects_unrepresentable_timestamps() {
+        let (before, after) = validate_time_range(Some(1_792_387_315), None).unwrap();
+        assert_eq!(before.map(|time| time.timestamp()), Some(1_792_387_315));
+        assert_eq!(after, None);
+
+        let errors = validate_time_range(Some(i64::MAX), Some(i64::MIN)).unwrap_err();
+        assert_eq!(errors.get(BEFORE_FIELD), Some(&ValidationError::InvalidTime));
+        assert_eq!(errors.get(AFTER_FIELD), Some(&ValidationError::InvalidTime));
+    }
+}